@@ -1,48 +1,75 @@
 // src/agent.rs
-use tokio::sync::mpsc;
-use anyhow::{Result, anyhow};
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::Arc;
+use tokio::sync::{broadcast, mpsc};
+use anyhow::Result;
 use serde_json::Value;
 use crate::{
+    config::expand_home,
+    content::{render_block, render_contents, render_result, summarize_result},
+    error::McpError,
     llm::{LlmClient, Message},
-    client::McpClient,
-    tui::UiMessage,
+    protocol::{CallToolResult, LoggingLevel, ProgressNotification},
+    registry::ServerRegistry,
+    tui::{AgentCommand, UiMessage},
 };
 
 pub struct Agent {
     // The "Brain" needs to talk to the "Face" (UI)
     tx_ui: mpsc::UnboundedSender<UiMessage>,
     // The "Brain" needs to listen to the User
-    rx_agent: mpsc::UnboundedReceiver<String>,
+    rx_agent: mpsc::UnboundedReceiver<AgentCommand>,
+    // ...and to the stop button while a tool runs
+    rx_abort: mpsc::UnboundedReceiver<()>,
     // Dependencies
-    client: McpClient,
+    registry: Arc<ServerRegistry>,
     llm: LlmClient,
 }
 
 impl Agent {
     pub fn new(
         tx_ui: mpsc::UnboundedSender<UiMessage>,
-        rx_agent: mpsc::UnboundedReceiver<String>,
-        client: McpClient,
+        rx_agent: mpsc::UnboundedReceiver<AgentCommand>,
+        rx_abort: mpsc::UnboundedReceiver<()>,
+        registry: Arc<ServerRegistry>,
         llm: LlmClient,
     ) -> Self {
-        Self { tx_ui, rx_agent, client, llm }
+        Self { tx_ui, rx_agent, rx_abort, registry, llm }
     }
 
     pub async fn run(mut self) {
         // Log startup
         self.log("Agent System Online.");
         
-        // 1. Load Tools
-        let tools = match self.client.list_tools().await {
-            Ok(t) => {
-                self.log(&format!("Tools Discovered: {}", t.len()));
-                t
-            },
-            Err(e) => {
-                self.error(&format!("Critical Tool Failure: {}", e));
-                return; // Stop the agent safely
-            }
-        };
+        // 1. Load Tools (the registry already fetched them from every server)
+        for (name, reason) in self.registry.failures() {
+            self.error(&format!("Server '{}' failed to start: {}", name, reason));
+        }
+        if self.registry.server_names().is_empty() {
+            self.error("Critical Tool Failure: no MCP servers are connected");
+            return; // Stop the agent safely
+        }
+        self.log(&format!(
+            "Tools Discovered: {} across {} server(s)",
+            self.registry.tools().len(),
+            self.registry.server_names().len()
+        ));
+
+        // Crashes, restarts and server chatter happen in the background; relay them to the UI
+        relay(self.registry.health_events(), self.tx_ui.clone(), |event| {
+            UiMessage::ServerHealth { server: event.server, health: event.health }
+        });
+        relay(self.registry.stderr_lines(), self.tx_ui.clone(), |stderr| {
+            UiMessage::Log(format!("[{}] {}", stderr.server, stderr.line))
+        });
+        relay(self.registry.server_logs(), self.tx_ui.clone(), |log| {
+            let logger = log.message.logger.as_deref().map(|l| format!(" {}:", l)).unwrap_or_default();
+            UiMessage::Log(format!("[{}] {}{} {}", log.server, log.message.level.as_str().to_uppercase(), logger, log.message.text()))
+        });
+        relay(self.registry.tool_changes(), self.tx_ui.clone(), |change| {
+            UiMessage::Log(format!("TOOLS: '{}' now offers {} tool(s)", change.server, change.count))
+        });
 
         // 2. Initialize History
         let mut history = vec![
@@ -54,25 +81,153 @@ impl Agent {
             }
         ];
 
+        // Resources the user attached: (server, uri). When one changes, the
+        // new version goes into the history too.
+        let mut attached: BTreeSet<(String, String)> = BTreeSet::new();
+        let mut resource_updates = self.registry.resource_updates();
+
         // 3. Main Loop (Waiting for user input)
-        while let Some(user_input) = self.rx_agent.recv().await {
-            self.log("Thinking...");
-            
-            // Add User Input
-            history.push(Message {
-                role: "user".to_string(),
-                content: Some(user_input),
-                tool_calls: None,
-                tool_call_id: None,
-            });
+        loop {
+            let command = tokio::select! {
+                command = self.rx_agent.recv() => match command {
+                    Some(command) => command,
+                    None => break,
+                },
+                Ok(update) = resource_updates.recv() => {
+                    if attached.contains(&(update.server.clone(), update.uri.clone())) {
+                        self.log(&format!("Resource changed: {}", update.uri));
+                        self.attach(&mut history, &update.server, &update.uri).await;
+                    }
+                    continue;
+                }
+            };
 
-            // Run the ReAct Cycle
-            if let Err(e) = self.cycle(&mut history, &tools).await {
-                self.error(&format!("Cycle Error: {}", e));
+            match command {
+                AgentCommand::Prompt(user_input) => {
+                    self.log("Thinking...");
+
+                    // Add User Input
+                    history.push(Message {
+                        role: "user".to_string(),
+                        content: Some(user_input),
+                        tool_calls: None,
+                        tool_call_id: None,
+                    });
+
+                    self.turn(&mut history).await;
+                }
+                AgentCommand::ListResources => {
+                    let _ = self.tx_ui.send(UiMessage::Resources(self.registry.resources()));
+                }
+                AgentCommand::AttachResource { server, uri } => {
+                    if self.attach(&mut history, &server, &uri).await {
+                        // Best effort: not every server can tell us about changes
+                        if self.registry.subscribe_resource(&server, &uri).await.is_ok() {
+                            self.log(&format!("Watching {} for changes", uri));
+                        }
+                        attached.insert((server, uri));
+                    }
+                }
+                AgentCommand::ListPrompts => {
+                    let _ = self.tx_ui.send(UiMessage::Prompts(self.registry.prompts()));
+                }
+                AgentCommand::RunPrompt { server, name, arguments } => {
+                    let prompt = match self.registry.get_prompt(&server, &name, arguments).await {
+                        Ok(prompt) => prompt,
+                        Err(e) => {
+                            self.error(&format!("Prompt '{}' failed: {:#}", name, e));
+                            continue;
+                        }
+                    };
+                    for message in &prompt.messages {
+                        history.push(Message {
+                            role: message.role.clone(),
+                            content: Some(render_block(&message.content)),
+                            tool_calls: None,
+                            tool_call_id: None,
+                        });
+                    }
+                    self.log(&format!("Prompt '{}' added {} message(s)", name, prompt.messages.len()));
+
+                    // A prompt that ends on the user's side is waiting for an answer
+                    if prompt.messages.last().is_some_and(|m| m.role == "user") {
+                        self.log("Thinking...");
+                        self.turn(&mut history).await;
+                    }
+                }
+                AgentCommand::ListRoots => {
+                    let dirs = self.registry.roots().dirs();
+                    if dirs.is_empty() {
+                        self.log("ROOTS: none granted (use /roots add <dir>)");
+                    }
+                    for dir in dirs {
+                        self.log(&format!("ROOT: {}", dir.display()));
+                    }
+                }
+                AgentCommand::AddRoot(dir) => {
+                    let dir = expand_home(Path::new(&dir));
+                    if !dir.is_dir() {
+                        self.error(&format!("Not a directory: {}", dir.display()));
+                    } else if self.registry.roots().add(&dir) {
+                        self.log(&format!("ROOTS: granted {}", dir.display()));
+                    } else {
+                        self.log(&format!("ROOTS: {} is already granted", dir.display()));
+                    }
+                }
+                AgentCommand::RemoveRoot(dir) => {
+                    let dir = expand_home(Path::new(&dir));
+                    match self.registry.roots().remove(&dir) {
+                        true => self.log(&format!("ROOTS: revoked {}", dir.display())),
+                        false => self.error(&format!("Not a root: {}", dir.display())),
+                    }
+                }
+                AgentCommand::SetLogLevel { server, level } => {
+                    let outcome = match level.parse::<LoggingLevel>() {
+                        Ok(level) => self.registry.set_log_level(&server, level).await.map(|()| level),
+                        Err(e) => Err(anyhow::anyhow!(e)),
+                    };
+                    match outcome {
+                        Ok(level) => self.log(&format!("LOGGING: '{}' now logs at {} and above", server, level)),
+                        Err(e) => self.error(&format!("Failed to set the log level of '{}': {:#}", server, e)),
+                    }
+                }
             }
         }
     }
 
+    // Run the ReAct Cycle (a restarted or list_changed server may have new tools)
+    async fn turn(&mut self, history: &mut Vec<Message>) {
+        let tools = self.registry.tools();
+        if let Err(e) = self.cycle(history, &tools).await {
+            self.error(&format!("Cycle Error: {}", e));
+        }
+    }
+
+    // Read a resource and add it to the conversation. Returns false if it couldn't be read.
+    async fn attach(&self, history: &mut Vec<Message>, server: &str, uri: &str) -> bool {
+        let contents = match self.registry.read_resource(server, uri).await {
+            Ok(contents) => contents,
+            Err(e) => {
+                self.error(&format!("Failed to read {}: {:#}", uri, e));
+                return false;
+            }
+        };
+
+        history.push(Message {
+            role: "user".to_string(),
+            content: Some(format!(
+                "Contents of resource {} (from server '{}'):\n{}",
+                uri,
+                server,
+                render_contents(&contents)
+            )),
+            tool_calls: None,
+            tool_call_id: None,
+        });
+        self.log(&format!("Attached {} ({} part(s))", uri, contents.len()));
+        true
+    }
+
     // Isolate the logic for one "Turn" of conversation
     async fn cycle(&mut self, history: &mut Vec<Message>, tools: &[crate::protocol::Tool]) -> Result<()> {
         // A. Ask LLM
@@ -83,20 +238,80 @@ impl Agent {
         if let Some(tool_calls) = response.tool_calls {
             self.log(&format!("Tools Requested: {}", tool_calls.len()));
 
+            // Forget any abort left over from an earlier turn
+            while self.rx_abort.try_recv().is_ok() {}
+            let mut aborted = false;
+
             for call in tool_calls {
+                // Every call still needs a tool message, even if we skip it
+                if aborted {
+                    history.push(Message {
+                        role: "tool".to_string(),
+                        content: Some("Error: skipped, the user aborted this turn".to_string()),
+                        tool_calls: None,
+                        tool_call_id: Some(call.id),
+                    });
+                    continue;
+                }
+
                 self.log(&format!("EXEC: {}({})", call.function.name, call.function.arguments));
                 
-                // Safe Argument Parsing (No unwrap)
-                let args: Value = serde_json::from_str(&call.function.arguments)
-                    .unwrap_or(serde_json::json!({})); 
-
-                // Execute
-                let result_str = match self.client.call_tool(&call.function.name, args).await {
-                    Ok(res) => res.to_string(),
-                    Err(e) => format!("Error: {}", e),
+                // The TUI shows the call (and any progress) until it returns
+                let _ = self.tx_ui.send(UiMessage::ToolStarted { id: call.id.clone(), tool: call.function.name.clone() });
+                let (tx_progress, rx_progress) = mpsc::unbounded_channel();
+                forward_progress(rx_progress, call.id.clone(), self.tx_ui.clone());
+
+                // Execute (racing the stop button; losing the race cancels the request).
+                // Arguments that aren't JSON go back to the LLM instead of the server.
+                let outcome = match parse_arguments(&call.function.arguments) {
+                    Ok(args) => tokio::select! {
+                        res = self.registry.call_tool_with_progress(&call.function.name, args, Some(tx_progress)) => res,
+                        Some(()) = self.rx_abort.recv() => {
+                            aborted = true;
+                            Err(McpError::Cancelled { method: call.function.name.clone() }.into())
+                        }
+                    },
+                    Err(e) => Err(e),
                 };
+                let _ = self.tx_ui.send(UiMessage::ToolFinished { id: call.id.clone() });
 
-                self.log(&format!("RESULT: {}", result_str));
+                let result_str = match outcome {
+                    Ok(res) => match serde_json::from_value::<CallToolResult>(res.clone()) {
+                        Ok(result) => {
+                            // A failed tool shows up red, but the LLM still gets to read why
+                            let summary = format!("RESULT: {}", summarize_result(&result));
+                            match result.is_error {
+                                true => self.error(&summary),
+                                false => self.log(&summary),
+                            }
+                            render_result(&result)
+                        }
+                        // Not a proper CallToolResult; the raw JSON is better than nothing
+                        Err(_) => {
+                            self.log(&format!("RESULT: {}", res));
+                            res.to_string()
+                        }
+                    },
+                    Err(e) => {
+                        match e.downcast_ref::<McpError>() {
+                            Some(McpError::Timeout { .. }) => self.error(&format!("TIMEOUT: {}", e)),
+                            Some(McpError::ResourceLimit { .. }) => self.error(&format!("LIMIT: {}", e)),
+                            _ => {}
+                        }
+                        // Tell the LLM what to change rather than just that it failed
+                        let message = match e.downcast_ref::<McpError>() {
+                            Some(McpError::InvalidArguments { .. }) => {
+                                format!("Error: {}\nFix the arguments to match the tool's inputSchema.", e)
+                            }
+                            Some(McpError::InvalidOutput { .. }) => {
+                                format!("Error: {}\nThe tool's result can't be trusted; don't rely on it.", e)
+                            }
+                            _ => format!("Error: {}", e),
+                        };
+                        self.log(&format!("RESULT: {}", message));
+                        message
+                    }
+                };
 
                 // Add Result to History
                 history.push(Message {
@@ -136,4 +351,49 @@ impl Agent {
     fn send_ai(&self, msg: &str) {
         let _ = self.tx_ui.send(UiMessage::Ai(msg.to_string()));
     }
-}
\ No newline at end of file
+}
+
+// Forward a background event stream to the UI until either side goes away
+fn relay<T: Clone + Send + 'static>(
+    mut events: broadcast::Receiver<T>,
+    tx_ui: mpsc::UnboundedSender<UiMessage>,
+    to_ui: fn(T) -> UiMessage,
+) {
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if tx_ui.send(to_ui(event)).is_err() {
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+// Pass one call's progress updates to the UI; ends when the call returns
+fn forward_progress(
+    mut progress: mpsc::UnboundedReceiver<ProgressNotification>,
+    id: String,
+    tx_ui: mpsc::UnboundedSender<UiMessage>,
+) {
+    tokio::spawn(async move {
+        while let Some(update) = progress.recv().await {
+            if tx_ui.send(UiMessage::ToolProgress { id: id.clone(), progress: update }).is_err() {
+                break;
+            }
+        }
+    });
+}
+
+// The LLM's argument string as JSON. Empty or `null` means "no arguments".
+pub fn parse_arguments(raw: &str) -> Result<Value> {
+    match raw.trim() {
+        "" | "null" => Ok(serde_json::json!({})),
+        raw => serde_json::from_str(raw)
+            .map_err(|e| anyhow::anyhow!("the arguments are not valid JSON ({}); send a JSON object", e)),
+    }
+}
@@ -1,5 +1,9 @@
 // src/bin/mock_tool.rs
-use serde_json::Value;
+use aether::protocol::{
+    ErrorCode, JsonRpcError, JsonRpcErrorResponse, JsonRpcMessage, JsonRpcNotification,
+    JsonRpcRequest, JsonRpcResponse,
+};
+use serde_json::{json, Value};
 use std::io::{self, BufRead, Write};
 
 fn main() {
@@ -13,82 +17,215 @@ fn main() {
         match handle.read_line(&mut line) {
             Ok(0) => break, // EOF
             Ok(_) => {
-                if let Ok(json) = serde_json::from_str::<Value>(&line) {
-                    let id = json["id"].as_u64().unwrap_or(1);
-                    let method = json["method"].as_str().unwrap_or("");
-
-                    // 1. INITIALIZE
-                    if method == "initialize" {
-                        let response = format!(
-                            r#"{{"jsonrpc":"2.0","id":{},"result":{{"protocolVersion":"2024-11-05","capabilities":{{}},"serverInfo":{{"name":"MockTool","version":"1.0"}}}}}}"#,
-                            id
-                        );
-                        send_response(&mut stdout, &response);
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let replies = match serde_json::from_str::<Value>(&line) {
+                    // A batch gets one array back (minus the notifications)
+                    Ok(Value::Array(batch)) if !batch.is_empty() => {
+                        let replies: Vec<JsonRpcMessage> = batch.into_iter().flat_map(answer).collect();
+                        match replies.is_empty() {
+                            true => vec![],
+                            false => vec![serde_json::to_string(&replies).unwrap()],
+                        }
                     }
-                    // 2. LIST TOOLS
-                    else if method == "tools/list" {
-                        let schema = serde_json::json!({
-                            "type": "object",
-                            "properties": {
-                                "a": { "type": "number" },
-                                "b": { "type": "number" }
-                            },
-                            "required": ["a", "b"]
-                        });
+                    Ok(Value::Array(_)) => vec![refuse(ErrorCode::InvalidRequest, "Empty batch")],
+                    Ok(message) => answer(message).iter().map(|reply| serde_json::to_string(reply).unwrap()).collect(),
+                    Err(_) => vec![refuse(ErrorCode::ParseError, "Parse error")],
+                };
+                for reply in replies {
+                    send_response(&mut stdout, &reply);
+                }
+            }
+            Err(_) => break,
+        }
+    }
+}
 
-                        let response = serde_json::json!({
-                            "jsonrpc": "2.0",
-                            "id": id,
-                            "result": {
-                                "tools": [{
-                                    "name": "calculate_sum",
-                                    "description": "Adds two numbers together",
-                                    "inputSchema": schema
-                                }]
-                            }
-                        });
-                        send_response(&mut stdout, &response.to_string());
-                    }
-                    // 3. CALL TOOL (The Missing Piece!)
-                    else if method == "tools/call" {
-                        // Extract arguments
-                        let params = &json["params"];
-                        let tool_name = params["name"].as_str().unwrap_or("");
-                        let args = &params["arguments"];
+// One message in, the replies out (notifications and responses get none).
+// A subscription is confirmed and then immediately "updated", and a new log
+// level is announced in a log message, so hosts can see the notifications arrive.
+fn answer(message: Value) -> Vec<JsonRpcMessage> {
+    match JsonRpcMessage::try_from(message) {
+        Ok(JsonRpcMessage::Request(request)) => {
+            let updated = (request.method == "resources/subscribe").then(|| {
+                let uri = request.params.as_ref().map(|p| p["uri"].clone()).unwrap_or_default();
+                JsonRpcMessage::Notification(JsonRpcNotification::new(
+                    "notifications/resources/updated",
+                    Some(json!({ "uri": uri })),
+                ))
+            });
+            let logged = (request.method == "logging/setLevel").then(|| {
+                let level = request.params.as_ref().and_then(|p| p["level"].as_str()).unwrap_or_default().to_string();
+                JsonRpcMessage::Notification(JsonRpcNotification::new(
+                    "notifications/message",
+                    Some(json!({ "level": "notice", "logger": "mock", "data": format!("Log level is now {}", level) })),
+                ))
+            });
+            std::iter::once(handle_request(request)).chain(updated).chain(logged).collect()
+        }
+        Ok(_) => vec![],
+        Err(e) => vec![JsonRpcMessage::Error(JsonRpcErrorResponse::new(
+            None,
+            JsonRpcError::new(ErrorCode::InvalidRequest, format!("Invalid Request: {}", e)),
+        ))],
+    }
+}
 
-                        if tool_name == "calculate_sum" {
-                            let a = args["a"].as_f64().unwrap_or(0.0);
-                            let b = args["b"].as_f64().unwrap_or(0.0);
-                            let sum = a + b;
+fn handle_request(request: JsonRpcRequest) -> JsonRpcMessage {
+    let id = request.id;
+    let params = request.params.unwrap_or(Value::Null);
 
-                            // Return the result
-                            let response = serde_json::json!({
-                                "jsonrpc": "2.0",
-                                "id": id,
-                                "result": {
-                                    "content": [{
-                                        "type": "text",
-                                        "text": format!("The sum is {}", sum)
-                                    }]
-                                }
-                            });
-                            send_response(&mut stdout, &response.to_string());
-                        } else {
-                            // Tool not found error
-                            let response = serde_json::json!({
-                                "jsonrpc": "2.0", "id": id,
-                                "error": { "code": -32601, "message": "Method not found" }
-                            });
-                            send_response(&mut stdout, &response.to_string());
-                        }
-                    }
+    let result = match request.method.as_str() {
+        // 1. INITIALIZE
+        // Speaks two revisions: echoes the client's if it knows it, else offers its newest
+        "initialize" => Ok(json!({
+            "protocolVersion": negotiate(params["protocolVersion"].as_str().unwrap_or_default()),
+            "capabilities": { "tools": {}, "resources": { "subscribe": true }, "prompts": {}, "logging": {} },
+            "serverInfo": { "name": "MockTool", "version": "1.0" }
+        })),
+        // 2. LIST TOOLS
+        "tools/list" => {
+            let schema = json!({
+                "type": "object",
+                "properties": {
+                    "a": { "type": "number" },
+                    "b": { "type": "number" }
+                },
+                "required": ["a", "b"]
+            });
+            Ok(json!({
+                "tools": [{
+                    "name": "calculate_sum",
+                    "description": "Adds two numbers together",
+                    "inputSchema": schema
+                }]
+            }))
+        }
+        // 3. CALL TOOL (The Missing Piece!)
+        "tools/call" => {
+            // Extract arguments
+            let tool_name = params["name"].as_str().unwrap_or("");
+            let args = &params["arguments"];
+
+            if tool_name == "calculate_sum" {
+                let a = args["a"].as_f64().unwrap_or(0.0);
+                let b = args["b"].as_f64().unwrap_or(0.0);
+                let sum = a + b;
+
+                // Return the result
+                Ok(json!({
+                    "content": [{
+                        "type": "text",
+                        "text": format!("The sum is {}", sum)
+                    }]
+                }))
+            } else {
+                // Tool not found error
+                Err(JsonRpcError::new(ErrorCode::InvalidParams, format!("Unknown tool: {}", tool_name)))
+            }
+        }
+        // 4. RESOURCES (two pages, so hosts have to follow the cursor)
+        "resources/list" => match params["cursor"].as_str() {
+            None => Ok(json!({
+                "resources": [{ "uri": README_URI, "name": "readme", "mimeType": "text/plain" }],
+                "nextCursor": "page-2"
+            })),
+            Some(_) => Ok(json!({
+                "resources": [{ "uri": PIXEL_URI, "name": "pixel", "mimeType": "image/png" }]
+            })),
+        },
+        "resources/templates/list" => Ok(json!({
+            "resourceTemplates": [{ "uriTemplate": "mock://notes/{name}", "name": "note", "mimeType": "text/plain" }]
+        })),
+        "resources/read" => {
+            let uri = params["uri"].as_str().unwrap_or("");
+            match uri {
+                README_URI => Ok(json!({
+                    "contents": [{ "uri": uri, "mimeType": "text/plain", "text": "Hello from MockTool" }]
+                })),
+                PIXEL_URI => Ok(json!({
+                    "contents": [{ "uri": uri, "mimeType": "image/png", "blob": PIXEL_PNG }]
+                })),
+                _ => match uri.strip_prefix("mock://notes/") {
+                    Some(name) => Ok(json!({
+                        "contents": [{ "uri": uri, "mimeType": "text/plain", "text": format!("Note {}", name) }]
+                    })),
+                    // MCP's "resource not found"
+                    None => Err(JsonRpcError::new(ErrorCode::from(-32002), format!("Resource not found: {}", uri))),
+                },
+            }
+        }
+        "resources/subscribe" | "resources/unsubscribe" => Ok(json!({})),
+        // 5. PROMPTS
+        "prompts/list" => Ok(json!({
+            "prompts": [
+                {
+                    "name": "review",
+                    "description": "Review a piece of code",
+                    "arguments": [
+                        { "name": "code", "description": "The code to review", "required": true },
+                        { "name": "focus", "description": "What to look at" }
+                    ]
+                },
+                { "name": "hello", "description": "Say hello" }
+            ]
+        })),
+        "prompts/get" => {
+            let args = &params["arguments"];
+            match (params["name"].as_str(), args["code"].as_str()) {
+                (Some("review"), Some(code)) => {
+                    let focus = args["focus"].as_str().unwrap_or("anything");
+                    Ok(json!({
+                        "description": "Code review",
+                        "messages": [
+                            { "role": "assistant", "content": { "type": "text", "text": "I review code." } },
+                            { "role": "user", "content": { "type": "text", "text": format!("Review this, focusing on {}:\n{}", focus, code) } }
+                        ]
+                    }))
                 }
+                (Some("review"), None) => Err(JsonRpcError::new(ErrorCode::InvalidParams, "Missing argument: code")),
+                (Some("hello"), _) => Ok(json!({
+                    "messages": [{ "role": "user", "content": { "type": "text", "text": "Hello!" } }]
+                })),
+                (name, _) => Err(JsonRpcError::new(ErrorCode::InvalidParams, format!("Unknown prompt: {}", name.unwrap_or("")))),
             }
-            Err(_) => break,
         }
+        // 6. LOGGING
+        "logging/setLevel" => match params["level"].as_str() {
+            Some(_) => Ok(json!({})),
+            None => Err(JsonRpcError::new(ErrorCode::InvalidParams, "Missing level")),
+        },
+        "ping" => Ok(json!({})),
+        other => Err(JsonRpcError::new(ErrorCode::MethodNotFound, format!("Method not found: {}", other))),
+    };
+
+    match result {
+        Ok(result) => JsonRpcMessage::Response(JsonRpcResponse::new(id, result)),
+        Err(error) => JsonRpcMessage::Error(JsonRpcErrorResponse::new(Some(id), error)),
+    }
+}
+
+fn negotiate(requested: &str) -> &str {
+    match MOCK_VERSIONS.contains(&requested) {
+        true => requested,
+        false => MOCK_VERSIONS[0],
     }
 }
 
+// Newest first
+const MOCK_VERSIONS: &[&str] = &["2025-03-26", "2024-11-05"];
+const README_URI: &str = "mock://notes/readme";
+const PIXEL_URI: &str = "mock://images/pixel";
+// A 1x1 transparent PNG
+const PIXEL_PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+// An error we can't pin on any request (id is null)
+fn refuse(code: ErrorCode, message: &str) -> String {
+    let response = JsonRpcErrorResponse::new(None, JsonRpcError::new(code, message));
+    serde_json::to_string(&response).unwrap()
+}
+
 // Helper to write output + newline + flush
 fn send_response(stdout: &mut std::io::Stdout, response: &str) {
     stdout.write_all(response.as_bytes()).unwrap();
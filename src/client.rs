@@ -1,150 +1,802 @@
 // src/client.rs
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+use crate::error::McpError;
 use crate::protocol::{
-    ClientCapabilities, ClientInfo, InitializeParams, InitializeResult, JsonRpcRequest,
-    JsonRpcResponse, ListToolsResult, Tool,
+    ClientCapabilities, ClientInfo, ErrorCode, GetPromptResult, InitializeParams, InitializeResult,
+    JsonRpcError, JsonRpcErrorResponse, JsonRpcMessage, JsonRpcNotification, JsonRpcRequest,
+    JsonRpcResponse, ListRootsResult, LoggingLevel, ProgressNotification, ProgressToken, Prompt,
+    ReadResourceResult, RequestId, Resource, ResourceContents, ResourceTemplate, ServerCapabilities,
+    Tool, LATEST_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS,
 };
-use crate::runtime::McpProcess;
+use crate::roots::{self, Roots};
+use crate::schema;
+use crate::transport::Transport;
 use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+use tokio::task::JoinHandle;
 use crate::security::SecurityConfig;
 
+// How many unread notifications a slow subscriber may fall behind by
+const NOTIFICATION_BUFFER: usize = 256;
+
+// How much of a dead server's stderr goes into the "connection closed" error
+const STDERR_LINES_IN_ERRORS: usize = 5;
+
+// Stop following `nextCursor` after this many pages (a buggy server could loop forever)
+const MAX_PAGES: usize = 100;
+
+// Deadline for any request that has no more specific one
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+// How long we wait for each kind of request
+#[derive(Debug, Clone)]
+pub struct Timeouts {
+    // Everything (handshake, listing, and tools without an override)
+    pub default: Duration,
+    // tools/call overrides, keyed by the server's own tool name
+    pub per_tool: HashMap<String, Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Self { default: DEFAULT_REQUEST_TIMEOUT, per_tool: HashMap::new() }
+    }
+}
+
+impl Timeouts {
+    pub fn for_tool(&self, tool_name: &str) -> Duration {
+        self.per_tool.get(tool_name).copied().unwrap_or(self.default)
+    }
+}
+
+// Handles requests the SERVER sends to US (ping, sampling, roots, ...).
+// Returning Err sends a JSON-RPC error response back.
+#[async_trait]
+pub trait RequestHandler: Send + Sync {
+    async fn handle(&self, method: &str, params: Option<Value>) -> Result<Value, JsonRpcError>;
+
+    // What we announce during the handshake; must match what `handle` answers
+    fn capabilities(&self) -> ClientCapabilities {
+        ClientCapabilities::default()
+    }
+}
+
+// The fallback: answer pings, refuse everything else
+pub struct DefaultRequestHandler;
+
+#[async_trait]
+impl RequestHandler for DefaultRequestHandler {
+    async fn handle(&self, method: &str, _params: Option<Value>) -> Result<Value, JsonRpcError> {
+        match method {
+            "ping" => Ok(json!({})),
+            _ => Err(JsonRpcError::new(ErrorCode::MethodNotFound, format!("Method not found: {}", method))),
+        }
+    }
+}
+
+// What a request gets back: the result, or the error the server answered with
+pub type RpcOutcome = std::result::Result<Value, JsonRpcError>;
+
+// Requests waiting for their response, keyed by JSON-RPC id.
+// `closed` lives under the same lock so a request can't slip in after the
+// reader has already given up.
+#[derive(Default)]
+struct Pending {
+    waiters: HashMap<RequestId, oneshot::Sender<RpcOutcome>>,
+    // Why the connection died (set once the reader stops)
+    closed: Option<String>,
+    // We hung up on purpose; outstanding and new requests fail with McpError::Shutdown
+    shutdown: bool,
+    // The typed reason behind `closed`, when the transport gave one (e.g. a resource limit)
+    cause: Option<McpError>,
+}
+
+// State shared between the client and its background reader
+struct Shared {
+    pending: Mutex<Pending>,
+    // Mirrors `Pending::closed` for anyone who wants to await it
+    closed_tx: watch::Sender<Option<String>>,
+    notifications: broadcast::Sender<JsonRpcNotification>,
+    handler: RwLock<Arc<dyn RequestHandler>>,
+    // The directories we share with the server, if any
+    roots: OnceLock<Roots>,
+    // Who wants `notifications/progress` for which of our requests
+    progress: Mutex<HashMap<ProgressToken, mpsc::UnboundedSender<ProgressNotification>>>,
+}
+
 pub struct McpClient {
-    transport: McpProcess,
-    request_id_counter: u64,
+    transport: Arc<dyn Transport>,
+    request_id_counter: AtomicU64,
     security: SecurityConfig,
+    timeouts: Timeouts,
+    // What the server told us about itself during the handshake
+    server: OnceLock<InitializeResult>,
+    shared: Arc<Shared>,
+    // The background task that demultiplexes everything the server sends
+    reader: JoinHandle<()>,
+    // Sends `notifications/roots/list_changed` (started by the handshake)
+    roots_watcher: Mutex<Option<JoinHandle<()>>>,
+    // The tools from the last `tools/list`, for checking arguments before a call
+    tools: RwLock<HashMap<String, Tool>>,
+    // The server's working directory, for relative path arguments (empty: ours)
+    cwd: PathBuf,
 }
 
 impl McpClient {
-    // 1. Constructor: Wrap the process
-    pub fn new(transport: McpProcess, config: SecurityConfig) -> Self {
+    // 1. Constructor: Wrap any transport (stdio process, HTTP, ...)
+    // Spawns the reader task, so this must be called inside a Tokio runtime.
+    pub fn new<T: Transport + 'static>(transport: T, config: SecurityConfig) -> Self {
+        let transport: Arc<dyn Transport> = Arc::new(transport);
+        let (notifications, _) = broadcast::channel(NOTIFICATION_BUFFER);
+        let shared = Arc::new(Shared {
+            pending: Mutex::new(Pending::default()),
+            closed_tx: watch::channel(None).0,
+            notifications,
+            handler: RwLock::new(Arc::new(DefaultRequestHandler)),
+            roots: OnceLock::new(),
+            progress: Mutex::new(HashMap::new()),
+        });
+
+        let reader = tokio::spawn(read_loop(transport.clone(), shared.clone()));
+
         Self {
             transport,
-            request_id_counter: 0,
+            request_id_counter: AtomicU64::new(0),
             security: config,
+            timeouts: Timeouts::default(),
+            server: OnceLock::new(),
+            shared,
+            reader,
+            roots_watcher: Mutex::new(None),
+            tools: RwLock::new(HashMap::new()),
+            cwd: PathBuf::new(),
         }
     }
 
+    // Builder-style: override the default deadlines
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    // Builder-style: share these roots with the server (answers `roots/list`,
+    // announces changes, and fences path arguments in `call_tool`)
+    pub fn with_roots(self, roots: Roots) -> Self {
+        let _ = self.shared.roots.set(roots);
+        self
+    }
+
+    // Builder-style: where the server runs, so relative paths in tool
+    // arguments are fenced the way the server will read them
+    pub fn with_cwd(mut self, cwd: PathBuf) -> Self {
+        self.cwd = cwd;
+        self
+    }
+
+    // Replace the handler for server-initiated requests.
+    // Set it before `initialize`, which announces the handler's capabilities.
+    pub fn set_request_handler(&self, handler: Arc<dyn RequestHandler>) {
+        *self.shared.handler.write().unwrap() = handler;
+    }
+
+    // Listen to every notification the server sends
+    pub fn notifications(&self) -> broadcast::Receiver<JsonRpcNotification> {
+        self.shared.notifications.subscribe()
+    }
+
     // 2. The Handshake Logic
-    pub async fn initialize(&mut self) -> Result<()> {
+    pub async fn initialize(&self) -> Result<()> {
         // A. Prepare the Payload
+        let mut capabilities = self.shared.handler.read().unwrap().capabilities();
+        if self.shared.roots.get().is_some() {
+            capabilities.roots = Some(json!({ "listChanged": true }));
+        }
         let params = InitializeParams {
-            protocol_version: "2024-11-05".to_string(),
-            capabilities: ClientCapabilities { experimental: None },
+            protocol_version: LATEST_PROTOCOL_VERSION.to_string(),
+            capabilities,
             client_info: ClientInfo {
                 name: "AETHER".to_string(),
                 version: "0.1.0".to_string(),
             },
         };
 
-        let request = JsonRpcRequest::new(
-            "initialize",
-            Some(serde_json::to_value(params)?),
-            Some(self.next_id()),
-        );
+        // B. Send Request & Wait for Response
+        let outcome = self.request("initialize", Some(serde_json::to_value(params)?)).await?;
 
-        // B. Send Request
-        self.transport.send_request(&request).await?;
+        // C. Check for Errors
+        let result = outcome.map_err(|err| anyhow!(
+            "MCP Init Error: {} (Code: {})",
+            err.message,
+            err.code
+        ))?;
 
-        // C. Wait for Response
-        let response_str = self.transport.read_line().await?;
+        // D. Decode the Result
+        let init_result: InitializeResult = serde_json::from_value(result)
+            .context("Tool sent invalid initialize result format")?;
 
-        // D. Parse Response
-        let response: JsonRpcResponse = serde_json::from_str(&response_str)
-            .context("Failed to parse init response from tool")?;
-
-        // E. Check for Errors
-        if let Some(err) = response.error {
+        // E. Negotiate: the server answers with our version or an older one it prefers
+        let version = init_result.protocol_version.as_str();
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
             return Err(anyhow!(
-                "MCP Init Error: {} (Code: {})",
-                err.message,
-                err.code
+                "Server '{}' speaks MCP {}, which AETHER does not support (supported: {})",
+                init_result.server_info.name,
+                version,
+                SUPPORTED_PROTOCOL_VERSIONS.join(", ")
             ));
         }
+        self.transport.set_protocol_version(version);
+
+        // Logged, not printed: stdout belongs to the TUI (and restarts handshake again)
+        tracing::info!(
+            "Connected to {} v{} (MCP {})",
+            init_result.server_info.name, init_result.server_info.version, version
+        );
 
-        // F. Decode the Result
-        if let Some(result) = response.result {
-            let init_result: InitializeResult = serde_json::from_value(result)
-                .context("Tool sent invalid initialize result format")?;
+        let _ = self.server.set(init_result);
+
+        // F. Only now may the server start sending us requests of its own
+        self.notify("notifications/initialized", None).await
+            .context("Failed to send notifications/initialized")?;
+        self.watch_roots();
+        Ok(())
+    }
 
-            println!("--- HANDSHAKE COMPLETE ---");
-            println!(
-                "Connected to: {} v{}",
-                init_result.server_info.name, init_result.server_info.version
-            );
+    // Tell the server whenever the roots change (until we're dropped)
+    fn watch_roots(&self) {
+        let Some(roots) = self.shared.roots.get() else { return };
+        let mut changes = roots.subscribe();
+        changes.mark_unchanged();
+        let transport = self.transport.clone();
 
-            Ok(())
-        } else {
-            Err(anyhow!("Tool returned no result for initialize"))
+        let watcher = tokio::spawn(async move {
+            while changes.changed().await.is_ok() {
+                let note = JsonRpcNotification::new("notifications/roots/list_changed", None);
+                let Ok(message) = serde_json::to_string(&note) else { break };
+                if let Err(e) = transport.send(&message).await {
+                    tracing::warn!("Failed to announce new roots: {}", e);
+                    break;
+                }
+            }
+        });
+        if let Some(old) = self.roots_watcher.lock().unwrap().replace(watcher) {
+            old.abort();
         }
     }
 
-    // Helper to generate IDs
-    fn next_id(&mut self) -> u64 {
-        self.request_id_counter += 1;
-        self.request_id_counter
+    // The capabilities the server declared (None before the handshake)
+    pub fn server_capabilities(&self) -> Option<&ServerCapabilities> {
+        self.server.get().map(|server| &server.capabilities)
     }
-    pub async fn list_tools(&mut self) -> Result<Vec<Tool>> {
-        // 1. Send Request
 
-        let request = JsonRpcRequest::new(
-            "tools/list",
-            None, // No params needed for listing
-            Some(self.next_id()),
-        );
+    // The protocol revision both sides agreed on (None before the handshake)
+    pub fn protocol_version(&self) -> Option<&str> {
+        self.server.get().map(|server| server.protocol_version.as_str())
+    }
+
+    // Did the server declare this capability ("tools", "resources", ...)?
+    pub fn supports(&self, capability: &str) -> bool {
+        self.server_capabilities().is_some_and(|caps| caps.has(capability))
+    }
+
+    // Helper to generate IDs (ours are always numbers)
+    fn next_id(&self) -> RequestId {
+        let id = self.request_id_counter.fetch_add(1, Ordering::Relaxed) + 1;
+        RequestId::Number(id as i64)
+    }
+
+    // Helper to serialize and ship a message over whatever transport we have
+    async fn send_message(&self, message: &impl Serialize) -> Result<()> {
+        let json_string = serde_json::to_string(message)?;
+        self.transport.send(&json_string).await
+    }
+
+    // The core round trip with the default deadline.
+    // The outer error is ours (timeout, dead connection); the inner one is the server's.
+    pub async fn request(&self, method: &str, params: Option<Value>) -> Result<RpcOutcome> {
+        self.request_with_timeout(method, params, self.timeouts.default).await
+    }
+
+    // Register a slot, send, wait for the reader to fill it.
+    // Safe to call concurrently; responses are matched by id, not by order.
+    // If the deadline passes, or the future is dropped before the response
+    // arrives (e.g. the user aborted), the server gets `notifications/cancelled`.
+    pub async fn request_with_timeout(
+        &self,
+        method: &str,
+        params: Option<Value>,
+        timeout: Duration,
+    ) -> Result<RpcOutcome> {
+        // Don't bother the server with methods it never offered
+        if let (Some(caps), Some(capability)) = (self.server_capabilities(), ServerCapabilities::required_for(method)) {
+            if !caps.has(capability) {
+                return Err(McpError::Unsupported { method: method.to_string(), capability: capability.to_string() }.into());
+            }
+        }
+
+        let id = self.next_id();
+        let (tx, rx) = oneshot::channel();
+
+        {
+            let mut pending = self.shared.pending.lock().unwrap();
+            if pending.shutdown {
+                return Err(McpError::Shutdown { method: method.to_string() }.into());
+            }
+            if let Some(cause) = &pending.cause {
+                return Err(cause.clone().into());
+            }
+            if let Some(reason) = &pending.closed {
+                return Err(anyhow!("Connection to MCP server is closed: {}", reason));
+            }
+            pending.waiters.insert(id.clone(), tx);
+        }
+
+        let mut in_flight = InFlight {
+            client: self,
+            id: id.clone(),
+            method,
+            reason: "Request aborted by the client",
+            finished: false,
+        };
+
+        let request = JsonRpcRequest::new(id.clone(), method, params);
+        if let Err(e) = self.send_message(&request).await {
+            self.shared.pending.lock().unwrap().waiters.remove(&id);
+            in_flight.finished = true;
+            // Usually the process just died; say what it printed on the way out
+            return match stderr_tail(&self.transport).await {
+                Some(tail) => Err(e.context(tail)),
+                None => Err(e),
+            };
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(outcome)) => {
+                in_flight.finished = true;
+                Ok(outcome)
+            }
+            Ok(Err(_)) => {
+                in_flight.finished = true;
+                let pending = self.shared.pending.lock().unwrap();
+                if pending.shutdown {
+                    return Err(McpError::Shutdown { method: method.to_string() }.into());
+                }
+                if let Some(cause) = &pending.cause {
+                    return Err(cause.clone().into());
+                }
+                let reason = pending.closed.clone().unwrap_or_default();
+                Err(anyhow!("Connection to MCP server closed before '{}' returned: {}", method, reason))
+            }
+            // `in_flight` drops here and sends the cancellation
+            Err(_) => {
+                in_flight.reason = "Request timed out";
+                Err(McpError::Timeout { method: method.to_string(), after: timeout }.into())
+            }
+        }
+    }
+
+    // Fire-and-forget message (no id, no response)
+    pub async fn notify(&self, method: &str, params: Option<Value>) -> Result<()> {
+        self.send_message(&JsonRpcNotification::new(method, params)).await
+    }
+
+    // Hang up on the server
+    pub async fn close(&self) -> Result<()> {
+        self.transport.close().await
+    }
+
+    // Hang up for good: fail everything in flight with McpError::Shutdown,
+    // then let the transport wind down (see `Transport::shutdown`)
+    pub async fn shutdown(&self, grace: Duration) -> Result<()> {
+        {
+            let mut pending = self.shared.pending.lock().unwrap();
+            pending.shutdown = true;
+            pending.waiters.clear();
+        }
+        self.transport.shutdown(grace).await
+    }
+
+    // Resolves (with the reason) once the connection is gone for good
+    pub async fn wait_closed(&self) -> String {
+        let mut closed = self.shared.closed_tx.subscribe();
+        let reason = closed.wait_for(|reason| reason.is_some()).await
+            .map(|reason| reason.clone().unwrap_or_default());
+        reason.unwrap_or_else(|_| "client dropped".to_string())
+    }
+
+    // The process exit status, for transports that have one
+    pub async fn exit_status(&self) -> Option<ExitStatus> {
+        self.transport.exit_status().await
+    }
+
+    // Every tool the server offers (all pages)
+    pub async fn list_tools(&self) -> Result<Vec<Tool>> {
+        let tools: Vec<Tool> = self.list_all("tools/list", "tools").await?;
 
-        self.transport.send_request(&request).await?;
+        // Remember the schemas for `call_tool`
+        *self.tools.write().unwrap() = tools.iter()
+            .map(|tool| (tool.name.clone(), tool.clone()))
+            .collect();
+        Ok(tools)
+    }
 
-        // 2. Read Response
+    // --- RESOURCES ---
 
-        let response_str = self.transport.read_line().await?;
+    // Every resource the server offers (all pages)
+    pub async fn list_resources(&self) -> Result<Vec<Resource>> {
+        self.list_all("resources/list", "resources").await
+    }
 
-        let response: JsonRpcResponse =
-            serde_json::from_str(&response_str).context("Failed to parse tools/list response")?;
+    // Every resource template the server offers (all pages)
+    pub async fn list_resource_templates(&self) -> Result<Vec<ResourceTemplate>> {
+        self.list_all("resources/templates/list", "resourceTemplates").await
+    }
 
-        // 3. Extract Result
+    pub async fn read_resource(&self, uri: &str) -> Result<Vec<ResourceContents>> {
+        let outcome = self.request("resources/read", Some(json!({ "uri": uri }))).await?;
+        let result = outcome.map_err(|err| anyhow!("Failed to read resource '{}': {}", uri, err))?;
+        let read: ReadResourceResult = serde_json::from_value(result)
+            .context("Invalid resources/read result format")?;
+        Ok(read.contents)
+    }
 
-        if let Some(result) = response.result {
-            let tools_result: ListToolsResult =
-                serde_json::from_value(result).context("Invalid tools list format")?;
+    // Ask for `notifications/resources/updated` whenever `uri` changes
+    pub async fn subscribe_resource(&self, uri: &str) -> Result<()> {
+        let can_subscribe = self.server_capabilities()
+            .and_then(|caps| caps.resources.as_ref())
+            .is_some_and(|resources| resources.subscribe);
+        if !can_subscribe {
+            return Err(anyhow!("Server does not support resource subscriptions"));
+        }
+        self.request("resources/subscribe", Some(json!({ "uri": uri }))).await?
+            .map_err(|err| anyhow!("Failed to subscribe to '{}': {}", uri, err))?;
+        Ok(())
+    }
+
+    pub async fn unsubscribe_resource(&self, uri: &str) -> Result<()> {
+        self.request("resources/unsubscribe", Some(json!({ "uri": uri }))).await?
+            .map_err(|err| anyhow!("Failed to unsubscribe from '{}': {}", uri, err))?;
+        Ok(())
+    }
+
+    // --- LOGGING ---
+
+    // Only send `notifications/message` at `level` or above
+    pub async fn set_log_level(&self, level: LoggingLevel) -> Result<()> {
+        self.request("logging/setLevel", Some(json!({ "level": level }))).await?
+            .map_err(|err| anyhow!("Failed to set the log level to '{}': {}", level, err))?;
+        Ok(())
+    }
 
-            Ok(tools_result.tools)
-        } else {
-            Err(anyhow!("Server returned error or no result"))
+    // --- PROMPTS ---
+
+    // Every prompt the server offers (all pages)
+    pub async fn list_prompts(&self) -> Result<Vec<Prompt>> {
+        self.list_all("prompts/list", "prompts").await
+    }
+
+    // Fill a prompt in; the server answers with the messages to add to the conversation
+    pub async fn get_prompt(&self, name: &str, arguments: HashMap<String, String>) -> Result<GetPromptResult> {
+        let params = json!({ "name": name, "arguments": arguments });
+        let result = self.request("prompts/get", Some(params)).await?
+            .map_err(|err| anyhow!("Failed to get prompt '{}': {}", name, err))?;
+        serde_json::from_value(result).context("Invalid prompts/get result format")
+    }
+
+    // Follow `nextCursor` until the server runs out of pages, collecting `result[key]`
+    async fn list_all<T: DeserializeOwned>(&self, method: &str, key: &str) -> Result<Vec<T>> {
+        let mut items = Vec::new();
+        let mut cursor: Option<String> = None;
+
+        for _ in 0..MAX_PAGES {
+            let params = cursor.as_ref().map(|cursor| json!({ "cursor": cursor }));
+            let mut result = self.request(method, params).await?
+                .map_err(|err| anyhow!("Server returned an error for {}: {}", method, err))?;
+
+            let page = result.get_mut(key).map(Value::take).unwrap_or_default();
+            let page: Vec<T> = serde_json::from_value(page)
+                .context(format!("Invalid {} result format", method))?;
+            items.extend(page);
+
+            cursor = result.get("nextCursor").and_then(Value::as_str).map(str::to_string);
+            if cursor.is_none() {
+                return Ok(items);
+            }
         }
+        Err(anyhow!("Gave up on {} after {} pages", method, MAX_PAGES))
+    }
+
+    pub async fn call_tool(&self, tool_name: &str, arguments: serde_json::Value) -> Result<serde_json::Value> {
+        self.call_tool_with_progress(tool_name, arguments, None).await
     }
-    pub async fn call_tool(&mut self, tool_name: &str, arguments: serde_json::Value) -> Result<serde_json::Value> {
+
+    // Like `call_tool`, but the server may report how far it got; updates go
+    // to `progress` until the call returns
+    pub async fn call_tool_with_progress(
+        &self,
+        tool_name: &str,
+        arguments: serde_json::Value,
+        progress: Option<mpsc::UnboundedSender<ProgressNotification>>,
+    ) -> Result<serde_json::Value> {
     // --- 1. THE SECURITY CHECK ---
         if !self.security.check_permission(tool_name) {
             return Err(anyhow::anyhow!("SECURITY ALERT: Tool '{}' is blocked by permissions.json", tool_name));
         }
+        if let Some(roots) = self.shared.roots.get() {
+            if let Some(path) = self.security.path_outside_roots(&arguments, roots, &roots::normalize(&self.cwd)) {
+                return Err(anyhow::anyhow!("SECURITY ALERT: Tool '{}' may not touch '{}' (outside the granted roots)", tool_name, path));
+            }
+        }
         // -----------------------------
 
-        // 2. Construct Request
-        let params = serde_json::json!({
+        // 2. Check the arguments against the inputSchema (unknown tools go through unchecked)
+        let (violations, output_schema) = match self.tools.read().unwrap().get(tool_name) {
+            Some(tool) => (schema::validate(&tool.input_schema, &arguments), tool.output_schema.clone()),
+            None => (Vec::new(), None),
+        };
+        if !violations.is_empty() {
+            return Err(McpError::InvalidArguments { tool: tool_name.to_string(), violations }.into());
+        }
+
+        // 3. Construct Request (with a progress token if anyone is listening)
+        let mut params = serde_json::json!({
             "name": tool_name,
             "arguments": arguments
         });
+        let _subscription = progress.map(|progress| {
+            let token = self.next_id();
+            params["_meta"] = json!({ "progressToken": token });
+            ProgressSubscription::new(&self.shared, token, progress)
+        });
+
+        // 4. Send & Wait (the standard MCP method to run a tool)
+        let timeout = self.timeouts.for_tool(tool_name);
+        let outcome = self.request_with_timeout("tools/call", Some(params), timeout).await?;
+
+        // 5. Parse Result
+        let result = outcome.map_err(|err| anyhow::anyhow!("Tool Execution Error: {}", err.message))?;
+
+        // 6. Hold the structured content to the outputSchema (failed calls owe us nothing)
+        if let Some(output_schema) = output_schema {
+            let violations = check_output(&output_schema, &result);
+            if !violations.is_empty() {
+                return Err(McpError::InvalidOutput { tool: tool_name.to_string(), violations }.into());
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Drop for McpClient {
+    fn drop(&mut self) {
+        self.reader.abort();
+        if let Some(watcher) = self.roots_watcher.lock().unwrap().take() {
+            watcher.abort();
+        }
+    }
+}
+
+// A request that hasn't been answered yet.
+// If it is dropped unfinished we withdraw the waiter and tell the server to
+// stop working on it (except for `initialize`, which must never be cancelled).
+struct InFlight<'a> {
+    client: &'a McpClient,
+    id: RequestId,
+    method: &'a str,
+    reason: &'static str,
+    finished: bool,
+}
 
-        let request = JsonRpcRequest::new(
-            "tools/call", // The standard MCP method to run a tool
-            Some(params),
-            Some(self.next_id()),
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+        let was_waiting = self.client.shared.pending.lock().unwrap().waiters.remove(&self.id).is_some();
+        if !was_waiting || self.method == "initialize" {
+            return;
+        }
+
+        let cancel = JsonRpcNotification::new(
+            "notifications/cancelled",
+            Some(json!({ "requestId": self.id, "reason": self.reason })),
         );
+        let Ok(message) = serde_json::to_string(&cancel) else { return };
+        let transport = self.client.transport.clone();
+
+        // Drop can't await, so the notification goes out on its own task
+        if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            runtime.spawn(async move {
+                if let Err(e) = transport.send(&message).await {
+                    tracing::warn!("Failed to send cancellation: {}", e);
+                }
+            });
+        }
+    }
+}
+
+// Routes `notifications/progress` for one token while it's alive
+struct ProgressSubscription<'a> {
+    shared: &'a Shared,
+    token: ProgressToken,
+}
+
+impl<'a> ProgressSubscription<'a> {
+    fn new(shared: &'a Shared, token: ProgressToken, tx: mpsc::UnboundedSender<ProgressNotification>) -> Self {
+        shared.progress.lock().unwrap().insert(token.clone(), tx);
+        Self { shared, token }
+    }
+}
+
+impl Drop for ProgressSubscription<'_> {
+    fn drop(&mut self) {
+        self.shared.progress.lock().unwrap().remove(&self.token);
+    }
+}
+
+// --- THE BACKGROUND READER ---
+// Pulls every message off the transport and sends it where it belongs:
+// responses -> the waiting request, notifications -> subscribers,
+// server requests -> the request handler.
+async fn read_loop(transport: Arc<dyn Transport>, shared: Arc<Shared>) {
+    let (reason, cause) = loop {
+        let line = match transport.receive().await {
+            Ok(line) => line,
+            Err(e) => break (e.to_string(), e.downcast_ref::<McpError>().cloned()),
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match serde_json::from_str::<Value>(&line) {
+            // A batch is just several messages in one frame
+            Ok(Value::Array(batch)) => {
+                for message in batch {
+                    dispatch(message, &transport, &shared);
+                }
+            }
+            Ok(message) => dispatch(message, &transport, &shared),
+            Err(e) => tracing::warn!("Ignoring non-JSON line from server: {} ({})", line.trim(), e),
+        }
+    };
+
+    // A crashing server usually says why on stderr
+    let reason = match stderr_tail(&transport).await {
+        Some(tail) => format!("{}; {}", reason, tail),
+        None => reason,
+    };
+
+    // Wake every waiter: dropping their senders makes them fail with `reason`
+    let mut pending = shared.pending.lock().unwrap();
+    pending.closed = Some(reason.clone());
+    pending.cause = cause;
+    pending.waiters.clear();
+    shared.closed_tx.send_replace(Some(reason));
+}
+
+// "last stderr: ..." for error messages, if the transport has any
+async fn stderr_tail(transport: &Arc<dyn Transport>) -> Option<String> {
+    let stderr = transport.recent_stderr(STDERR_LINES_IN_ERRORS).await;
+    (!stderr.is_empty()).then(|| format!("last stderr: {}", stderr.join(" | ")))
+}
+
+fn dispatch(message: Value, transport: &Arc<dyn Transport>, shared: &Arc<Shared>) {
+    // Enough to answer (or release a waiter) even if the rest doesn't parse
+    let has_method = message.get("method").is_some();
+    let id = message.get("id").and_then(|id| RequestId::deserialize(id).ok());
+
+    let message = match JsonRpcMessage::try_from(message) {
+        Ok(message) => message,
+        Err(e) => {
+            match (has_method, id) {
+                // A broken request still deserves an answer
+                (true, Some(id)) => {
+                    let error = JsonRpcError::new(ErrorCode::InvalidRequest, format!("Invalid request: {}", e));
+                    reply(transport, JsonRpcMessage::Error(JsonRpcErrorResponse::new(Some(id), error)));
+                }
+                // A garbled response still has to release its waiter
+                (false, Some(id)) => {
+                    let error = JsonRpcError::new(ErrorCode::InternalError, format!("Malformed response from server: {}", e));
+                    resolve(shared, &id, Err(error));
+                }
+                _ => tracing::warn!("Ignoring malformed message from server: {}", e),
+            }
+            return;
+        }
+    };
 
-        // 3. Send & Wait
-        self.transport.send_request(&request).await?;
-        let response_str = self.transport.read_line().await?;
+    match message {
+        // A. Server -> Client request: answer it off the reader task
+        JsonRpcMessage::Request(request) => {
+            let handler = shared.handler.read().unwrap().clone();
+            let roots = shared.roots.get().cloned();
+            let transport = transport.clone();
 
-        // 4. Parse Result
-        let response: JsonRpcResponse = serde_json::from_str(&response_str)?;
+            tokio::spawn(async move {
+                let outcome = match (request.method.as_str(), roots) {
+                    ("roots/list", Some(roots)) => Ok(json!(ListRootsResult { roots: roots.list() })),
+                    _ => handler.handle(&request.method, request.params).await,
+                };
+                let answer = match outcome {
+                    Ok(result) => JsonRpcMessage::Response(JsonRpcResponse::new(request.id, result)),
+                    Err(error) => JsonRpcMessage::Error(JsonRpcErrorResponse::new(Some(request.id), error)),
+                };
+                if let Err(e) = send(&transport, &answer).await {
+                    tracing::warn!("Failed to answer server request '{}': {}", request.method, e);
+                }
+            });
+        }
 
-        if let Some(err) = response.error {
-            return Err(anyhow::anyhow!("Tool Execution Error: {}", err.message));
+        // B. Notification: fan out (no subscribers is fine); progress also
+        // goes to whoever made the request
+        JsonRpcMessage::Notification(notification) => {
+            if notification.method == "notifications/progress" {
+                route_progress(shared, notification.params.clone());
+            }
+            let _ = shared.notifications.send(notification);
         }
 
-        if let Some(result) = response.result {
-            Ok(result)
-        } else {
-            Err(anyhow::anyhow!("Tool returned no result"))
+        // C. Response: hand it to whoever is waiting on that id
+        JsonRpcMessage::Response(response) => resolve(shared, &response.id, Ok(response.result)),
+        JsonRpcMessage::Error(response) => match response.id {
+            Some(id) => resolve(shared, &id, Err(response.error)),
+            // The server couldn't even tell which request it was
+            None => tracing::warn!("Server reported an error without an id: {}", response.error),
+        },
+    }
+}
+
+fn resolve(shared: &Shared, id: &RequestId, outcome: RpcOutcome) {
+    let Some(waiter) = shared.pending.lock().unwrap().waiters.remove(id) else {
+        tracing::warn!("Response for unknown request id {}", id);
+        return;
+    };
+    let _ = waiter.send(outcome);
+}
+
+fn route_progress(shared: &Shared, params: Option<Value>) {
+    let update = match serde_json::from_value::<ProgressNotification>(params.unwrap_or_default()) {
+        Ok(update) => update,
+        Err(e) => return tracing::warn!("Ignoring malformed progress notification: {}", e),
+    };
+    // Late updates for a finished request have nowhere to go
+    if let Some(tx) = shared.progress.lock().unwrap().get(&update.progress_token) {
+        let _ = tx.send(update);
+    }
+}
+
+// Send a reply from the reader without holding it up
+fn reply(transport: &Arc<dyn Transport>, message: JsonRpcMessage) {
+    let transport = transport.clone();
+    tokio::spawn(async move {
+        if let Err(e) = send(&transport, &message).await {
+            tracing::warn!("Failed to send reply to server: {}", e);
         }
+    });
+}
+
+async fn send(transport: &Arc<dyn Transport>, message: &JsonRpcMessage) -> Result<()> {
+    transport.send(&serde_json::to_string(message)?).await
+}
+
+// What's wrong with a `tools/call` result given the tool's outputSchema
+fn check_output(output_schema: &Value, result: &Value) -> Vec<schema::Violation> {
+    if result.get("isError").and_then(Value::as_bool).unwrap_or(false) {
+        return Vec::new();
+    }
+    match result.get("structuredContent") {
+        Some(structured) => schema::validate(output_schema, structured),
+        None => vec![schema::Violation {
+            path: String::new(),
+            message: "missing structuredContent (the tool declares an outputSchema)".to_string(),
+        }],
     }
 }
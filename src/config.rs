@@ -0,0 +1,479 @@
+// src/config.rs
+// The Host Config (aether.json)
+// The `mcpServers` section uses the same shape desktop MCP hosts use, so an
+// existing config can be dropped in verbatim. AETHER adds three optional
+// sections of its own: `llm`, `permissions` and `roots`.
+//
+// {
+//   "mcpServers": {
+//     "math":   { "command": "target/debug/mock_tool", "args": [], "cwd": ".",
+//                 "env": { "API_KEY": "${env:MATH_API_KEY}", "TOKEN": "${file:secrets/token}" },
+//                 "inheritEnv": ["HTTPS_PROXY"],
+//                 "timeout": 30, "toolTimeouts": { "calculate_sum": 5 },
+//                 "limits": { "memoryMb": 256, "cpuSeconds": 60, "openFiles": 256, "processes": 512 } },
+//     "files":  { "command": "npx", "args": ["some-fs-server"],
+//                 "sandbox": { "readOnly": ["."], "writable": ["/tmp/scratch"], "network": false } },
+//     "remote": { "type": "http", "url": "https://example.com/mcp" }
+//   },
+//   "llm": { "model": "llama-3.3-70b-versatile",
+//            "samplingModels": ["llama-3.3-70b-versatile", "llama-3.1-8b-instant"] },
+//   "permissions": "permissions.json",
+//   "roots": ["~/projects/aether", "/tmp/scratch"]
+// }
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use serde::Deserialize;
+use crate::client::Timeouts;
+use crate::registry::NAMESPACE_SEPARATOR;
+use crate::roots::Roots;
+
+pub const DEFAULT_CONFIG_PATH: &str = "aether.json";
+pub const DEFAULT_MODEL: &str = "llama-3.3-70b-versatile";
+pub const DEFAULT_PERMISSIONS_PATH: &str = "permissions.json";
+// Without a cgroup, `processes` is RLIMIT_NPROC: it counts every process the
+// user owns (the sandbox's own fork included), so a tiny value just breaks startup
+pub const MIN_RLIMIT_PROCESSES: u64 = 256;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AetherConfig {
+    #[serde(default)]
+    pub mcp_servers: BTreeMap<String, ServerConfig>,
+    #[serde(default)]
+    pub llm: LlmConfig,
+    // Path to the permissions file (relative paths are resolved against the config file)
+    #[serde(default = "default_permissions")]
+    pub permissions: PathBuf,
+    // Directories servers may work in (relative paths are resolved against the config file)
+    #[serde(default)]
+    pub roots: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LlmConfig {
+    #[serde(default = "default_model")]
+    pub model: String,
+    // Models servers may pick from for sampling, most capable first.
+    // Empty means they always get `model`.
+    #[serde(default)]
+    pub sampling_models: Vec<String>,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self { model: default_model(), sampling_models: Vec::new() }
+    }
+}
+
+// How we reach a server
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum TransportKind {
+    // Spawn `command` and speak over its stdin/stdout
+    #[serde(rename = "stdio")]
+    Stdio,
+    // Streamable HTTP (POST to `url`)
+    #[serde(rename = "http", alias = "streamable-http", alias = "streamableHttp")]
+    Http,
+    // Legacy HTTP+SSE (GET event stream at `url`)
+    #[serde(rename = "sse")]
+    Sse,
+}
+
+// One entry under `mcpServers`. Unknown keys are errors: a misspelled
+// `sandbox` or `limits` would otherwise quietly turn the protection off.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ServerConfig {
+    // Filled in from the map key at load time
+    #[serde(skip)]
+    pub name: String,
+
+    // --- Stdio servers ---
+    pub command: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    // Values may pull in secrets: `${env:NAME}` (host variable) or
+    // `${file:path}` (file contents). Resolved only when this server launches.
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // Host variables passed through as-is, on top of the basic allowlist
+    #[serde(default)]
+    pub inherit_env: Vec<String>,
+    pub cwd: Option<PathBuf>,
+    // Where `${file:...}` paths are relative to (the config file's directory)
+    #[serde(skip)]
+    pub base_dir: PathBuf,
+
+    // --- Remote servers ---
+    pub url: Option<String>,
+    #[serde(rename = "type")]
+    pub transport: Option<TransportKind>,
+
+    // --- Deadlines (seconds) ---
+    // Applies to every request to this server (and to the legacy SSE endpoint wait)
+    pub timeout: Option<u64>,
+    // tools/call overrides by tool name
+    #[serde(default)]
+    pub tool_timeouts: BTreeMap<String, u64>,
+
+    // OS-level confinement (stdio only, Linux only). Off unless present.
+    pub sandbox: Option<SandboxConfig>,
+    // Resource caps (stdio only, Linux only). Off unless present.
+    pub limits: Option<ResourceLimits>,
+
+    // Some hosts let you park an entry without deleting it
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+// What a sandboxed server may touch. See src/sandbox.rs for how it's enforced.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SandboxConfig {
+    // Paths the server may read (and execute) below; system dirs are always added
+    #[serde(default)]
+    pub read_only: Vec<PathBuf>,
+    // Paths the server may read and write below
+    #[serde(default)]
+    pub writable: Vec<PathBuf>,
+    // The roots are readable by default; this makes them writable too
+    #[serde(default)]
+    pub writable_roots: bool,
+    // Without this the server gets its own empty network namespace
+    #[serde(default)]
+    pub network: bool,
+    // Syscall allowlist (on by default; turn off for runtimes it breaks)
+    #[serde(default = "default_true")]
+    pub seccomp: bool,
+    // Extra syscalls to allow on top of the default list
+    #[serde(default)]
+    pub allow_syscalls: Vec<String>,
+    // Start the server even if the kernel has no landlock (no filesystem rules at all)
+    #[serde(default)]
+    pub allow_without_landlock: bool,
+}
+
+// How much a server may use. See src/runtime/limits.rs for how it's enforced.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct ResourceLimits {
+    // Address space (RLIMIT_AS); with `cgroup` also memory.max
+    pub memory_mb: Option<u64>,
+    // Total CPU time before the kernel kills it (RLIMIT_CPU)
+    pub cpu_seconds: Option<u64>,
+    // File descriptors (RLIMIT_NOFILE)
+    pub open_files: Option<u64>,
+    // With `cgroup`: the server's processes/threads (pids.max). Without: the
+    // user's, all of them (RLIMIT_NPROC), so at least MIN_RLIMIT_PROCESSES
+    pub processes: Option<u64>,
+    // Also put the server in its own cgroup v2 (needs a delegated cgroup)
+    #[serde(default)]
+    pub cgroup: bool,
+    // Share of one CPU (cgroup only; 50 = half a core)
+    pub cpu_percent: Option<u32>,
+}
+
+impl Default for SandboxConfig {
+    fn default() -> Self {
+        Self {
+            read_only: Vec::new(),
+            writable: Vec::new(),
+            writable_roots: false,
+            network: false,
+            seccomp: true,
+            allow_syscalls: Vec::new(),
+            allow_without_landlock: false,
+        }
+    }
+}
+
+fn default_model() -> String {
+    DEFAULT_MODEL.to_string()
+}
+
+fn default_permissions() -> PathBuf {
+    PathBuf::from(DEFAULT_PERMISSIONS_PATH)
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl ServerConfig {
+    // Shorthand for a plain child-process server
+    pub fn stdio(name: &str, command: &str, args: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            command: Some(command.to_string()),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    // The transport to use: explicit `type`, else inferred from `command`/`url`
+    pub fn transport_kind(&self) -> TransportKind {
+        match (self.transport, &self.url) {
+            (Some(kind), _) => kind,
+            (None, Some(_)) => TransportKind::Http,
+            (None, None) => TransportKind::Stdio,
+        }
+    }
+
+    // `env` with every `${env:...}` / `${file:...}` reference filled in
+    pub fn resolved_env(&self) -> Result<BTreeMap<String, String>> {
+        let mut env = BTreeMap::new();
+        for (var, value) in &self.env {
+            let resolved = expand_refs(value, |reference| match reference {
+                SecretRef::Env(name) => std::env::var(name)
+                    .map_err(|_| anyhow!("${{env:{}}} is not set", name)),
+                SecretRef::File(path) => {
+                    let path = self.base_dir.join(path);
+                    fs::read_to_string(&path)
+                        .map(|content| content.trim_end_matches(['\r', '\n']).to_string())
+                        .map_err(|e| anyhow!("${{file:{}}}: {}", path.display(), e))
+                }
+            }).map_err(|e| anyhow!("mcpServers.{}.env.{}: {}", self.name, var, e))?;
+            env.insert(var.clone(), resolved);
+        }
+        Ok(env)
+    }
+
+    // Deadlines for this server's client
+    pub fn timeouts(&self) -> Timeouts {
+        let mut timeouts = Timeouts::default();
+        if let Some(secs) = self.timeout {
+            timeouts.default = Duration::from_secs(secs);
+        }
+        for (tool, secs) in &self.tool_timeouts {
+            timeouts.per_tool.insert(tool.clone(), Duration::from_secs(*secs));
+        }
+        timeouts
+    }
+
+    // Check one entry; errors name the offending key
+    fn validate(&self, key: &str) -> Result<()> {
+        if self.name.is_empty() {
+            return Err(anyhow!("{}: server name must not be empty", key));
+        }
+        if self.name.contains(NAMESPACE_SEPARATOR) {
+            return Err(anyhow!(
+                "{}: server names may not contain '{}' (it is used to namespace tools)",
+                key, NAMESPACE_SEPARATOR
+            ));
+        }
+
+        match self.transport_kind() {
+            TransportKind::Stdio => {
+                match self.command.as_deref() {
+                    None => return Err(anyhow!("{}.command: required for stdio servers", key)),
+                    Some(c) if c.trim().is_empty() => {
+                        return Err(anyhow!("{}.command: must not be empty", key));
+                    }
+                    Some(_) => {}
+                }
+                if self.url.is_some() {
+                    return Err(anyhow!("{}.url: not allowed together with `command`", key));
+                }
+            }
+            TransportKind::Http | TransportKind::Sse => {
+                let url = self.url.as_deref()
+                    .ok_or(anyhow!("{}.url: required for remote servers", key))?;
+                let parsed = reqwest::Url::parse(url)
+                    .map_err(|e| anyhow!("{}.url: invalid URL '{}': {}", key, url, e))?;
+                if !matches!(parsed.scheme(), "http" | "https") {
+                    return Err(anyhow!("{}.url: scheme must be http or https", key));
+                }
+                if self.command.is_some() {
+                    return Err(anyhow!("{}.command: not allowed for remote servers", key));
+                }
+            }
+        }
+
+        if self.timeout == Some(0) {
+            return Err(anyhow!("{}.timeout: must be at least 1 second", key));
+        }
+        for (tool, secs) in &self.tool_timeouts {
+            if *secs == 0 {
+                return Err(anyhow!("{}.toolTimeouts.{}: must be at least 1 second", key, tool));
+            }
+        }
+
+        if let Some(sandbox) = &self.sandbox {
+            if self.transport_kind() != TransportKind::Stdio {
+                return Err(anyhow!("{}.sandbox: only stdio servers can be sandboxed", key));
+            }
+            for (field, paths) in [("readOnly", &sandbox.read_only), ("writable", &sandbox.writable)] {
+                if paths.iter().any(|p| p.as_os_str().is_empty()) {
+                    return Err(anyhow!("{}.sandbox.{}: paths must not be empty", key, field));
+                }
+            }
+        }
+
+        if let Some(limits) = &self.limits {
+            if self.transport_kind() != TransportKind::Stdio {
+                return Err(anyhow!("{}.limits: only stdio servers can be limited", key));
+            }
+            let caps = [
+                ("memoryMb", limits.memory_mb),
+                ("cpuSeconds", limits.cpu_seconds),
+                ("openFiles", limits.open_files),
+                ("processes", limits.processes),
+                ("cpuPercent", limits.cpu_percent.map(u64::from)),
+            ];
+            for (field, value) in caps {
+                if value == Some(0) {
+                    return Err(anyhow!("{}.limits.{}: must be at least 1", key, field));
+                }
+            }
+            if let Some(processes) = limits.processes.filter(|p| *p < MIN_RLIMIT_PROCESSES && !limits.cgroup) {
+                return Err(anyhow!(
+                    "{}.limits.processes: {} is too low without \"cgroup\": true (it limits all of the user's \
+                     processes, not just this server's); use at least {} or a cgroup",
+                    key, processes, MIN_RLIMIT_PROCESSES
+                ));
+            }
+            if limits.cpu_percent.is_some() && !limits.cgroup {
+                return Err(anyhow!("{}.limits.cpuPercent: needs \"cgroup\": true", key));
+            }
+        }
+
+        for (name, value) in &self.env {
+            if !valid_env_name(name) {
+                return Err(anyhow!("{}.env.{}: invalid environment variable name", key, name));
+            }
+            // Syntax only; the secrets themselves are read at launch
+            expand_refs(value, |_| Ok(String::new()))
+                .map_err(|e| anyhow!("{}.env.{}: {}", key, name, e))?;
+        }
+        for (i, name) in self.inherit_env.iter().enumerate() {
+            if !valid_env_name(name) {
+                return Err(anyhow!("{}.inheritEnv[{}]: invalid environment variable name", key, i));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl AetherConfig {
+    // 1. Load from Disk
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path)
+            .context(format!("Failed to read config file: {}", path.display()))?;
+
+        let mut config = Self::parse(&content)
+            .context(format!("Invalid config file: {}", path.display()))?;
+
+        // Relative paths mean "next to the config file"
+        let base = path.parent().unwrap_or(Path::new(""));
+        config.permissions = base.join(&config.permissions);
+        for root in config.roots.iter_mut() {
+            *root = base.join(expand_home(root));
+        }
+        for server in config.mcp_servers.values_mut() {
+            server.base_dir = base.to_path_buf();
+            if let Some(cwd) = &server.cwd {
+                server.cwd = Some(base.join(expand_home(cwd)));
+            }
+            if let Some(sandbox) = &mut server.sandbox {
+                for path in sandbox.read_only.iter_mut().chain(sandbox.writable.iter_mut()) {
+                    *path = base.join(expand_home(path));
+                }
+            }
+        }
+
+        Ok(config)
+    }
+
+    // 2. Parse + Validate
+    pub fn parse(content: &str) -> Result<Self> {
+        let deserializer = &mut serde_json::Deserializer::from_str(content);
+        let mut config: AetherConfig = serde_path_to_error::deserialize(deserializer)
+            .map_err(|e| {
+                let path = e.path().to_string();
+                anyhow!("{}: {}", path, e.into_inner())
+            })?;
+
+        for (name, server) in config.mcp_servers.iter_mut() {
+            server.name = name.clone();
+            server.validate(&format!("mcpServers.{}", name))?;
+        }
+
+        if config.llm.model.trim().is_empty() {
+            return Err(anyhow!("llm.model: must not be empty"));
+        }
+        if let Some(i) = config.llm.sampling_models.iter().position(|m| m.trim().is_empty()) {
+            return Err(anyhow!("llm.samplingModels[{}]: must not be empty", i));
+        }
+        if let Some(i) = config.roots.iter().position(|r| r.as_os_str().is_empty()) {
+            return Err(anyhow!("roots[{}]: must not be empty", i));
+        }
+
+        Ok(config)
+    }
+
+    // 3. The roots, once they are known to be directories
+    pub fn granted_roots(&self) -> Result<Roots> {
+        for (i, root) in self.roots.iter().enumerate() {
+            if !root.is_dir() {
+                return Err(anyhow!("roots[{}]: '{}' is not a directory", i, root.display()));
+            }
+        }
+        Ok(Roots::new(self.roots.iter().cloned()))
+    }
+
+    // 4. The servers we should actually launch
+    pub fn servers(&self) -> Vec<ServerConfig> {
+        self.mcp_servers.values()
+            .filter(|s| !s.disabled)
+            .cloned()
+            .collect()
+    }
+}
+
+// "~/x" -> "$HOME/x" (anything else is left alone)
+pub fn expand_home(path: &Path) -> PathBuf {
+    match (path.strip_prefix("~"), std::env::var_os("HOME")) {
+        (Ok(rest), Some(home)) => PathBuf::from(home).join(rest),
+        _ => path.to_path_buf(),
+    }
+}
+
+fn valid_env_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains('=') && !name.contains('\0')
+}
+
+// A secret reference inside an `env` value
+enum SecretRef<'a> {
+    Env(&'a str),
+    File(&'a str),
+}
+
+// Replace every `${env:NAME}` / `${file:path}` in `value` using `resolve`
+fn expand_refs(value: &str, mut resolve: impl FnMut(SecretRef) -> Result<String>) -> Result<String> {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after.find('}').ok_or(anyhow!("unterminated '${{' in value"))?;
+        let reference = &after[..end];
+
+        let resolved = match reference.split_once(':') {
+            Some(("env", name)) if !name.is_empty() => resolve(SecretRef::Env(name))?,
+            Some(("file", path)) if !path.is_empty() => resolve(SecretRef::File(path))?,
+            _ => return Err(anyhow!(
+                "unknown reference '${{{}}}' (expected ${{env:NAME}} or ${{file:path}})",
+                reference
+            )),
+        };
+        out.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
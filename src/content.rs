@@ -0,0 +1,192 @@
+// src/content.rs
+// The Translator: MCP content blocks as the LLM and the TUI see them.
+// The LLM only reads text, so binary blocks are described instead; the TUI
+// gets one-line summaries like "[image 320x200 png]".
+use serde_json::Value;
+use crate::protocol::{CallToolResult, ContentBlock, Resource, ResourceContents};
+
+// How much of one resource we paste into the conversation
+pub const MAX_RESOURCE_CHARS: usize = 16_000;
+
+// A tool result as the tool message the LLM gets
+pub fn render_result(result: &CallToolResult) -> String {
+    let mut parts: Vec<String> = result.content.iter().map(render_block).collect();
+    if let Some(structured) = unmirrored(result) {
+        parts.push(format!("Structured result: {}", structured));
+    }
+    let body = match parts.is_empty() {
+        true => "(no content)".to_string(),
+        false => parts.join("\n\n"),
+    };
+    match result.is_error {
+        true => format!("Error: {}", body),
+        false => body,
+    }
+}
+
+// A tool result as one line for the log pane
+pub fn summarize_result(result: &CallToolResult) -> String {
+    let mut parts: Vec<String> = result.content.iter().map(summarize_block).collect();
+    if let Some(structured) = unmirrored(result) {
+        parts.push(structured.to_string());
+    }
+    let body = parts.join(" | ");
+    match result.is_error {
+        true => format!("ERROR: {}", body),
+        false => body,
+    }
+}
+
+// One block of content as text for the LLM
+pub fn render_block(block: &ContentBlock) -> String {
+    match block {
+        ContentBlock::Text { text } => text.clone(),
+        ContentBlock::Resource { resource } => render_contents(std::slice::from_ref(resource)),
+        ContentBlock::ResourceLink(link) => {
+            let about = link.description.as_deref().map(|d| format!(": {}", d)).unwrap_or_default();
+            format!("{}{}", describe_link(link), about)
+        }
+        other => summarize_block(other),
+    }
+}
+
+// One block of content as a short label
+pub fn summarize_block(block: &ContentBlock) -> String {
+    match block {
+        ContentBlock::Text { text } => text.clone(),
+        ContentBlock::Image { data, mime_type } => {
+            let size = decode_base64(data).as_deref().and_then(image_size);
+            match size {
+                Some((width, height)) => format!("[image {}x{} {}]", width, height, format_of(mime_type)),
+                None => format!("[image {}]", format_of(mime_type)),
+            }
+        }
+        ContentBlock::Audio { data, mime_type } => {
+            format!("[audio {}, {}]", format_of(mime_type), human_size(data.len() as u64 * 3 / 4))
+        }
+        ContentBlock::Resource { resource } => match resource.mime_type() {
+            Some(mime_type) => format!("[resource {} ({})]", resource.uri(), mime_type),
+            None => format!("[resource {}]", resource.uri()),
+        },
+        ContentBlock::ResourceLink(link) => describe_link(link),
+        ContentBlock::Unknown => "[unsupported content]".to_string(),
+    }
+}
+
+// Resource contents as text for the LLM (binary parts are only described)
+pub fn render_contents(contents: &[ResourceContents]) -> String {
+    let parts: Vec<String> = contents.iter().map(|part| match part {
+        ResourceContents::Text { text, .. } if text.chars().count() > MAX_RESOURCE_CHARS => {
+            let kept: String = text.chars().take(MAX_RESOURCE_CHARS).collect();
+            format!("{}\n[... truncated, {} characters in total]", kept, text.chars().count())
+        }
+        ResourceContents::Text { text, .. } => text.clone(),
+        ResourceContents::Blob { uri, mime_type, blob } => format!(
+            "[binary {} ({}), about {} bytes]",
+            uri,
+            mime_type.as_deref().unwrap_or("unknown type"),
+            blob.len() * 3 / 4
+        ),
+    }).collect();
+    parts.join("\n\n")
+}
+
+// Servers are asked to mirror structured content as JSON text; only show it
+// when they didn't
+fn unmirrored(result: &CallToolResult) -> Option<&Value> {
+    let structured = result.structured_content.as_ref()?;
+    let mirrored = result.content.iter().any(|block| match block {
+        ContentBlock::Text { text } => serde_json::from_str::<Value>(text).ok().as_ref() == Some(structured),
+        _ => false,
+    });
+    (!mirrored).then_some(structured)
+}
+
+fn describe_link(link: &Resource) -> String {
+    let mut details = Vec::new();
+    if let Some(mime_type) = &link.mime_type {
+        details.push(mime_type.clone());
+    }
+    if let Some(size) = link.size {
+        details.push(human_size(size));
+    }
+    match details.is_empty() {
+        true => format!("[link {} <{}>]", link.name, link.uri),
+        false => format!("[link {} <{}> ({})]", link.name, link.uri, details.join(", ")),
+    }
+}
+
+// "image/png" -> "png"
+fn format_of(mime_type: &str) -> &str {
+    mime_type.rsplit('/').next().unwrap_or(mime_type)
+}
+
+fn human_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+// Standard or URL-safe base64; whitespace is skipped and padding ends it
+pub fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(data.len() * 3 / 4);
+    let (mut buffer, mut bits) = (0u32, 0u32);
+    for byte in data.bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            b'=' => break,
+            b' ' | b'\t' | b'\r' | b'\n' => continue,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(out)
+}
+
+// Width and height from a PNG, GIF or JPEG header
+pub fn image_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    let be16 = |at: usize| Some(u32::from(u16::from_be_bytes([*bytes.get(at)?, *bytes.get(at + 1)?])));
+    let le16 = |at: usize| Some(u32::from(u16::from_le_bytes([*bytes.get(at)?, *bytes.get(at + 1)?])));
+    let be32 = |at: usize| Some(u32::from_be_bytes(bytes.get(at..at + 4)?.try_into().ok()?));
+
+    // A. PNG: the IHDR chunk comes first
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        return Some((be32(16)?, be32(20)?));
+    }
+    // B. GIF: right after the signature
+    if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        return Some((le16(6)?, le16(8)?));
+    }
+    // C. JPEG: walk the segments until a start-of-frame
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        let mut at = 2;
+        while at + 4 <= bytes.len() {
+            if bytes[at] != 0xFF {
+                return None;
+            }
+            let marker = bytes[at + 1];
+            if marker == 0xFF {
+                at += 1; // Fill byte
+                continue;
+            }
+            let is_frame = matches!(marker, 0xC0..=0xCF) && !matches!(marker, 0xC4 | 0xC8 | 0xCC);
+            if is_frame {
+                return Some((be16(at + 7)?, be16(at + 5)?));
+            }
+            at += 2 + be16(at + 2)? as usize;
+        }
+    }
+    None
+}
@@ -0,0 +1,59 @@
+// src/error.rs
+// Typed failures callers may want to react to.
+// Everything still travels as anyhow::Error; use `err.downcast_ref::<McpError>()`
+// to tell these apart from ordinary failures.
+use std::fmt;
+use std::time::Duration;
+use crate::schema::Violation;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum McpError {
+    // The server didn't answer within the deadline (a cancellation was sent)
+    Timeout { method: String, after: Duration },
+    // We gave up on the request ourselves (e.g. the user aborted)
+    Cancelled { method: String },
+    // The host is exiting and took the server down with it
+    Shutdown { method: String },
+    // The server was killed (or gave up) for going over a configured limit
+    ResourceLimit { server: String, limit: String },
+    // The server never advertised the capability this method needs (nothing was sent)
+    Unsupported { method: String, capability: String },
+    // The arguments don't match the tool's inputSchema (nothing was sent)
+    InvalidArguments { tool: String, violations: Vec<Violation> },
+    // The tool's structuredContent doesn't match its outputSchema
+    InvalidOutput { tool: String, violations: Vec<Violation> },
+}
+
+impl fmt::Display for McpError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            McpError::Timeout { method, after } => {
+                write!(f, "'{}' timed out after {:.1}s", method, after.as_secs_f64())
+            }
+            McpError::Cancelled { method } => write!(f, "'{}' was cancelled", method),
+            McpError::Shutdown { method } => write!(f, "'{}' aborted: the host is shutting down", method),
+            McpError::ResourceLimit { server, limit } => {
+                write!(f, "MCP server '{}' was stopped for exceeding its {}", server, limit)
+            }
+            McpError::Unsupported { method, capability } => {
+                write!(f, "'{}' is not available: the server did not advertise the '{}' capability", method, capability)
+            }
+            McpError::InvalidArguments { tool, violations } => {
+                write!(f, "Invalid arguments for tool '{}':", tool)?;
+                for violation in violations {
+                    write!(f, "\n- {}", violation)?;
+                }
+                Ok(())
+            }
+            McpError::InvalidOutput { tool, violations } => {
+                write!(f, "Tool '{}' returned output that does not match its outputSchema:", tool)?;
+                for violation in violations {
+                    write!(f, "\n- {}", violation)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for McpError {}
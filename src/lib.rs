@@ -1,7 +1,18 @@
 pub mod runtime;
+pub mod transport;
 pub mod protocol;
 pub mod security;
+pub mod roots;
+#[cfg(target_os = "linux")]
+pub mod sandbox;
 pub mod client;
+pub mod content;
+pub mod schema;
+pub mod config;
+pub mod error;
+pub mod registry;
+pub mod supervisor;
 pub mod llm;
+pub mod sampling;
 pub mod tui;
 pub mod agent;
\ No newline at end of file
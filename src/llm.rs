@@ -13,6 +13,12 @@ struct ChatCompletionRequest {
     messages: Vec<Message>,
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<GroqTool>>, 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    stop: Vec<String>,
 }
 
 // A single message in the conversation (User, Assistant, or Tool)
@@ -60,9 +66,33 @@ struct GroqFunctionDefinition {
 
 // --- 3. THE CLIENT ---
 
+// Groq's OpenAI-compatible endpoint
+pub const DEFAULT_ENDPOINT: &str = "https://api.groq.com/openai/v1/chat/completions";
+
+// Per-call knobs; everything left as None uses the provider's default
+#[derive(Debug, Clone, Default)]
+pub struct CompletionOptions {
+    // Overrides the client's model for this one call
+    pub model: Option<String>,
+    pub max_tokens: Option<u32>,
+    pub temperature: Option<f64>,
+    pub stop: Vec<String>,
+}
+
+// The reply plus the bookkeeping around it
+#[derive(Debug, Clone)]
+pub struct Completion {
+    pub message: Message,
+    pub model: String,
+    // "stop", "length", "tool_calls", ...
+    pub finish_reason: Option<String>,
+}
+
+#[derive(Clone)]
 pub struct LlmClient {
     api_key: String,
     client: reqwest::Client,
+    endpoint: String,
     pub model: String,
 }
 
@@ -76,17 +106,34 @@ impl LlmClient {
         Ok(Self {
             api_key,
             client: reqwest::Client::new(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
             model: model.to_string(),
         })
     }
 
+    // Builder-style: talk to another OpenAI-compatible endpoint
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
     // The Main Function: Send history -> Get Answer
     pub async fn send_completion(
         &self, 
         messages: &[Message], 
         tools: &[crate::protocol::Tool] // Take our internal tools
     ) -> Result<Message> {
-        
+        let completion = self.complete(messages, tools, &CompletionOptions::default()).await?;
+        Ok(completion.message)
+    }
+
+    // Like `send_completion`, with per-call options and the finish reason
+    pub async fn complete(
+        &self,
+        messages: &[Message],
+        tools: &[crate::protocol::Tool],
+        options: &CompletionOptions,
+    ) -> Result<Completion> {
         // A. Translate Tools (Our Struct -> Groq JSON)
         let groq_tools: Vec<GroqTool> = tools.iter().map(|t| {
             GroqTool {
@@ -100,14 +147,18 @@ impl LlmClient {
         }).collect();
 
         // B. Build Request
+        let model = options.model.clone().unwrap_or_else(|| self.model.clone());
         let request = ChatCompletionRequest {
-            model: self.model.clone(),
+            model: model.clone(),
             messages: messages.to_vec(),
             tools: if groq_tools.is_empty() { None } else { Some(groq_tools) },
+            max_tokens: options.max_tokens,
+            temperature: options.temperature,
+            stop: options.stop.clone(),
         };
 
         // C. Send HTTP Post
-        let res = self.client.post("https://api.groq.com/openai/v1/chat/completions")
+        let res = self.client.post(&self.endpoint)
             .header("Authorization", format!("Bearer {}", self.api_key))
             .json(&request)
             .send()
@@ -127,7 +178,10 @@ impl LlmClient {
         let message_value = response_json["choices"][0]["message"].clone();
         let message: Message = serde_json::from_value(message_value)
             .context("Failed to parse API response message")?;
+        let finish_reason = response_json["choices"][0]["finish_reason"].as_str().map(str::to_string);
+        // The provider may name the model more precisely than we did
+        let model = response_json["model"].as_str().map(str::to_string).unwrap_or(model);
 
-        Ok(message)
+        Ok(Completion { message, model, finish_reason })
     }
 }
\ No newline at end of file
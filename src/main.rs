@@ -1,41 +1,114 @@
 // src/main.rs
-use anyhow::Result;
+use std::sync::Arc;
+use anyhow::{anyhow, Result};
 use tokio::sync::mpsc;
+use std::path::PathBuf;
+use aether::config::{expand_home, AetherConfig, DEFAULT_CONFIG_PATH};
+use aether::client::RequestHandler;
 use aether::llm::LlmClient;
-use aether::runtime::McpProcess;
-use aether::client::McpClient;
+use aether::registry::{HandlerFactory, ServerRegistry, SHUTDOWN_GRACE};
+use aether::supervisor::RestartPolicy;
+use aether::sampling::SamplingHandler;
 use aether::security::SecurityConfig;
-use aether::tui::{self, App, UiMessage};
+use aether::tui::{self, AgentCommand, App, UiMessage};
 use aether::agent::Agent; // <--- Import your new Module
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // 1. SETUP CHANNELS
-    let (tx_agent, rx_agent) = mpsc::unbounded_channel::<String>();
+    let (tx_agent, rx_agent) = mpsc::unbounded_channel::<AgentCommand>();
     let (tx_ui, rx_ui) = mpsc::unbounded_channel::<UiMessage>();
+    let (tx_abort, rx_abort) = mpsc::unbounded_channel::<()>();
 
     // 2. SETUP DEPENDENCIES
     // We do the dangerous setup here, but handle errors gracefully with '?'
-    let security = SecurityConfig::load("permissions.json")?;
-    
-    // NOTE: Ensure this path matches your OS (Windows: mock_tool.exe)
-    let tool_path = "target/debug/mock_tool.exe"; 
-    let process = McpProcess::start(tool_path, &[])?;
-    
-    let mut client = McpClient::new(process, security);
-    client.initialize().await?; // Handshake
+    let args = CliArgs::parse()?;
+    let mut config = AetherConfig::load(&args.config)?;
+    config.roots.extend(args.roots.iter().map(|root| expand_home(root)));
+    let roots = config.granted_roots()?;
+    let security = SecurityConfig::load(&config.permissions.to_string_lossy())?;
 
-    let llm = LlmClient::new("llama-3.3-70b-versatile")?;
+    let llm = LlmClient::new(&config.llm.model)?;
+
+    // Servers may borrow the LLM (sampling), once the user approves in the TUI
+    let handlers: HandlerFactory = {
+        let (llm, models, tx_ui) = (llm.clone(), config.llm.sampling_models.clone(), tx_ui.clone());
+        Arc::new(move |server: &str| -> Arc<dyn RequestHandler> {
+            Arc::new(SamplingHandler::new(server, llm.clone(), models.clone(), tx_ui.clone()))
+        })
+    };
+
+    // Launch + handshake + tools/list for every server
+    let registry = Arc::new(
+        ServerRegistry::start_with_handlers(&config.servers(), &security, RestartPolicy::default(), handlers, roots)
+            .await?,
+    );
 
     // 3. SPAWN THE BRAIN (Now just 2 lines!)
+    let agent = Agent::new(tx_ui.clone(), rx_agent, rx_abort, registry.clone(), llm);
+    tokio::spawn(agent.run());
+
+    // Ctrl-C / SIGTERM from outside: ask the TUI to close like Esc would
     tokio::spawn(async move {
-        let agent = Agent::new(tx_ui, rx_agent, client, llm);
-        agent.run().await;
+        shutdown_signal().await;
+        let _ = tx_ui.send(UiMessage::Quit);
     });
 
     // 4. START THE FACE
-    let app = App::new(tx_agent);
-    tui::run_tui(app, rx_ui).await?;
+    let app = App::new(tx_agent, tx_abort);
+    let result = tui::run_tui(app, rx_ui).await;
+
+    // 5. TEAR DOWN (whatever made the TUI stop, the servers go with it)
+    registry.shutdown(SHUTDOWN_GRACE).await;
+    result
+}
+
+// Resolves on Ctrl-C, or SIGTERM on unix
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut term) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = term.recv() => {}
+                }
+            }
+            Err(_) => {
+                let _ = tokio::signal::ctrl_c().await;
+            }
+        }
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
+}
+
+// `aether [--config <path>] [--root <dir>]...`
+struct CliArgs {
+    config: String,
+    // Granted on top of the config's `roots` (relative to the working directory)
+    roots: Vec<PathBuf>,
+}
+
+impl CliArgs {
+    fn parse() -> Result<Self> {
+        let mut args = std::env::args().skip(1);
+        let mut parsed = Self { config: DEFAULT_CONFIG_PATH.to_string(), roots: Vec::new() };
 
-    Ok(())
-}
\ No newline at end of file
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" | "-c" => {
+                    parsed.config = args.next().ok_or(anyhow!("--config needs a path"))?;
+                }
+                "--root" | "-r" => {
+                    parsed.roots.push(args.next().ok_or(anyhow!("--root needs a directory"))?.into());
+                }
+                other => return Err(anyhow!("Unknown argument: {}", other)),
+            }
+        }
+        Ok(parsed)
+    }
+}
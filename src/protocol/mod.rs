@@ -1,46 +1,270 @@
 // src/protocol/mod.rs
-use serde::{Deserialize, Serialize};
+use std::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
-// 1. The Request Struct
-#[derive(Debug, Serialize, Deserialize)]
+// Every message we send or accept must say this
+pub const JSONRPC_VERSION: &str = "2.0";
+
+// 0. Request ids: the spec allows strings or (integral) numbers.
+// We always send numbers, but servers are free to use strings for theirs.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum RequestId {
+    Number(i64),
+    String(String),
+}
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestId::Number(n) => write!(f, "{}", n),
+            RequestId::String(s) => write!(f, "\"{}\"", s),
+        }
+    }
+}
+
+impl From<i64> for RequestId {
+    fn from(n: i64) -> Self {
+        RequestId::Number(n)
+    }
+}
+
+impl From<&str> for RequestId {
+    fn from(s: &str) -> Self {
+        RequestId::String(s.to_string())
+    }
+}
+
+// 1. A request: expects exactly one response with the same id
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct JsonRpcRequest {
     pub jsonrpc: String,
+    pub id: RequestId,
     pub method: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub params: Option<Value>,
-    pub id: Option<u64>,
 }
 
 impl JsonRpcRequest {
-    pub fn new(method: &str, params: Option<Value>, id: Option<u64>) -> Self {
+    pub fn new(id: impl Into<RequestId>, method: &str, params: Option<Value>) -> Self {
         Self {
-            jsonrpc: "2.0".to_string(),
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            id: id.into(),
             method: method.to_string(),
             params,
-            id,
         }
     }
 }
 
-// 2. The Response Struct
-#[derive(Debug, Serialize, Deserialize)]
+// 2. A notification: like a request, but without an id, and never answered
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcNotification {
+    pub jsonrpc: String,
+    pub method: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcNotification {
+    pub fn new(method: &str, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: method.to_string(),
+            params,
+        }
+    }
+}
+
+// 3. A successful response
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct JsonRpcResponse {
     pub jsonrpc: String,
-    pub result: Option<Value>,
-    pub error: Option<JsonRpcError>,
-    pub id: Option<u64>,
+    pub id: RequestId,
+    pub result: Value,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl JsonRpcResponse {
+    pub fn new(id: RequestId, result: Value) -> Self {
+        Self { jsonrpc: JSONRPC_VERSION.to_string(), id, result }
+    }
+}
+
+// 4. A failed response. The id is null when the request was too broken to
+// read one (parse errors, invalid requests).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcErrorResponse {
+    pub jsonrpc: String,
+    pub id: Option<RequestId>,
+    pub error: JsonRpcError,
+}
+
+impl JsonRpcErrorResponse {
+    pub fn new(id: Option<RequestId>, error: JsonRpcError) -> Self {
+        Self { jsonrpc: JSONRPC_VERSION.to_string(), id, error }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct JsonRpcError {
-    pub code: i32,
+    pub code: ErrorCode,
     pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub data: Option<Value>,
 }
 
+impl JsonRpcError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self { code, message: message.into(), data: None }
+    }
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+
+impl std::error::Error for JsonRpcError {}
+
+// The error codes the spec defines. Anything else is kept as-is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "i32", into = "i32")]
+pub enum ErrorCode {
+    ParseError,     // -32700: invalid JSON
+    InvalidRequest, // -32600: JSON, but not a valid message
+    MethodNotFound, // -32601
+    InvalidParams,  // -32602
+    InternalError,  // -32603
+    // -32000 to -32099: reserved for implementation-defined server errors
+    ServerError(i32),
+    // Application-defined
+    Other(i32),
+}
+
+impl From<i32> for ErrorCode {
+    fn from(code: i32) -> Self {
+        match code {
+            -32700 => ErrorCode::ParseError,
+            -32600 => ErrorCode::InvalidRequest,
+            -32601 => ErrorCode::MethodNotFound,
+            -32602 => ErrorCode::InvalidParams,
+            -32603 => ErrorCode::InternalError,
+            -32099..=-32000 => ErrorCode::ServerError(code),
+            other => ErrorCode::Other(other),
+        }
+    }
+}
+
+impl From<ErrorCode> for i32 {
+    fn from(code: ErrorCode) -> Self {
+        match code {
+            ErrorCode::ParseError => -32700,
+            ErrorCode::InvalidRequest => -32600,
+            ErrorCode::MethodNotFound => -32601,
+            ErrorCode::InvalidParams => -32602,
+            ErrorCode::InternalError => -32603,
+            ErrorCode::ServerError(code) | ErrorCode::Other(code) => code,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", i32::from(*self))
+    }
+}
+
+// 5. Any single message, whichever way it travels.
+// Which kind it is follows from the members present, so decoding is done by
+// hand instead of trusting `#[serde(untagged)]` to guess.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum JsonRpcMessage {
+    Request(JsonRpcRequest),
+    Notification(JsonRpcNotification),
+    Response(JsonRpcResponse),
+    Error(JsonRpcErrorResponse),
+}
+
+impl TryFrom<Value> for JsonRpcMessage {
+    type Error = String;
+
+    fn try_from(value: Value) -> Result<Self, String> {
+        let Some(fields) = value.as_object() else {
+            return Err(format!("expected a JSON object, got {}", value));
+        };
+        if fields.get("jsonrpc").and_then(Value::as_str) != Some(JSONRPC_VERSION) {
+            return Err(format!("\"jsonrpc\" must be \"{}\"", JSONRPC_VERSION));
+        }
+
+        let has = |key: &str| fields.contains_key(key);
+        let kind = match (has("method"), has("id"), has("result"), has("error")) {
+            (true, true, false, false) => "request",
+            (true, false, false, false) => "notification",
+            (false, true, true, false) => "response",
+            (false, true, false, true) => "error response",
+            (false, _, true, true) => return Err("a response can't have both \"result\" and \"error\"".to_string()),
+            (true, _, _, _) => return Err("a request can't have \"result\" or \"error\"".to_string()),
+            _ => return Err("not a request, notification or response".to_string()),
+        };
+
+        let message = match kind {
+            "request" => serde_json::from_value(value).map(JsonRpcMessage::Request),
+            "notification" => serde_json::from_value(value).map(JsonRpcMessage::Notification),
+            "response" => serde_json::from_value(value).map(JsonRpcMessage::Response),
+            _ => serde_json::from_value(value).map(JsonRpcMessage::Error),
+        };
+        message.map_err(|e| format!("invalid {}: {}", kind, e))
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonRpcMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        JsonRpcMessage::try_from(Value::deserialize(deserializer)?).map_err(de::Error::custom)
+    }
+}
+
+// 6. What one read off the wire holds: a message, or a batch of them
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum JsonRpcFrame {
+    Single(JsonRpcMessage),
+    Batch(Vec<JsonRpcMessage>),
+}
+
+impl JsonRpcFrame {
+    pub fn into_messages(self) -> Vec<JsonRpcMessage> {
+        match self {
+            JsonRpcFrame::Single(message) => vec![message],
+            JsonRpcFrame::Batch(messages) => messages,
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for JsonRpcFrame {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        match Value::deserialize(deserializer)? {
+            Value::Array(items) if items.is_empty() => Err(de::Error::custom("empty batch")),
+            Value::Array(items) => items.into_iter()
+                .map(JsonRpcMessage::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map(JsonRpcFrame::Batch)
+                .map_err(de::Error::custom),
+            single => JsonRpcMessage::try_from(single)
+                .map(JsonRpcFrame::Single)
+                .map_err(de::Error::custom),
+        }
+    }
+}
+
 // --- MCP SPECIFIC TYPES (FIXED) ---
 
+// Protocol revisions we speak, newest first. We ask for the newest and
+// accept whichever of these the server answers with.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+pub const LATEST_PROTOCOL_VERSION: &str = SUPPORTED_PROTOCOL_VERSIONS[0];
+
 // We add this line to ALL MCP structs to handle the case conversion
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")] 
@@ -50,10 +274,18 @@ pub struct InitializeParams {
     pub client_info: ClientInfo,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// What we can do for servers. An absent field means "not supported".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ClientCapabilities {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub experimental: Option<serde_json::Value>,
+    // We run `sampling/createMessage` for servers
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sampling: Option<serde_json::Value>,
+    // We answer `roots/list` (and say when the list changes)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub roots: Option<serde_json::Value>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -63,15 +295,89 @@ pub struct ClientInfo {
     pub version: String,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct InitializeResult {
     pub protocol_version: String,
-    pub capabilities: serde_json::Value,
+    pub capabilities: ServerCapabilities,
     pub server_info: ServerInfo,
+    // Hints for the LLM on how to use this server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instructions: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+// What a server offers. An absent field means "not supported".
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerCapabilities {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub experimental: Option<serde_json::Value>,
+    // Accepts `logging/setLevel` and sends `notifications/message`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logging: Option<serde_json::Value>,
+    // Answers `completion/complete`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completions: Option<serde_json::Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompts: Option<PromptsCapability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resources: Option<ResourcesCapability>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<ToolsCapability>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptsCapability {
+    #[serde(default)]
+    pub list_changed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourcesCapability {
+    #[serde(default)]
+    pub subscribe: bool,
+    #[serde(default)]
+    pub list_changed: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolsCapability {
+    #[serde(default)]
+    pub list_changed: bool,
+}
+
+impl ServerCapabilities {
+    // Did the server declare this capability ("tools", "resources", ...)?
+    pub fn has(&self, capability: &str) -> bool {
+        match capability {
+            "experimental" => self.experimental.is_some(),
+            "logging" => self.logging.is_some(),
+            "completions" => self.completions.is_some(),
+            "prompts" => self.prompts.is_some(),
+            "resources" => self.resources.is_some(),
+            "tools" => self.tools.is_some(),
+            _ => false,
+        }
+    }
+
+    // The capability a request method needs, if any ("ping" needs none)
+    pub fn required_for(method: &str) -> Option<&'static str> {
+        let (family, _) = method.split_once('/')?;
+        match family {
+            "tools" => Some("tools"),
+            "resources" => Some("resources"),
+            "prompts" => Some("prompts"),
+            "logging" => Some("logging"),
+            "completion" => Some("completions"),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ServerInfo {
     pub name: String,
@@ -84,13 +390,358 @@ pub struct ServerInfo {
 #[serde(rename_all = "camelCase")]
 pub struct ListToolsResult {
     pub tools: Vec<Tool>,
+    pub next_cursor: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tool {
     pub name: String,
     pub description: Option<String>,
     // The "inputSchema" tells the LLM what arguments the tool needs.
     pub input_schema: serde_json::Value, 
-}
\ No newline at end of file
+    // The shape of `structuredContent` in its results (2025-06-18), if it promises one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_schema: Option<serde_json::Value>,
+}
+
+// What `tools/call` answers with. `isError` means the tool ran and failed
+// (the LLM should see why); protocol failures arrive as JSON-RPC errors instead.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CallToolResult {
+    #[serde(default)]
+    pub content: Vec<ContentBlock>,
+    // Machine-readable result (2025-06-18), usually mirrored as JSON text in `content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_content: Option<serde_json::Value>,
+    #[serde(default)]
+    pub is_error: bool,
+}
+
+impl CallToolResult {
+    // The structured content as a Rust type (None if the tool sent none)
+    pub fn structured<T: de::DeserializeOwned>(&self) -> serde_json::Result<Option<T>> {
+        self.structured_content.clone().map(serde_json::from_value).transpose()
+    }
+}
+
+// --- RESOURCE TYPES ---
+// Resources are read-only context a server offers (files, rows, pages...),
+// addressed by URI. Listing is paginated via `nextCursor`.
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Resource {
+    pub uri: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+    // In bytes, if the server knows
+    pub size: Option<u64>,
+}
+
+// A family of resources, e.g. "file:///{path}" (RFC 6570)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceTemplate {
+    pub uri_template: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub mime_type: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourcesResult {
+    pub resources: Vec<Resource>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListResourceTemplatesResult {
+    pub resource_templates: Vec<ResourceTemplate>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReadResourceResult {
+    pub contents: Vec<ResourceContents>,
+}
+
+// One piece of a resource: text, or binary data as base64
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ResourceContents {
+    Text {
+        uri: String,
+        #[serde(rename = "mimeType")]
+        mime_type: Option<String>,
+        text: String,
+    },
+    Blob {
+        uri: String,
+        #[serde(rename = "mimeType")]
+        mime_type: Option<String>,
+        blob: String,
+    },
+}
+
+impl ResourceContents {
+    pub fn uri(&self) -> &str {
+        match self {
+            ResourceContents::Text { uri, .. } | ResourceContents::Blob { uri, .. } => uri,
+        }
+    }
+
+    pub fn mime_type(&self) -> Option<&str> {
+        match self {
+            ResourceContents::Text { mime_type, .. } | ResourceContents::Blob { mime_type, .. } => {
+                mime_type.as_deref()
+            }
+        }
+    }
+}
+
+// --- PROMPT TYPES ---
+// Prompts are canned conversations a server offers, filled in with arguments.
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Prompt {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub arguments: Vec<PromptArgument>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptArgument {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub required: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListPromptsResult {
+    pub prompts: Vec<Prompt>,
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GetPromptResult {
+    pub description: Option<String>,
+    pub messages: Vec<PromptMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptMessage {
+    pub role: String, // "user" or "assistant"
+    pub content: ContentBlock,
+}
+
+// One piece of message content, tagged by "type"
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    // Base64 data
+    Image {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    Audio {
+        data: String,
+        #[serde(rename = "mimeType")]
+        mime_type: String,
+    },
+    // A resource embedded in the message
+    Resource {
+        resource: ResourceContents,
+    },
+    // A pointer to a resource that can be read separately
+    ResourceLink(Resource),
+    // Something newer than we know about
+    #[serde(other)]
+    Unknown,
+}
+
+// --- SAMPLING TYPES ---
+// A server asking the host to run an LLM completion for it.
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageParams {
+    pub messages: Vec<SamplingMessage>,
+    pub model_preferences: Option<ModelPreferences>,
+    pub system_prompt: Option<String>,
+    // "none", "thisServer" or "allServers"; we never share other context
+    pub include_context: Option<String>,
+    pub temperature: Option<f64>,
+    pub max_tokens: u32,
+    #[serde(default)]
+    pub stop_sequences: Vec<String>,
+    pub metadata: Option<Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SamplingMessage {
+    pub role: String, // "user" or "assistant"
+    pub content: ContentBlock,
+}
+
+// Soft wishes: hints are model name fragments, priorities go from 0 to 1
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPreferences {
+    #[serde(default)]
+    pub hints: Vec<ModelHint>,
+    pub cost_priority: Option<f64>,
+    pub speed_priority: Option<f64>,
+    pub intelligence_priority: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelHint {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateMessageResult {
+    pub role: String,
+    pub content: ContentBlock,
+    pub model: String,
+    // "endTurn", "stopSequence", "maxTokens", or whatever the provider said
+    pub stop_reason: Option<String>,
+}
+
+// --- ROOTS TYPES ---
+// The directories the user has granted, so servers know where to work.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Root {
+    // Always a file:// URI
+    pub uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListRootsResult {
+    pub roots: Vec<Root>,
+}
+
+// --- PROGRESS TYPES ---
+// A request that carries `_meta.progressToken` may get `notifications/progress`
+// updates until it is answered. Tokens are strings or numbers, like ids.
+pub type ProgressToken = RequestId;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgressNotification {
+    pub progress_token: ProgressToken,
+    // Only ever goes up, even when the total is unknown
+    pub progress: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ProgressNotification {
+    // How far along, from 0 to 1 (None when the server didn't say how much there is)
+    pub fn ratio(&self) -> Option<f64> {
+        let total = self.total.filter(|total| *total > 0.0)?;
+        Some((self.progress / total).clamp(0.0, 1.0))
+    }
+}
+
+// --- LOGGING TYPES ---
+// Servers with the `logging` capability send `notifications/message` at or
+// above the level set by `logging/setLevel` (syslog severities, RFC 5424).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoggingLevel {
+    Debug,
+    Info,
+    Notice,
+    Warning,
+    Error,
+    Critical,
+    Alert,
+    Emergency,
+}
+
+impl LoggingLevel {
+    pub const ALL: [LoggingLevel; 8] = [
+        LoggingLevel::Debug,
+        LoggingLevel::Info,
+        LoggingLevel::Notice,
+        LoggingLevel::Warning,
+        LoggingLevel::Error,
+        LoggingLevel::Critical,
+        LoggingLevel::Alert,
+        LoggingLevel::Emergency,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoggingLevel::Debug => "debug",
+            LoggingLevel::Info => "info",
+            LoggingLevel::Notice => "notice",
+            LoggingLevel::Warning => "warning",
+            LoggingLevel::Error => "error",
+            LoggingLevel::Critical => "critical",
+            LoggingLevel::Alert => "alert",
+            LoggingLevel::Emergency => "emergency",
+        }
+    }
+}
+
+impl fmt::Display for LoggingLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for LoggingLevel {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let wanted = s.trim().to_ascii_lowercase();
+        LoggingLevel::ALL.into_iter().find(|level| level.as_str() == wanted).ok_or_else(|| {
+            let names: Vec<&str> = LoggingLevel::ALL.iter().map(LoggingLevel::as_str).collect();
+            format!("unknown log level '{}' (expected one of: {})", s, names.join(", "))
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LoggingMessageNotification {
+    pub level: LoggingLevel,
+    // Which part of the server is talking, if it says
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub logger: Option<String>,
+    // Any JSON; usually a string
+    pub data: Value,
+}
+
+impl LoggingMessageNotification {
+    // `data` as one line of text (strings without their quotes)
+    pub fn text(&self) -> String {
+        match &self.data {
+            Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }
+    }
+}
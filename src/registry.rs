@@ -0,0 +1,707 @@
+// src/registry.rs
+// The Switchboard: many MCP servers behind one tool list.
+// Each server gets its own McpClient. We merge their tools into one list for
+// the LLM and remember which server owns which tool so calls get routed back.
+// A supervisor task per server restarts it if the connection dies.
+// Resources and prompts aren't merged: they stay grouped by the server that offers them.
+use std::collections::{BTreeSet, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock, Weak};
+use std::time::Duration;
+use anyhow::{anyhow, Context, Result};
+use serde_json::Value;
+use tokio::sync::{broadcast, mpsc};
+use tokio::task::{JoinHandle, JoinSet};
+use crate::client::{DefaultRequestHandler, McpClient, RequestHandler};
+use crate::config::{ServerConfig, TransportKind};
+use crate::protocol::{
+    GetPromptResult, JsonRpcNotification, LoggingLevel, LoggingMessageNotification, ProgressNotification,
+    Prompt, Resource, ResourceContents, ResourceTemplate, Tool,
+};
+use crate::runtime::stderr::StderrLine;
+use crate::runtime::{LaunchOptions, McpProcess};
+use crate::roots::Roots;
+use crate::security::SecurityConfig;
+use crate::supervisor::{self, HealthEvent, RestartPolicy, ServerHealth};
+use crate::transport::{LegacySseTransport, StreamableHttpTransport};
+
+// Separator used when two servers expose the same tool name
+pub const NAMESPACE_SEPARATOR: &str = "__";
+
+// How many health changes a slow listener may fall behind by
+const HEALTH_BUFFER: usize = 64;
+
+// How long each server gets per shutdown step (EOF, then SIGTERM) before we escalate
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(2);
+
+// How many stderr lines a slow listener may fall behind by
+const STDERR_BUFFER: usize = 256;
+
+// How many resource updates a slow listener may fall behind by
+const RESOURCE_BUFFER: usize = 64;
+
+// How many tool list changes a slow listener may fall behind by
+const TOOLS_BUFFER: usize = 64;
+
+// How many server log messages a slow listener may fall behind by
+const LOG_BUFFER: usize = 256;
+
+// What one server offers besides tools
+#[derive(Debug, Clone)]
+pub struct ServerResources {
+    pub server: String,
+    pub resources: Vec<Resource>,
+    pub templates: Vec<ResourceTemplate>,
+}
+
+// The prompts one server offers
+#[derive(Debug, Clone)]
+pub struct ServerPrompts {
+    pub server: String,
+    pub prompts: Vec<Prompt>,
+}
+
+// A subscribed resource changed on the server (read it again to see how)
+#[derive(Debug, Clone)]
+pub struct ResourceUpdate {
+    pub server: String,
+    pub uri: String,
+}
+
+// A `notifications/message` log event from a server
+#[derive(Debug, Clone)]
+pub struct ServerLog {
+    pub server: String,
+    pub message: LoggingMessageNotification,
+}
+
+// A server's tool list changed at runtime and the catalog now reflects it
+#[derive(Debug, Clone)]
+pub struct ToolsChanged {
+    pub server: String,
+    pub count: usize,
+}
+
+// Builds the handler for requests a server sends us, given the server's name.
+// Called again for every restart, before the handshake.
+pub type HandlerFactory = Arc<dyn Fn(&str) -> Arc<dyn RequestHandler> + Send + Sync>;
+
+// Everything a server offers, fetched right after the handshake
+struct Listings {
+    tools: Vec<Tool>,
+    resources: Vec<Resource>,
+    templates: Vec<ResourceTemplate>,
+    prompts: Vec<Prompt>,
+    // Subscribed before tools/list, so no `tools/list_changed` slips through
+    notifications: broadcast::Receiver<JsonRpcNotification>,
+}
+
+// A connected server. The client and tools are swapped out on restart.
+pub(crate) struct ServerSlot {
+    pub(crate) name: String,
+    config: ServerConfig,
+    state: RwLock<SlotState>,
+}
+
+struct SlotState {
+    client: Arc<McpClient>,
+    tools: Vec<Tool>,
+    resources: Vec<Resource>,
+    templates: Vec<ResourceTemplate>,
+    prompts: Vec<Prompt>,
+    // Resource URIs we subscribed to; renewed after every restart
+    subscriptions: BTreeSet<String>,
+    health: ServerHealth,
+}
+
+impl ServerSlot {
+    pub(crate) fn client(&self) -> Arc<McpClient> {
+        self.state.read().unwrap().client.clone()
+    }
+}
+
+// Where an exposed tool name really lives
+struct Route {
+    server: usize,
+    tool_name: String,
+}
+
+// The merged view, rebuilt whenever a server's tools change
+#[derive(Default)]
+struct Catalog {
+    // Exposed (possibly namespaced) tool name -> owner
+    routes: HashMap<String, Route>,
+    // The merged list we hand to the LLM
+    tools: Vec<Tool>,
+}
+
+// What every connection attempt needs, at startup and on restart
+struct Wiring {
+    security: SecurityConfig,
+    stderr_tx: broadcast::Sender<StderrLine>,
+    resource_tx: broadcast::Sender<ResourceUpdate>,
+    log_tx: broadcast::Sender<ServerLog>,
+    handlers: HandlerFactory,
+    roots: Roots,
+}
+
+// Everything the supervisors share with the registry
+pub(crate) struct RegistryInner {
+    pub(crate) servers: Vec<ServerSlot>,
+    wiring: Wiring,
+    catalog: RwLock<Catalog>,
+    health_tx: broadcast::Sender<HealthEvent>,
+    tools_tx: broadcast::Sender<ToolsChanged>,
+    shutting_down: AtomicBool,
+}
+
+pub struct ServerRegistry {
+    inner: Arc<RegistryInner>,
+    // Servers that failed to come up, with the reason
+    failures: Vec<(String, String)>,
+    supervisors: Vec<JoinHandle<()>>,
+}
+
+impl ServerRegistry {
+    // 1. Launch every server, handshake, and collect its tools.
+    // A server that fails to start is recorded and skipped so one broken
+    // entry doesn't take the whole host down.
+    pub async fn start(configs: &[ServerConfig], security: &SecurityConfig) -> Result<Self> {
+        Self::start_with_policy(configs, security, RestartPolicy::default()).await
+    }
+
+    pub async fn start_with_policy(
+        configs: &[ServerConfig],
+        security: &SecurityConfig,
+        policy: RestartPolicy,
+    ) -> Result<Self> {
+        let handlers: HandlerFactory = Arc::new(|_: &str| -> Arc<dyn RequestHandler> { Arc::new(DefaultRequestHandler) });
+        Self::start_with_handlers(configs, security, policy, handlers, Roots::default()).await
+    }
+
+    // Like `start_with_policy`, with our side of server-initiated requests
+    // (sampling, ...) and the directories the user granted
+    pub async fn start_with_handlers(
+        configs: &[ServerConfig],
+        security: &SecurityConfig,
+        policy: RestartPolicy,
+        handlers: HandlerFactory,
+        roots: Roots,
+    ) -> Result<Self> {
+        let mut servers: Vec<ServerSlot> = Vec::new();
+        let mut failures = Vec::new();
+        let wiring = Wiring {
+            security: security.clone(),
+            stderr_tx: broadcast::channel(STDERR_BUFFER).0,
+            resource_tx: broadcast::channel(RESOURCE_BUFFER).0,
+            log_tx: broadcast::channel(LOG_BUFFER).0,
+            handlers,
+            roots,
+        };
+
+        let mut watches = Vec::new();
+        for config in configs {
+            if servers.iter().any(|s| s.name == config.name) {
+                return Err(anyhow!("Duplicate server name '{}'", config.name));
+            }
+
+            match connect(config, &wiring).await {
+                Ok((client, listings)) => {
+                    watches.push((servers.len(), listings.notifications));
+                    servers.push(ServerSlot {
+                        name: config.name.clone(),
+                        config: config.clone(),
+                        state: RwLock::new(SlotState {
+                            client: Arc::new(client),
+                            tools: listings.tools,
+                            resources: listings.resources,
+                            templates: listings.templates,
+                            prompts: listings.prompts,
+                            subscriptions: BTreeSet::new(),
+                            health: ServerHealth::Healthy,
+                        }),
+                    });
+                }
+                Err(e) => failures.push((config.name.clone(), format!("{:#}", e))),
+            }
+        }
+
+        let (health_tx, _) = broadcast::channel(HEALTH_BUFFER);
+        let inner = Arc::new(RegistryInner {
+            servers,
+            wiring,
+            catalog: RwLock::new(Catalog::default()),
+            health_tx,
+            tools_tx: broadcast::channel(TOOLS_BUFFER).0,
+            shutting_down: AtomicBool::new(false),
+        });
+        inner.rebuild_catalog();
+        for (index, notifications) in watches {
+            watch_tools(Arc::downgrade(&inner), index, notifications);
+        }
+
+        let supervisors = (0..inner.servers.len())
+            .map(|index| tokio::spawn(supervisor::supervise(inner.clone(), index, policy.clone())))
+            .collect();
+
+        Ok(Self { inner, failures, supervisors })
+    }
+
+    // The merged tool list (names are what the LLM should call)
+    pub fn tools(&self) -> Vec<Tool> {
+        self.inner.catalog.read().unwrap().tools.clone()
+    }
+
+    // Names of the servers that are connected
+    pub fn server_names(&self) -> Vec<&str> {
+        self.inner.servers.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    // Current health of every server
+    pub fn health(&self) -> Vec<(String, ServerHealth)> {
+        self.inner.servers.iter()
+            .map(|s| (s.name.clone(), s.state.read().unwrap().health.clone()))
+            .collect()
+    }
+
+    // Listen for health changes (crashes, restarts, giving up)
+    pub fn health_events(&self) -> broadcast::Receiver<HealthEvent> {
+        self.inner.health_tx.subscribe()
+    }
+
+    // The granted directories. Changes reach every server right away
+    // (sandboxes only pick them up when the server restarts).
+    pub fn roots(&self) -> Roots {
+        self.inner.wiring.roots.clone()
+    }
+
+    // Listen for stderr output from every stdio server
+    pub fn stderr_lines(&self) -> broadcast::Receiver<StderrLine> {
+        self.inner.wiring.stderr_tx.subscribe()
+    }
+
+    // Resources and templates, per server (servers without any are left out)
+    pub fn resources(&self) -> Vec<ServerResources> {
+        self.inner.servers.iter()
+            .map(|s| {
+                let state = s.state.read().unwrap();
+                ServerResources {
+                    server: s.name.clone(),
+                    resources: state.resources.clone(),
+                    templates: state.templates.clone(),
+                }
+            })
+            .filter(|offer| !offer.resources.is_empty() || !offer.templates.is_empty())
+            .collect()
+    }
+
+    // Prompts, per server (servers without any are left out)
+    pub fn prompts(&self) -> Vec<ServerPrompts> {
+        self.inner.servers.iter()
+            .map(|s| ServerPrompts { server: s.name.clone(), prompts: s.state.read().unwrap().prompts.clone() })
+            .filter(|offer| !offer.prompts.is_empty())
+            .collect()
+    }
+
+    // Listen for servers changing their tools at runtime (the catalog is already updated)
+    pub fn tool_changes(&self) -> broadcast::Receiver<ToolsChanged> {
+        self.inner.tools_tx.subscribe()
+    }
+
+    // Listen for changes to subscribed resources
+    pub fn resource_updates(&self) -> broadcast::Receiver<ResourceUpdate> {
+        self.inner.wiring.resource_tx.subscribe()
+    }
+
+    // Listen for log messages from every server (they also go to `tracing`)
+    pub fn server_logs(&self) -> broadcast::Receiver<ServerLog> {
+        self.inner.wiring.log_tx.subscribe()
+    }
+
+    // Servers that failed to start: (name, reason)
+    pub fn failures(&self) -> &[(String, String)] {
+        &self.failures
+    }
+
+    // 2. Route a call to the server that owns the tool
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> Result<Value> {
+        self.call_tool_with_progress(name, arguments, None).await
+    }
+
+    // Like `call_tool`, with the server's progress updates sent to `progress`
+    pub async fn call_tool_with_progress(
+        &self,
+        name: &str,
+        arguments: Value,
+        progress: Option<mpsc::UnboundedSender<ProgressNotification>>,
+    ) -> Result<Value> {
+        let (index, tool_name) = {
+            let catalog = self.inner.catalog.read().unwrap();
+            let route = catalog.routes.get(name)
+                .ok_or(anyhow!("Unknown tool '{}'", name))?;
+            (route.server, route.tool_name.clone())
+        };
+
+        // Permissions go by the names the LLM sees, so a rule can single out one server's tool
+        let qualified = format!("{}{}{}", self.inner.servers[index].name, NAMESPACE_SEPARATOR, tool_name);
+        if !self.inner.wiring.security.check_tool_permission(name, &qualified) {
+            return Err(anyhow!("SECURITY ALERT: Tool '{}' is blocked by permissions.json", name));
+        }
+
+        self.inner.healthy_client(index)?.call_tool_with_progress(&tool_name, arguments, progress).await
+    }
+
+    // Read one resource from the server that offers it
+    pub async fn read_resource(&self, server: &str, uri: &str) -> Result<Vec<ResourceContents>> {
+        self.inner.healthy_client(self.inner.index_of(server)?)?.read_resource(uri).await
+    }
+
+    // Fill in one of a server's prompts
+    pub async fn get_prompt(
+        &self,
+        server: &str,
+        name: &str,
+        arguments: HashMap<String, String>,
+    ) -> Result<GetPromptResult> {
+        self.inner.healthy_client(self.inner.index_of(server)?)?.get_prompt(name, arguments).await
+    }
+
+    // Get a ResourceUpdate whenever `uri` changes (kept across server restarts)
+    pub async fn subscribe_resource(&self, server: &str, uri: &str) -> Result<()> {
+        let index = self.inner.index_of(server)?;
+        self.inner.healthy_client(index)?.subscribe_resource(uri).await?;
+        self.inner.servers[index].state.write().unwrap().subscriptions.insert(uri.to_string());
+        Ok(())
+    }
+
+    pub async fn unsubscribe_resource(&self, server: &str, uri: &str) -> Result<()> {
+        let index = self.inner.index_of(server)?;
+        self.inner.servers[index].state.write().unwrap().subscriptions.remove(uri);
+        self.inner.healthy_client(index)?.unsubscribe_resource(uri).await
+    }
+
+    // Ask one server to only log at `level` or above
+    pub async fn set_log_level(&self, server: &str, level: LoggingLevel) -> Result<()> {
+        self.inner.healthy_client(self.inner.index_of(server)?)?.set_log_level(level).await
+    }
+
+    // 3. Hang up on everyone, right now
+    pub async fn close(&self) {
+        self.inner.shutting_down.store(true, Ordering::SeqCst);
+        for supervisor in &self.supervisors {
+            supervisor.abort();
+        }
+        for server in &self.inner.servers {
+            if let Err(e) = server.client().close().await {
+                tracing::warn!("Failed to close server '{}': {}", server.name, e);
+            }
+        }
+    }
+
+    // 4. Orderly exit: pending calls fail with McpError::Shutdown, then every
+    // server gets EOF, SIGTERM and finally SIGKILL (all servers in parallel)
+    pub async fn shutdown(&self, grace: Duration) {
+        self.inner.shutting_down.store(true, Ordering::SeqCst);
+        for supervisor in &self.supervisors {
+            supervisor.abort();
+        }
+
+        let mut tasks = JoinSet::new();
+        for server in &self.inner.servers {
+            let client = server.client();
+            let name = server.name.clone();
+            tasks.spawn(async move {
+                if let Err(e) = client.shutdown(grace).await {
+                    tracing::warn!("Failed to shut down server '{}': {}", name, e);
+                }
+            });
+        }
+        while tasks.join_next().await.is_some() {}
+    }
+}
+
+impl RegistryInner {
+    pub(crate) fn is_shutting_down(&self) -> bool {
+        self.shutting_down.load(Ordering::SeqCst)
+    }
+
+    fn index_of(&self, server: &str) -> Result<usize> {
+        self.servers.iter()
+            .position(|s| s.name == server)
+            .ok_or(anyhow!("Unknown server '{}'", server))
+    }
+
+    // The server's client, unless the supervisor gave up on it
+    fn healthy_client(&self, index: usize) -> Result<Arc<McpClient>> {
+        let slot = &self.servers[index];
+        let state = slot.state.read().unwrap();
+        if let ServerHealth::Unhealthy { reason } = &state.health {
+            return Err(anyhow!("Server '{}' is unhealthy: {}", slot.name, reason));
+        }
+        Ok(state.client.clone())
+    }
+
+    pub(crate) fn set_health(&self, index: usize, health: ServerHealth) {
+        let slot = &self.servers[index];
+        slot.state.write().unwrap().health = health.clone();
+        let _ = self.health_tx.send(HealthEvent { server: slot.name.clone(), health });
+    }
+
+    // Launch a fresh connection for one server and swap it in
+    pub(crate) async fn reconnect(self: &Arc<Self>, index: usize) -> Result<()> {
+        let slot = &self.servers[index];
+        let (client, listings) = connect(&slot.config, &self.wiring).await?;
+        let client = Arc::new(client);
+        watch_tools(Arc::downgrade(self), index, listings.notifications);
+        let subscriptions = {
+            let mut state = slot.state.write().unwrap();
+            state.client = client.clone();
+            state.tools = listings.tools;
+            state.resources = listings.resources;
+            state.templates = listings.templates;
+            state.prompts = listings.prompts;
+            state.subscriptions.clone()
+        };
+        self.rebuild_catalog();
+
+        // The new process knows nothing about our old subscriptions
+        for uri in subscriptions {
+            if let Err(e) = client.subscribe_resource(&uri).await {
+                tracing::warn!("Failed to renew subscription to '{}' on '{}': {}", uri, slot.name, e);
+            }
+        }
+        Ok(())
+    }
+
+    // Fetch one server's tools again and swap them into the catalog
+    async fn refresh_tools(&self, index: usize) -> Result<()> {
+        let slot = &self.servers[index];
+        let client = slot.client();
+        let tools = client.list_tools().await?;
+        let count = tools.len();
+        {
+            let mut state = slot.state.write().unwrap();
+            // A restart in the meantime brought its own list
+            if !Arc::ptr_eq(&state.client, &client) {
+                return Ok(());
+            }
+            state.tools = tools;
+        }
+        self.rebuild_catalog();
+        let _ = self.tools_tx.send(ToolsChanged { server: slot.name.clone(), count });
+        Ok(())
+    }
+
+    // Merge the tool lists.
+    // Unique names are exposed as-is; a name offered by more than one server
+    // is exposed as `server__tool` for every server that offers it.
+    fn rebuild_catalog(&self) {
+        let server_tools: Vec<Vec<Tool>> = self.servers.iter()
+            .map(|s| s.state.read().unwrap().tools.clone())
+            .collect();
+
+        let mut counts: HashMap<&str, usize> = HashMap::new();
+        for tools in &server_tools {
+            for tool in tools {
+                *counts.entry(tool.name.as_str()).or_default() += 1;
+            }
+        }
+
+        let mut catalog = Catalog::default();
+        for (index, tools) in server_tools.iter().enumerate() {
+            for tool in tools {
+                let exposed = if counts[tool.name.as_str()] > 1 {
+                    format!("{}{}{}", self.servers[index].name, NAMESPACE_SEPARATOR, tool.name)
+                } else {
+                    tool.name.clone()
+                };
+
+                let mut tool = tool.clone();
+                catalog.routes.insert(exposed.clone(), Route { server: index, tool_name: tool.name.clone() });
+                tool.name = exposed;
+                catalog.tools.push(tool);
+            }
+        }
+
+        *self.catalog.write().unwrap() = catalog;
+    }
+}
+
+// Open a client for one server, handshake, and fetch everything it offers
+async fn connect(config: &ServerConfig, wiring: &Wiring) -> Result<(McpClient, Listings)> {
+    let client = open_client(config, wiring).await
+        .context(format!("Failed to launch server '{}'", config.name))?
+        .with_timeouts(config.timeouts())
+        .with_roots(wiring.roots.clone())
+        .with_cwd(config.cwd.clone().unwrap_or_default());
+    client.set_request_handler((wiring.handlers)(&config.name));
+    forward_resource_updates(config.name.clone(), client.notifications(), wiring.resource_tx.clone());
+    forward_logs(config.name.clone(), client.notifications(), wiring.log_tx.clone());
+    let notifications = client.notifications();
+
+    client.initialize().await
+        .context(format!("Handshake with server '{}' failed", config.name))?;
+    // A server without the tools capability (say, prompts only) simply has none
+    let tools = match client.supports("tools") {
+        true => client.list_tools().await
+            .context(format!("tools/list on server '{}' failed", config.name))?,
+        false => Vec::new(),
+    };
+
+    // Resources and prompts are optional; a server that botches them still serves its tools
+    let (mut resources, mut templates, mut prompts) = (Vec::new(), Vec::new(), Vec::new());
+    if client.supports("resources") {
+        match client.list_resources().await {
+            Ok(listed) => resources = listed,
+            Err(e) => tracing::warn!("resources/list on server '{}' failed: {:#}", config.name, e),
+        }
+        match client.list_resource_templates().await {
+            Ok(listed) => templates = listed,
+            Err(e) => tracing::warn!("resources/templates/list on server '{}' failed: {:#}", config.name, e),
+        }
+    }
+    if client.supports("prompts") {
+        match client.list_prompts().await {
+            Ok(listed) => prompts = listed,
+            Err(e) => tracing::warn!("prompts/list on server '{}' failed: {:#}", config.name, e),
+        }
+    }
+
+    Ok((client, Listings { tools, resources, templates, prompts, notifications }))
+}
+
+// Refetch one server's tools whenever it sends `notifications/tools/list_changed`.
+// Ends by itself once the client is dropped (restart or shutdown).
+fn watch_tools(
+    registry: Weak<RegistryInner>,
+    index: usize,
+    mut notifications: broadcast::Receiver<JsonRpcNotification>,
+) {
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(note) if note.method == "notifications/tools/list_changed" => {}
+                // We may have missed a change; refetching is cheap
+                Err(broadcast::error::RecvError::Lagged(_)) => {}
+                Ok(_) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+            let Some(registry) = registry.upgrade() else { break };
+            if let Err(e) = registry.refresh_tools(index).await {
+                tracing::warn!("tools/list on server '{}' failed: {:#}", registry.servers[index].name, e);
+            }
+        }
+    });
+}
+
+// Turn one client's `notifications/resources/updated` into ResourceUpdates.
+// Ends by itself once the client is dropped.
+fn forward_resource_updates(
+    server: String,
+    mut notifications: broadcast::Receiver<JsonRpcNotification>,
+    resource_tx: broadcast::Sender<ResourceUpdate>,
+) {
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(note) if note.method == "notifications/resources/updated" => {
+                    let uri = note.params.as_ref().and_then(|p| p.get("uri")).and_then(Value::as_str);
+                    if let Some(uri) = uri {
+                        let _ = resource_tx.send(ResourceUpdate { server: server.clone(), uri: uri.to_string() });
+                    }
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+// Turn one client's `notifications/message` into tracing events and ServerLogs.
+// Ends by itself once the client is dropped.
+fn forward_logs(
+    server: String,
+    mut notifications: broadcast::Receiver<JsonRpcNotification>,
+    log_tx: broadcast::Sender<ServerLog>,
+) {
+    tokio::spawn(async move {
+        loop {
+            match notifications.recv().await {
+                Ok(note) if note.method == "notifications/message" => {
+                    let params = note.params.unwrap_or_default();
+                    let message = match serde_json::from_value::<LoggingMessageNotification>(params) {
+                        Ok(message) => message,
+                        Err(e) => {
+                            tracing::warn!("Ignoring malformed log message from '{}': {}", server, e);
+                            continue;
+                        }
+                    };
+                    trace_log(&server, &message);
+                    let _ = log_tx.send(ServerLog { server: server.clone(), message });
+                }
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+// Syslog severities folded onto tracing's levels
+fn trace_log(server: &str, message: &LoggingMessageNotification) {
+    let logger = message.logger.as_deref().unwrap_or("-");
+    let level = message.level.as_str();
+    let text = message.text();
+    match message.level {
+        LoggingLevel::Debug => tracing::debug!(target: "mcp_log", server, logger, level, "{}", text),
+        LoggingLevel::Info | LoggingLevel::Notice => tracing::info!(target: "mcp_log", server, logger, level, "{}", text),
+        LoggingLevel::Warning => tracing::warn!(target: "mcp_log", server, logger, level, "{}", text),
+        _ => tracing::error!(target: "mcp_log", server, logger, level, "{}", text),
+    }
+}
+
+// Pick the transport the config asks for
+async fn open_client(config: &ServerConfig, wiring: &Wiring) -> Result<McpClient> {
+    // Tool permissions were checked by the registry (under the exposed names);
+    // the client only keeps the path fence
+    let security = SecurityConfig {
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+        ..wiring.security.clone()
+    };
+    let url = config.url.as_deref().unwrap_or_default();
+    match config.transport_kind() {
+        TransportKind::Stdio => {
+            let command = config.command.as_deref()
+                .ok_or(anyhow!("No command configured"))?;
+            let args: Vec<&str> = config.args.iter().map(String::as_str).collect();
+            // A sandboxed server may read the roots, and write them only if its profile says so
+            let sandbox = config.sandbox.clone().map(|mut profile| {
+                match profile.writable_roots {
+                    true => profile.writable.extend(wiring.roots.dirs()),
+                    false => profile.read_only.extend(wiring.roots.dirs()),
+                }
+                profile
+            });
+            let options = LaunchOptions {
+                env: config.resolved_env()?,
+                inherit_env: config.inherit_env.clone(),
+                cwd: config.cwd.clone(),
+                name: Some(config.name.clone()),
+                stderr_sink: Some(wiring.stderr_tx.clone()),
+                sandbox,
+                limits: config.limits.clone(),
+            };
+            let process = McpProcess::start_with(command, &args, &options)?;
+            Ok(McpClient::new(process, security))
+        }
+        TransportKind::Http => Ok(McpClient::new(StreamableHttpTransport::new(url), security)),
+        TransportKind::Sse => {
+            // The server's `timeout` also bounds the wait for its endpoint
+            let transport = match config.timeout {
+                Some(secs) => LegacySseTransport::connect_with_timeout(url, Duration::from_secs(secs)).await?,
+                None => LegacySseTransport::connect(url).await?,
+            };
+            Ok(McpClient::new(transport, security))
+        }
+    }
+}
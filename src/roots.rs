@@ -0,0 +1,148 @@
+// src/roots.rs
+// The Map: the directories the user has granted (from the config and
+// `--root`). Servers can ask for them (`roots/list`), sandboxed servers may
+// write inside them, and tool calls can't point outside them.
+// The set can change while we run; every client tells its server when it does.
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::watch;
+use crate::protocol::Root;
+
+// One shared, observable set of root directories. Clones see the same set.
+#[derive(Clone)]
+pub struct Roots {
+    dirs: Arc<watch::Sender<Vec<PathBuf>>>,
+}
+
+impl Default for Roots {
+    fn default() -> Self {
+        Self::new(Vec::new())
+    }
+}
+
+impl Roots {
+    // Paths are made absolute (against the working directory) and deduplicated
+    pub fn new(dirs: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut normalized: Vec<PathBuf> = Vec::new();
+        for dir in dirs {
+            let dir = normalize(&dir);
+            if !normalized.contains(&dir) {
+                normalized.push(dir);
+            }
+        }
+        Self { dirs: Arc::new(watch::channel(normalized).0) }
+    }
+
+    pub fn dirs(&self) -> Vec<PathBuf> {
+        self.dirs.borrow().clone()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.dirs.borrow().is_empty()
+    }
+
+    // What `roots/list` answers
+    pub fn list(&self) -> Vec<Root> {
+        self.dirs.borrow().iter()
+            .map(|dir| Root {
+                uri: file_uri(dir),
+                name: dir.file_name().map(|name| name.to_string_lossy().into_owned()),
+            })
+            .collect()
+    }
+
+    // Grant another directory. False if it was already a root.
+    pub fn add(&self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        self.dirs.send_if_modified(|dirs| match dirs.contains(&dir) {
+            true => false,
+            false => {
+                dirs.push(dir);
+                true
+            }
+        })
+    }
+
+    // Take a directory back. False if it wasn't a root.
+    pub fn remove(&self, dir: &Path) -> bool {
+        let dir = normalize(dir);
+        self.dirs.send_if_modified(|dirs| {
+            let before = dirs.len();
+            dirs.retain(|d| *d != dir);
+            dirs.len() != before
+        })
+    }
+
+    // Wakes up whenever the set changes
+    pub fn subscribe(&self) -> watch::Receiver<Vec<PathBuf>> {
+        self.dirs.subscribe()
+    }
+
+    // Is `path` one of the roots or somewhere below one?
+    pub fn contains(&self, path: &Path) -> bool {
+        let path = normalize(path);
+        self.dirs.borrow().iter().any(|dir| path.starts_with(dir))
+    }
+}
+
+// Absolute, without `.`/`..`, and with symlinks resolved as far as the path
+// exists (so a link can't lead out of a root)
+pub fn normalize(path: &Path) -> PathBuf {
+    let cwd = std::env::current_dir().unwrap_or_default();
+    let mut absolute = PathBuf::new();
+    for component in cwd.join(path).components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                absolute.pop();
+            }
+            other => absolute.push(other),
+        }
+    }
+
+    for existing in absolute.ancestors() {
+        if let Ok(real) = fs::canonicalize(existing) {
+            return match absolute.strip_prefix(existing) {
+                Ok(rest) if !rest.as_os_str().is_empty() => real.join(rest),
+                _ => real,
+            };
+        }
+    }
+    absolute
+}
+
+// "/home/me/My Project" -> "file:///home/me/My%20Project"
+pub fn file_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+// The reverse of `file_uri` (None for anything that isn't a local file URI)
+pub fn path_from_uri(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    let rest = rest.strip_prefix("localhost").unwrap_or(rest);
+    if !rest.starts_with('/') {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(rest.len());
+    let mut input = rest.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let hex = [input.next()?, input.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    Some(PathBuf::from(String::from_utf8(bytes).ok()?))
+}
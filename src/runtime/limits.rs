@@ -0,0 +1,300 @@
+// src/runtime/limits.rs
+// The Leash: rlimits (and optionally a cgroup v2 group) per server, so one
+// runaway tool can't take the whole laptop with it.
+// When a limited server dies we also try to say *which* limit got it.
+use std::fs::{self, File, OpenOptions};
+use std::io;
+use std::os::fd::AsRawFd;
+use std::os::unix::process::ExitStatusExt;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use anyhow::{anyhow, Context, Result};
+use tokio::process::Command;
+use crate::config::ResourceLimits;
+
+// cgroup v2 cpu.max period (microseconds)
+const CPU_PERIOD_US: u64 = 100_000;
+
+// Keeps cgroup names unique within this host process
+static CGROUP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+// The group server groups are created in, once the host has moved out of it
+static CGROUP_PARENT: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+// A cgroup v2 group made for one server; removed again on drop
+pub struct Cgroup {
+    path: PathBuf,
+    // Open before fork so the child only has to write "0" to join
+    procs: File,
+}
+
+impl Cgroup {
+    // Create a sibling of the host's group and write the limits into it.
+    // Any limit that can't be set is an error: the server doesn't start unlimited.
+    pub fn create(server: &str, limits: &ResourceLimits) -> Result<Self> {
+        let mut controllers = Vec::new();
+        if limits.memory_mb.is_some() {
+            controllers.push("memory");
+        }
+        if limits.processes.is_some() {
+            controllers.push("pids");
+        }
+        if limits.cpu_percent.is_some() {
+            controllers.push("cpu");
+        }
+        let path = cgroup_parent(&controllers)?.join(group_name(server));
+        fs::create_dir(&path).context(format!(
+            "Failed to create cgroup {} (is the cgroup delegated to this user?)",
+            path.display()
+        ))?;
+
+        let procs = OpenOptions::new().write(true).open(path.join("cgroup.procs"));
+        let cgroup = match procs {
+            Ok(procs) => Self { path, procs },
+            Err(e) => {
+                let _ = fs::remove_dir(&path);
+                return Err(anyhow!("Failed to open {}/cgroup.procs: {}", path.display(), e));
+            }
+        };
+
+        if let Some(mb) = limits.memory_mb {
+            cgroup.set("memory.max", &(mb * 1024 * 1024).to_string())?;
+            cgroup.set("memory.swap.max", "0").ok(); // Not every kernel has swap accounting
+        }
+        if let Some(processes) = limits.processes {
+            cgroup.set("pids.max", &processes.to_string())?;
+        }
+        if let Some(percent) = limits.cpu_percent {
+            let quota = CPU_PERIOD_US * u64::from(percent) / 100;
+            cgroup.set("cpu.max", &format!("{} {}", quota, CPU_PERIOD_US))?;
+        }
+        Ok(cgroup)
+    }
+
+    fn set(&self, file: &str, value: &str) -> Result<()> {
+        fs::write(self.path.join(file), value).context(format!(
+            "Failed to set {} in {} (is the controller enabled?)",
+            file,
+            self.path.display()
+        ))
+    }
+
+    // Did the kernel OOM-kill anything in here?
+    pub fn oom_killed(&self) -> bool {
+        let events = fs::read_to_string(self.path.join("memory.events")).unwrap_or_default();
+        events.lines()
+            .filter_map(|line| line.strip_prefix("oom_kill "))
+            .any(|count| count.trim() != "0")
+    }
+}
+
+impl Drop for Cgroup {
+    fn drop(&mut self) {
+        // Only works once the group is empty; a leftover empty dir is harmless
+        let _ = fs::remove_dir(&self.path);
+    }
+}
+
+// Our cgroup, with AETHER moved into a leaf below it and the controllers
+// enabled for its children. Controllers only reach a group's children if the
+// group itself holds no processes ("no internal processes"), so the group we
+// were started in has to be emptied first. We only do that to a group that is
+// ours alone (a delegated scope); anything else is an error and left untouched.
+fn cgroup_parent(controllers: &[&str]) -> Result<PathBuf> {
+    let mut parent = CGROUP_PARENT.lock().unwrap();
+    let path = match parent.as_ref() {
+        Some(path) => path.clone(),
+        None => {
+            let path = claim_own_cgroup()?;
+            *parent = Some(path.clone());
+            path
+        }
+    };
+    // Enabling one that's already on is a no-op
+    for controller in controllers {
+        fs::write(path.join("cgroup.subtree_control"), format!("+{}", controller)).context(format!(
+            "Failed to enable the {} controller in {} (is it delegated to this user?)",
+            controller,
+            path.display()
+        ))?;
+    }
+    Ok(path)
+}
+
+// How to give AETHER a cgroup it may rearrange
+const DELEGATE_HINT: &str = "start AETHER in a delegated cgroup of its own, \
+    e.g. `systemd-run --user --scope -p Delegate=yes aether`";
+
+// Directory name for a server's group. Server names are free-form ("../x",
+// "a/b", spaces), so anything but [A-Za-z0-9_-] becomes '_' and the name is
+// cut short; the pid and counter keep it unique.
+pub fn group_name(server: &str) -> String {
+    let safe: String = server.chars()
+        .take(64)
+        .map(|c| match c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            true => c,
+            false => '_',
+        })
+        .collect();
+    format!(
+        "aether-{}-{}-{}",
+        safe,
+        std::process::id(),
+        CGROUP_COUNTER.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+// Move AETHER (and the servers it already started) into `aether-host` below
+// our group. Returns the now empty group.
+fn claim_own_cgroup() -> Result<PathBuf> {
+    let (mount, own) = own_cgroup()?;
+    if own == mount {
+        return Err(anyhow!("AETHER runs in the root cgroup, which it won't reorganize; {}", DELEGATE_HINT));
+    }
+    let procs = fs::read_to_string(own.join("cgroup.procs"))
+        .context(format!("Failed to read {}/cgroup.procs", own.display()))?;
+    let pids: Vec<libc::pid_t> = procs.lines().filter_map(|pid| pid.trim().parse().ok()).collect();
+
+    // A shell or anything else sharing the group means it isn't ours to change
+    let host = std::process::id() as libc::pid_t;
+    if let Some(stranger) = pids.iter().find(|pid| !descends_from(**pid, host)) {
+        return Err(anyhow!(
+            "Cgroup {} also holds process {}, which AETHER didn't start; {}",
+            own.display(),
+            stranger,
+            DELEGATE_HINT
+        ));
+    }
+
+    let leaf = own.join("aether-host");
+    match fs::create_dir(&leaf) {
+        Err(e) if e.kind() != io::ErrorKind::AlreadyExists => {
+            return Err(anyhow!("Failed to create cgroup {} ({}): {}", leaf.display(), DELEGATE_HINT, e));
+        }
+        _ => {}
+    }
+    for pid in pids {
+        match fs::write(leaf.join("cgroup.procs"), pid.to_string()) {
+            // Exited in the meantime
+            Err(e) if e.raw_os_error() == Some(libc::ESRCH) => {}
+            Err(e) => return Err(anyhow!("Failed to move process {} into {}: {}", pid, leaf.display(), e)),
+            Ok(()) => {}
+        }
+    }
+    Ok(own)
+}
+
+// Is `pid` the host or one of its descendants? (A process that's already gone counts.)
+fn descends_from(mut pid: libc::pid_t, ancestor: libc::pid_t) -> bool {
+    while pid != ancestor {
+        if pid <= 1 {
+            return false;
+        }
+        // "pid (comm) state ppid ...": comm may contain spaces, so split after the ')'
+        let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) else { return true };
+        let ppid = stat.rsplit_once(')')
+            .and_then(|(_, rest)| rest.split_whitespace().nth(1))
+            .and_then(|ppid| ppid.parse().ok());
+        match ppid {
+            Some(ppid) => pid = ppid,
+            None => return false,
+        }
+    }
+    true
+}
+
+// Where we live in the cgroup v2 hierarchy: (mount point, our group)
+fn own_cgroup() -> Result<(PathBuf, PathBuf)> {
+    let mounts = fs::read_to_string("/proc/self/mounts").context("Failed to read /proc/self/mounts")?;
+    let mount = mounts.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .find(|fields| fields.get(2) == Some(&"cgroup2"))
+        .and_then(|fields| fields.get(1).map(|m| PathBuf::from(*m)))
+        .ok_or(anyhow!("No cgroup v2 hierarchy is mounted"))?;
+
+    let membership = fs::read_to_string("/proc/self/cgroup").context("Failed to read /proc/self/cgroup")?;
+    let own = membership.lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .ok_or(anyhow!("This process is not in a cgroup v2 group"))?;
+
+    let own = mount.join(Path::new(own.trim_start_matches('/')));
+    Ok((mount, own))
+}
+
+// Apply the limits between fork and exec
+pub fn install(cmd: &mut Command, limits: &ResourceLimits, cgroup: Option<&Cgroup>) {
+    let mut rlimits = Vec::new();
+    if let Some(mb) = limits.memory_mb {
+        let bytes = mb * 1024 * 1024;
+        rlimits.push((libc::RLIMIT_AS, bytes, bytes));
+    }
+    if let Some(secs) = limits.cpu_seconds {
+        // SIGXCPU at the soft limit (fatal by default), SIGKILL a second later
+        rlimits.push((libc::RLIMIT_CPU, secs, secs + 1));
+    }
+    if let Some(files) = limits.open_files {
+        rlimits.push((libc::RLIMIT_NOFILE, files, files));
+    }
+    // RLIMIT_NPROC counts all of the user's processes; a cgroup's pids.max counts just the server's
+    if let Some(processes) = limits.processes.filter(|_| cgroup.is_none()) {
+        rlimits.push((libc::RLIMIT_NPROC, processes, processes));
+    }
+    let procs_fd = cgroup.map(|c| c.procs.as_raw_fd());
+
+    // SAFETY: only setrlimit/write syscalls on data prepared above; the fd
+    // stays open because the Cgroup outlives the spawn
+    unsafe {
+        cmd.pre_exec(move || {
+            if let Some(fd) = procs_fd {
+                // "0" means "the process writing this"
+                if libc::write(fd, b"0".as_ptr().cast(), 1) != 1 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            for &(resource, soft, hard) in &rlimits {
+                let limit = libc::rlimit { rlim_cur: soft as libc::rlim_t, rlim_max: hard as libc::rlim_t };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+}
+
+// Best guess at which limit killed a server, from how it exited.
+// CPU and cgroup OOM kills are certain; running out of address space is
+// inferred from the process complaining about memory on its way out.
+pub fn diagnose(
+    status: ExitStatus,
+    limits: &ResourceLimits,
+    cgroup: Option<&Cgroup>,
+    stderr_tail: &[String],
+) -> Option<String> {
+    if status.success() {
+        return None;
+    }
+    let signal = status.signal();
+
+    if let Some(secs) = limits.cpu_seconds {
+        if signal == Some(libc::SIGXCPU) {
+            return Some(format!("CPU time limit ({} s)", secs));
+        }
+    }
+    if let Some(mb) = limits.memory_mb {
+        if cgroup.is_some_and(Cgroup::oom_killed) {
+            return Some(format!("memory limit ({} MB)", mb));
+        }
+        let complained = stderr_tail.iter().any(|line| {
+            let line = line.to_lowercase();
+            line.contains("out of memory") || line.contains("memory allocation")
+                || line.contains("cannot allocate memory") || line.contains("memoryerror")
+        });
+        if complained {
+            return Some(format!("memory limit ({} MB)", mb));
+        }
+    }
+    None
+}
@@ -1,72 +1,251 @@
 // src/runtime/mod.rs
+#[cfg(target_os = "linux")]
+pub mod limits;
+pub mod stderr;
+
 use tokio::process::{Command, Child, ChildStdin, ChildStdout};
 use tokio::io::{AsyncWriteExt, AsyncBufReadExt, BufReader};
-use std::process::Stdio;
+use tokio::sync::{broadcast, Mutex};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::process::{ExitStatus, Stdio};
+use std::time::Duration;
 use anyhow::{Result, Context, anyhow};
-use crate::protocol::JsonRpcRequest; // Import our protocol
+use async_trait::async_trait;
+use crate::config::{ResourceLimits, SandboxConfig};
+use crate::error::McpError;
+use crate::transport::Transport;
+use self::stderr::{StderrLine, StderrLog, STDERR_HISTORY};
+
+// Extra knobs for how the child is launched
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+    // Variables set on top of the scrubbed environment (see INHERITED_ENV)
+    pub env: BTreeMap<String, String>,
+    // Extra host variables to pass through
+    pub inherit_env: Vec<String>,
+    // Working directory (defaults to ours)
+    pub cwd: Option<PathBuf>,
+    // Tag for stderr lines (defaults to the command)
+    pub name: Option<String>,
+    // Where stderr lines go besides the log (e.g. the TUI)
+    pub stderr_sink: Option<broadcast::Sender<StderrLine>>,
+    // Confine the process (see src/sandbox.rs)
+    pub sandbox: Option<SandboxConfig>,
+    // Cap what it may use (see src/runtime/limits.rs)
+    pub limits: Option<ResourceLimits>,
+}
+
+// The only host variables a server sees unless its config asks for more.
+// Everything else (API keys from .env in particular) stays with us.
+pub const INHERITED_ENV: &[&str] = &[
+    "PATH", "HOME", "USER", "LOGNAME", "SHELL", "LANG", "LC_ALL", "LC_CTYPE", "TERM", "TZ", "TMPDIR",
+];
+
+// How long we wait, after stdout hits EOF, for an exit status to explain it
+const EXIT_DIAGNOSIS_WAIT: Duration = Duration::from_millis(500);
 
 // The Structure that holds a running tool
 pub struct McpProcess {
     // We keep the child handle so we can kill it later if needed
-    pub child: Child, 
-    // The "Pipe" we speak into
-    pub stdin: ChildStdin, 
+    pub child: Mutex<Child>,
+    // The "Pipe" we speak into (None once closed)
+    pub stdin: Mutex<Option<ChildStdin>>,
     // The "Ear" we listen to (Buffered for performance)
-    pub stdout: BufReader<ChildStdout>, 
+    pub stdout: Mutex<BufReader<ChildStdout>>,
+    // The last lines it wrote to stderr
+    pub stderr: StderrLog,
+    // Name used in errors
+    name: String,
+    limits: Option<ResourceLimits>,
+    // Dropped (and removed) together with the process
+    #[cfg(target_os = "linux")]
+    cgroup: Option<limits::Cgroup>,
 }
 
 impl McpProcess {
     // 1. Spawn the Process
     pub fn start(command: &str, args: &[&str]) -> Result<Self> {
+        Self::start_with(command, args, &LaunchOptions::default())
+    }
+
+    pub fn start_with(command: &str, args: &[&str], options: &LaunchOptions) -> Result<Self> {
         let mut cmd = Command::new(command);
         cmd.args(args);
 
-        // CRITICAL: We must "Pipe" the streams. 
+        // Start from nothing, then add the allowlist and the server's own variables
+        cmd.env_clear();
+        let inherited = INHERITED_ENV.iter().copied().chain(options.inherit_env.iter().map(String::as_str));
+        for name in inherited {
+            if let Some(value) = std::env::var_os(name) {
+                cmd.env(name, value);
+            }
+        }
+        cmd.envs(&options.env);
+        if let Some(cwd) = &options.cwd {
+            cmd.current_dir(cwd);
+        }
+
+        // CRITICAL: We must "Pipe" the streams.
         // If we don't do this, the child inherits OUR terminal.
         cmd.stdin(Stdio::piped());
         cmd.stdout(Stdio::piped());
         cmd.stderr(Stdio::piped()); // Capture errors too
 
-        let mut child = cmd.spawn().context("Failed to spawn MCP tool")?;
+        let name = options.name.clone().unwrap_or_else(|| command.to_string());
+
+        // Cap resources, then lock it down, if the config asks for it
+        // (pre-exec hooks run in order: the cgroup must be joined before the sandbox unshares)
+        #[cfg(target_os = "linux")]
+        let cgroup = match &options.limits {
+            Some(caps) => {
+                let cgroup = match caps.cgroup {
+                    true => Some(limits::Cgroup::create(&name, caps)?),
+                    false => None,
+                };
+                limits::install(&mut cmd, caps, cgroup.as_ref());
+                cgroup
+            }
+            None => None,
+        };
+        #[cfg(not(target_os = "linux"))]
+        if options.limits.is_some() {
+            return Err(anyhow!("Resource limits are only supported on Linux (requested for '{}')", command));
+        }
+        if let Some(profile) = &options.sandbox {
+            #[cfg(target_os = "linux")]
+            crate::sandbox::Sandbox::prepare(profile, command)?.install(&mut cmd);
+            #[cfg(not(target_os = "linux"))]
+            return Err(anyhow!("Sandboxing is only supported on Linux (requested for '{}')", command));
+        }
+
+        let mut child = cmd.spawn().context(format!("Failed to spawn MCP tool '{}'", command))?;
 
         // 2. Extract the handles
-        // We take() them because a child only has one stdin/stdout. 
+        // We take() them because a child only has one stdin/stdout.
         // Once we take them, they are ours.
         let stdin = child.stdin.take().ok_or(anyhow!("Failed to open stdin"))?;
         let stdout = child.stdout.take().ok_or(anyhow!("Failed to open stdout"))?;
+        let stderr = child.stderr.take().ok_or(anyhow!("Failed to open stderr"))?;
+
+        // 3. Keep stderr flowing (a full pipe would block the tool)
+        let log = StderrLog::new(STDERR_HISTORY);
+        stderr::drain(stderr, name.clone(), log.clone(), options.stderr_sink.clone());
 
         Ok(Self {
-            child,
-            stdin,
-            stdout: BufReader::new(stdout),
+            child: Mutex::new(child),
+            stdin: Mutex::new(Some(stdin)),
+            stdout: Mutex::new(BufReader::new(stdout)),
+            stderr: log,
+            name,
+            limits: options.limits.clone(),
+            #[cfg(target_os = "linux")]
+            cgroup,
         })
     }
 
-    // 3. Send a Message
-    // Note: We use &mut self because writing changes the state of the stream
-    pub async fn send_request(&mut self, request: &JsonRpcRequest) -> Result<()> {
-        // Serialize to JSON
-        let mut json_string = serde_json::to_string(request)?;
+    // Called at EOF: if the process was killed for going over a limit, say which
+    #[cfg(target_os = "linux")]
+    async fn limit_exceeded(&self) -> Option<McpError> {
+        let limits = self.limits.as_ref()?;
+        let status = {
+            let mut child = self.child.lock().await;
+            tokio::time::timeout(EXIT_DIAGNOSIS_WAIT, child.wait()).await.ok()?.ok()?
+        };
+        let stderr = self.stderr.settled_tail(STDERR_HISTORY).await;
+        let limit = limits::diagnose(status, limits, self.cgroup.as_ref(), &stderr)?;
+        Some(McpError::ResourceLimit { server: self.name.clone(), limit })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    async fn limit_exceeded(&self) -> Option<McpError> {
+        None
+    }
+}
+
+#[async_trait]
+impl Transport for McpProcess {
+    // 4. Send a Message
+    async fn send(&self, message: &str) -> Result<()> {
+        let mut stdin = self.stdin.lock().await;
+        let stdin = stdin.as_mut().ok_or(anyhow!("Process stdin is closed"))?;
+
         // MCP spec requires messages to be separated by newlines
-        json_string.push('\n'); 
+        let mut line = String::with_capacity(message.len() + 1);
+        line.push_str(message);
+        line.push('\n');
 
         // Write to the process's Stdin
-        self.stdin.write_all(json_string.as_bytes()).await?;
-        self.stdin.flush().await?; // Ensure it's actually sent
-        
+        stdin.write_all(line.as_bytes()).await?;
+        stdin.flush().await?; // Ensure it's actually sent
+
         Ok(())
     }
 
-    // 4. Wait for ONE Response (Simple version)
-    pub async fn read_line(&mut self) -> Result<String> {
+    // 5. Wait for ONE Message
+    async fn receive(&self) -> Result<String> {
+        let mut stdout = self.stdout.lock().await;
         let mut line = String::new();
         // This waits until the process sends a "\n" character
-        let bytes_read = self.stdout.read_line(&mut line).await?;
-        
+        let bytes_read = stdout.read_line(&mut line).await?;
+
         if bytes_read == 0 {
+            if let Some(exceeded) = self.limit_exceeded().await {
+                return Err(exceeded.into());
+            }
             return Err(anyhow!("Process closed the connection (EOF)"));
         }
-        
+
         Ok(line)
     }
-}
\ No newline at end of file
+
+    // 6. Hang Up
+    // Dropping stdin signals EOF to the tool; then we make sure it's gone.
+    async fn close(&self) -> Result<()> {
+        self.stdin.lock().await.take();
+
+        let mut child = self.child.lock().await;
+        if child.try_wait()?.is_none() {
+            child.kill().await.context("Failed to kill MCP tool")?;
+        }
+        Ok(())
+    }
+
+    // 7. Wait for the Process to Exit
+    async fn exit_status(&self) -> Option<ExitStatus> {
+        self.child.lock().await.wait().await.ok()
+    }
+
+    // 8. What it said on the way down
+    async fn recent_stderr(&self, lines: usize) -> Vec<String> {
+        self.stderr.settled_tail(lines).await
+    }
+
+    // 9. Orderly Exit
+    // EOF first (well-behaved servers exit on their own), then SIGTERM, then SIGKILL.
+    // Each polite step gets `grace` to work.
+    async fn shutdown(&self, grace: Duration) -> Result<()> {
+        // A. Close stdin
+        self.stdin.lock().await.take();
+
+        let mut child = self.child.lock().await;
+        if tokio::time::timeout(grace, child.wait()).await.is_ok() {
+            return Ok(());
+        }
+
+        // B. SIGTERM
+        #[cfg(unix)]
+        if let Some(pid) = child.id() {
+            tracing::info!("MCP tool (pid {}) ignored EOF, sending SIGTERM", pid);
+            // SAFETY: plain syscall; the pid is our own un-reaped child, so it can't have been reused
+            unsafe { libc::kill(pid as libc::pid_t, libc::SIGTERM) };
+            if tokio::time::timeout(grace, child.wait()).await.is_ok() {
+                return Ok(());
+            }
+        }
+
+        // C. SIGKILL
+        tracing::warn!("MCP tool did not exit in time, killing it");
+        child.kill().await.context("Failed to kill MCP tool")
+    }
+}
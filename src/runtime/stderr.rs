@@ -0,0 +1,98 @@
+// src/runtime/stderr.rs
+// The Back Channel: whatever a server prints to stderr.
+// If nobody reads the pipe, a chatty server blocks once it fills up (~64KB),
+// so we drain it in the background, log every line, and keep the last few
+// around for error messages.
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::ChildStderr;
+use tokio::sync::{broadcast, watch};
+
+// How many lines we remember per process
+pub const STDERR_HISTORY: usize = 50;
+
+// How long we wait for the last lines of a dying process to come in
+const SETTLE_TIMEOUT: Duration = Duration::from_millis(200);
+
+// One line of output, tagged with the server it came from
+#[derive(Debug, Clone)]
+pub struct StderrLine {
+    pub server: String,
+    pub line: String,
+}
+
+// Bounded ring buffer of recent stderr lines
+#[derive(Debug, Clone)]
+pub struct StderrLog {
+    lines: Arc<Mutex<VecDeque<String>>>,
+    capacity: usize,
+    // Flips to true once the pipe hits EOF
+    finished: watch::Sender<bool>,
+}
+
+impl StderrLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            lines: Arc::new(Mutex::new(VecDeque::with_capacity(capacity))),
+            capacity,
+            finished: watch::channel(false).0,
+        }
+    }
+
+    pub fn push(&self, line: String) {
+        let mut lines = self.lines.lock().unwrap();
+        if lines.len() == self.capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    // The last `n` lines, oldest first
+    pub fn tail(&self, n: usize) -> Vec<String> {
+        let lines = self.lines.lock().unwrap();
+        lines.iter().skip(lines.len().saturating_sub(n)).cloned().collect()
+    }
+
+    // Like `tail`, but first gives a dying process a moment to finish writing
+    pub async fn settled_tail(&self, n: usize) -> Vec<String> {
+        let mut finished = self.finished.subscribe();
+        let _ = tokio::time::timeout(SETTLE_TIMEOUT, finished.wait_for(|done| *done)).await;
+        self.tail(n)
+    }
+}
+
+// Read the pipe until EOF: every line goes to tracing, the ring buffer, and
+// (if anyone listens) the sink.
+pub(crate) fn drain(
+    stderr: ChildStderr,
+    server: String,
+    log: StderrLog,
+    sink: Option<broadcast::Sender<StderrLine>>,
+) {
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(stderr);
+        let mut buf = Vec::new();
+        loop {
+            // Raw bytes, not `lines()`: a non-UTF-8 line must not stop the drain
+            buf.clear();
+            match reader.read_until(b'\n', &mut buf).await {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!("Stopped reading stderr of '{}': {}", server, e);
+                    break;
+                }
+            }
+            let line = String::from_utf8_lossy(&buf).trim_end_matches(['\r', '\n']).to_string();
+
+            tracing::info!(target: "mcp_stderr", "[{}] {}", server, line);
+            log.push(line.clone());
+            if let Some(sink) = &sink {
+                let _ = sink.send(StderrLine { server: server.clone(), line });
+            }
+        }
+        log.finished.send_replace(true);
+    });
+}
@@ -0,0 +1,178 @@
+// src/sampling.rs
+// The Loan Desk: servers may borrow our LLM through `sampling/createMessage`.
+// Nothing is sent to the provider until the user says yes in the TUI.
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, oneshot};
+use crate::client::{DefaultRequestHandler, RequestHandler};
+use crate::content::render_block;
+use crate::llm::{CompletionOptions, LlmClient, Message};
+use crate::protocol::{
+    ClientCapabilities, ContentBlock, CreateMessageParams, CreateMessageResult, ErrorCode,
+    JsonRpcError, ModelPreferences,
+};
+use crate::tui::UiMessage;
+
+// How much of the request the approval prompt shows
+const SUMMARY_CHARS: usize = 160;
+
+// MCP's code for "the user said no"
+const USER_REJECTED: i32 = -1;
+
+// A sampling request waiting for the user's yes or no.
+// Only the first answer counts; dropping every copy unanswered means no.
+#[derive(Clone)]
+pub struct Approval {
+    pub server: String,
+    pub summary: String,
+    reply: Arc<Mutex<Option<oneshot::Sender<bool>>>>,
+}
+
+impl Approval {
+    pub fn new(server: &str, summary: &str) -> (Self, oneshot::Receiver<bool>) {
+        let (tx, rx) = oneshot::channel();
+        let approval = Self {
+            server: server.to_string(),
+            summary: summary.to_string(),
+            reply: Arc::new(Mutex::new(Some(tx))),
+        };
+        (approval, rx)
+    }
+
+    pub fn answer(&self, approved: bool) {
+        if let Some(reply) = self.reply.lock().unwrap().take() {
+            let _ = reply.send(approved);
+        }
+    }
+}
+
+impl fmt::Debug for Approval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Approval").field("server", &self.server).field("summary", &self.summary).finish()
+    }
+}
+
+// Answers one server's requests: sampling through the LLM, the rest as usual
+pub struct SamplingHandler {
+    server: String,
+    llm: LlmClient,
+    // Models the server may choose from, most capable first
+    models: Vec<String>,
+    tx_ui: mpsc::UnboundedSender<UiMessage>,
+}
+
+impl SamplingHandler {
+    pub fn new(server: &str, llm: LlmClient, models: Vec<String>, tx_ui: mpsc::UnboundedSender<UiMessage>) -> Self {
+        Self { server: server.to_string(), llm, models, tx_ui }
+    }
+
+    async fn create_message(&self, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        // 1. Read the request
+        let params: CreateMessageParams = serde_json::from_value(params.unwrap_or_default())
+            .map_err(|e| JsonRpcError::new(ErrorCode::InvalidParams, format!("Invalid sampling request: {}", e)))?;
+        let mut messages = Vec::new();
+        if let Some(system) = &params.system_prompt {
+            messages.push(message("system", system.clone()));
+        }
+        for sampled in &params.messages {
+            if sampled.role != "user" && sampled.role != "assistant" {
+                return Err(JsonRpcError::new(ErrorCode::InvalidParams, format!("Unknown role '{}'", sampled.role)));
+            }
+            messages.push(message(&sampled.role, render_block(&sampled.content)));
+        }
+        let model = choose_model(params.model_preferences.as_ref(), &self.llm.model, &self.models);
+
+        // 2. Ask the user
+        let last = messages.last().and_then(|m| m.content.clone()).unwrap_or_default();
+        let summary = format!("{} ({}, up to {} tokens)", shorten(&last), model, params.max_tokens);
+        let (approval, answer) = Approval::new(&self.server, &summary);
+        let _ = self.tx_ui.send(UiMessage::Approval(approval));
+        if answer.await != Ok(true) {
+            self.log(&format!("SAMPLING: denied request from '{}'", self.server));
+            return Err(JsonRpcError::new(ErrorCode::from(USER_REJECTED), "User rejected sampling request"));
+        }
+        self.log(&format!("SAMPLING: running {} for '{}'", model, self.server));
+
+        // 3. Run it
+        let options = CompletionOptions {
+            model: Some(model),
+            max_tokens: Some(params.max_tokens),
+            temperature: params.temperature,
+            stop: params.stop_sequences.clone(),
+        };
+        let completion = self.llm.complete(&messages, &[], &options).await
+            .map_err(|e| JsonRpcError::new(ErrorCode::InternalError, format!("Sampling failed: {:#}", e)))?;
+
+        let result = CreateMessageResult {
+            role: "assistant".to_string(),
+            content: ContentBlock::Text { text: completion.message.content.unwrap_or_default() },
+            model: completion.model,
+            stop_reason: completion.finish_reason.map(|reason| match reason.as_str() {
+                "stop" => "endTurn".to_string(),
+                "length" => "maxTokens".to_string(),
+                _ => reason,
+            }),
+        };
+        Ok(json!(result))
+    }
+
+    fn log(&self, text: &str) {
+        let _ = self.tx_ui.send(UiMessage::Log(text.to_string()));
+    }
+}
+
+#[async_trait]
+impl RequestHandler for SamplingHandler {
+    async fn handle(&self, method: &str, params: Option<Value>) -> Result<Value, JsonRpcError> {
+        match method {
+            "sampling/createMessage" => self.create_message(params).await,
+            _ => DefaultRequestHandler.handle(method, params).await,
+        }
+    }
+
+    fn capabilities(&self) -> ClientCapabilities {
+        ClientCapabilities { sampling: Some(json!({})), ..ClientCapabilities::default() }
+    }
+}
+
+// Pick a model for a sampling request.
+// Hints win (first hint that is part of a candidate's name). Otherwise a
+// request that cares more about cost or speed than about intelligence gets
+// the last (cheapest) candidate, and one that cares more about intelligence
+// the first. Everything else gets our own model.
+pub fn choose_model(preferences: Option<&ModelPreferences>, default: &str, candidates: &[String]) -> String {
+    let (Some(preferences), Some(first), Some(last)) = (preferences, candidates.first(), candidates.last()) else {
+        return default.to_string();
+    };
+
+    for hint in preferences.hints.iter().filter_map(|h| h.name.as_deref()) {
+        let hint = hint.to_lowercase();
+        if let Some(found) = candidates.iter().find(|c| c.to_lowercase().contains(&hint)) {
+            return found.clone();
+        }
+    }
+
+    let intelligence = preferences.intelligence_priority.unwrap_or(0.0);
+    let thrift = preferences.cost_priority.unwrap_or(0.0).max(preferences.speed_priority.unwrap_or(0.0));
+    if thrift > intelligence {
+        last.clone()
+    } else if intelligence > thrift {
+        first.clone()
+    } else {
+        default.to_string()
+    }
+}
+
+fn message(role: &str, content: String) -> Message {
+    Message { role: role.to_string(), content: Some(content), tool_calls: None, tool_call_id: None }
+}
+
+fn shorten(text: &str) -> String {
+    let flat = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    match flat.chars().count() > SUMMARY_CHARS {
+        true => format!("{}...", flat.chars().take(SUMMARY_CHARS).collect::<String>()),
+        false => flat,
+    }
+}
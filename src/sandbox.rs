@@ -0,0 +1,470 @@
+// src/sandbox.rs
+// The Cage: opt-in OS-level confinement for stdio MCP servers (Linux only).
+// permissions.json decides which tools the LLM may *call*; this decides what
+// the server process can actually *do*:
+//   A. fresh user/mount/PID namespaces, plus an empty network namespace
+//      unless the profile grants network access
+//   B. landlock: the server only sees the paths it was given
+//   C. seccomp: a syscall allowlist, everything else fails with EPERM
+//      (clone may not create namespaces, clone3 is hidden behind ENOSYS)
+//
+// Everything that allocates or opens files happens in `Sandbox::prepare`,
+// before fork. The hook that runs between fork and exec only makes syscalls.
+use std::collections::BTreeMap;
+use std::ffi::CString;
+use std::io;
+use std::os::fd::{FromRawFd, OwnedFd};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicI32, Ordering};
+use anyhow::{anyhow, Context, Result};
+use landlock::{
+    path_beneath_rules, Access, AccessFs, PathBeneath, Ruleset, RulesetAttr, RulesetCreated,
+    RulesetCreatedAttr, ABI,
+};
+use seccompiler::{
+    BpfProgram, SeccompAction, SeccompCmpArgLen, SeccompCmpOp, SeccompCondition, SeccompFilter, SeccompRule,
+    TargetArch,
+};
+use tokio::process::Command;
+use crate::config::SandboxConfig;
+
+// Needed by practically every program (loader, libc, certificates, randomness).
+// /proc is readable too, but it's remounted in the child so its rule is added there.
+// No other devices: a terminal would let the server read keystrokes or inject input.
+const SYSTEM_READ_ONLY: &[&str] = &["/bin", "/sbin", "/usr", "/lib", "/lib64", "/etc", "/dev/urandom"];
+const SYSTEM_WRITABLE: &[&str] = &["/dev/null", "/dev/zero"];
+
+// clone() may start threads and processes, not namespaces (that's what `unshare` is for)
+const CLONE_NEW_FLAGS: libc::c_int = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID
+    | libc::CLONE_NEWNET | libc::CLONE_NEWUTS | libc::CLONE_NEWIPC | libc::CLONE_NEWCGROUP
+    | libc::CLONE_NEWTIME;
+
+// Signals the middle process passes on to the server instead of dying of them
+const FORWARDED_SIGNALS: [libc::c_int; 3] = [libc::SIGTERM, libc::SIGINT, libc::SIGHUP];
+
+// The server's pid, for the middle process's signal handler
+static SERVER_PID: AtomicI32 = AtomicI32::new(0);
+
+// Newest landlock ABI we ask for; older kernels get the subset they support
+const LANDLOCK_ABI: ABI = ABI::V2;
+
+// Everything the pre-exec hook needs, built ahead of time
+pub struct Sandbox {
+    unshare_flags: libc::c_int,
+    // "/proc/self/..." file -> contents, written right after unshare
+    id_maps: Vec<(CString, Vec<u8>)>,
+    proc_fs: CString,
+    proc_dir: CString,
+    ruleset: Option<RulesetCreated>,
+    // Applied in order; the kernel runs every one of them on each syscall.
+    // The main allowlist goes last since it blocks seccomp() itself.
+    filters: Vec<BpfProgram>,
+}
+
+impl Sandbox {
+    // 1. Turn a profile into ready-to-apply kernel objects
+    pub fn prepare(profile: &SandboxConfig, program: &str) -> Result<Self> {
+        // A. Namespaces. Mapping our own uid/gid means files keep their owners.
+        let mut unshare_flags = libc::CLONE_NEWUSER | libc::CLONE_NEWNS | libc::CLONE_NEWPID;
+        if !profile.network {
+            unshare_flags |= libc::CLONE_NEWNET;
+        }
+        // SAFETY: plain syscalls without arguments
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        let id_maps = vec![
+            (c_path("/proc/self/setgroups"), b"deny".to_vec()),
+            (c_path("/proc/self/uid_map"), format!("{} {} 1", uid, uid).into_bytes()),
+            (c_path("/proc/self/gid_map"), format!("{} {} 1", gid, gid).into_bytes()),
+        ];
+
+        // B. Landlock (the program itself must stay executable)
+        let mut read_only: Vec<PathBuf> = SYSTEM_READ_ONLY.iter().map(PathBuf::from).collect();
+        read_only.extend(profile.read_only.iter().cloned());
+        read_only.extend(resolve_program(program));
+        let mut writable: Vec<PathBuf> = SYSTEM_WRITABLE.iter().map(PathBuf::from).collect();
+        writable.extend(profile.writable.iter().cloned());
+
+        // Without landlock the profile can't be enforced, so refuse unless told otherwise
+        let ruleset = match landlock_abi() {
+            Some(_) => Some(build_ruleset(&read_only, &writable).context("Failed to build landlock ruleset")?),
+            None if profile.allow_without_landlock => {
+                tracing::warn!("Landlock is not available on this kernel; '{}' gets no filesystem rules", program);
+                None
+            }
+            None => {
+                return Err(anyhow!(
+                    "Landlock is not available on this kernel, so the sandbox can't confine '{}' \
+                     (set sandbox.allowWithoutLandlock to run it anyway)",
+                    program
+                ))
+            }
+        };
+
+        // C. Seccomp
+        let filters = match profile.seccomp {
+            true => vec![build_no_clone3()?, build_filter(&profile.allow_syscalls)?],
+            false => Vec::new(),
+        };
+
+        Ok(Self {
+            unshare_flags,
+            id_maps,
+            proc_fs: c_path("proc"),
+            proc_dir: c_path("/proc"),
+            ruleset,
+            filters,
+        })
+    }
+
+    // 2. Arrange for the sandbox to be entered between fork and exec
+    pub fn install(self, cmd: &mut Command) {
+        let mut sandbox = self;
+        // SAFETY: the hook only makes syscalls, directly or through the thin
+        // landlock/seccompiler wrappers; nothing it touches is shared with the parent.
+        unsafe {
+            cmd.pre_exec(move || sandbox.enter());
+        }
+    }
+
+    // Runs in the forked child. On success the child exec's the server.
+    fn enter(&mut self) -> io::Result<()> {
+        // A. New namespaces, with our ids mapped inside
+        // SAFETY: the child is single-threaded, which unshare(CLONE_NEWUSER) requires
+        check(unsafe { libc::unshare(self.unshare_flags) })?;
+        for (path, contents) in &self.id_maps {
+            write_file(path, contents)?;
+        }
+
+        // The PID namespace only applies to children: fork once more so the
+        // server becomes PID 1 in it. The middle process relays signals down
+        // and the exit back up. Until its handlers are in place, the signals
+        // it forwards stay blocked (so a SIGTERM can't slip past it).
+        // SAFETY: single-threaded child, see above; the sets are plain data
+        let mut forwarded: libc::sigset_t = unsafe { std::mem::zeroed() };
+        let mut previous: libc::sigset_t = unsafe { std::mem::zeroed() };
+        unsafe {
+            libc::sigemptyset(&mut forwarded);
+            for signal in FORWARDED_SIGNALS {
+                libc::sigaddset(&mut forwarded, signal);
+            }
+            check(libc::sigprocmask(libc::SIG_BLOCK, &forwarded, &mut previous))?;
+        }
+        match unsafe { libc::fork() } {
+            -1 => return Err(io::Error::last_os_error()),
+            0 => {}
+            pid => relay_exit(pid, &previous),
+        }
+        // SAFETY: restores the mask we had before forking
+        check(unsafe { libc::sigprocmask(libc::SIG_SETMASK, &previous, std::ptr::null_mut()) })?;
+        // If the middle process dies (e.g. SIGKILL on shutdown), so do we.
+        // A new session drops the controlling terminal, so TIOCSTI has nothing to aim at.
+        // SAFETY: plain syscalls
+        unsafe {
+            libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+            check(libc::setsid())?;
+
+            // A /proc that only shows our namespace. Without it the server would
+            // still see the host's processes, so a failed mount is fatal.
+            let none = std::ptr::null();
+            check(libc::mount(none, c"/".as_ptr(), none, libc::MS_REC | libc::MS_PRIVATE, std::ptr::null()))?;
+            check(libc::mount(
+                self.proc_fs.as_ptr(),
+                self.proc_dir.as_ptr(),
+                self.proc_fs.as_ptr(),
+                libc::MS_NOSUID | libc::MS_NODEV | libc::MS_NOEXEC,
+                std::ptr::null(),
+            ))?;
+        }
+
+        // B. Filesystem rules (plus the fresh /proc)
+        if let Some(mut ruleset) = self.ruleset.take() {
+            // SAFETY: valid NUL-terminated path; the fd is handed to OwnedFd
+            let proc_fd = unsafe { libc::open(self.proc_dir.as_ptr(), libc::O_PATH | libc::O_CLOEXEC) };
+            check(proc_fd)?;
+            // SAFETY: we just opened it and nobody else owns it
+            let proc_fd = unsafe { OwnedFd::from_raw_fd(proc_fd) };
+            ruleset = ruleset
+                .add_rule(PathBeneath::new(proc_fd, AccessFs::from_read(LANDLOCK_ABI)))
+                .map_err(io::Error::other)?;
+            ruleset.restrict_self().map_err(io::Error::other)?;
+        }
+
+        // C. Syscall filters last: the steps above need syscalls they may block
+        for filter in &self.filters {
+            seccompiler::apply_filter(filter).map_err(io::Error::other)?;
+        }
+        Ok(())
+    }
+}
+
+// The landlock ABI version the kernel speaks, if any
+pub fn landlock_abi() -> Option<i64> {
+    // SAFETY: LANDLOCK_CREATE_RULESET_VERSION only queries, it creates nothing
+    let version = unsafe {
+        libc::syscall(libc::SYS_landlock_create_ruleset, std::ptr::null::<u8>(), 0usize, 1u32)
+    };
+    (version > 0).then_some(version)
+}
+
+fn build_ruleset(read_only: &[PathBuf], writable: &[PathBuf]) -> Result<RulesetCreated> {
+    // Missing paths are skipped by `path_beneath_rules`
+    let ruleset = Ruleset::default()
+        .handle_access(AccessFs::from_all(LANDLOCK_ABI))?
+        .create()?
+        .add_rules(path_beneath_rules(read_only, AccessFs::from_read(LANDLOCK_ABI)))?
+        .add_rules(path_beneath_rules(writable, AccessFs::from_all(LANDLOCK_ABI)))?;
+    Ok(ruleset)
+}
+
+fn build_filter(extra: &[String]) -> Result<BpfProgram> {
+    let mut rules = BTreeMap::new();
+    for (_, nr) in DEFAULT_SYSCALLS.iter().chain(ARCH_SYSCALLS) {
+        rules.insert(*nr, vec![]);
+    }
+    // ioctl, except pushing input into a terminal (the request type differs between targets)
+    #[allow(clippy::unnecessary_cast)]
+    let no_tiocsti = SeccompCondition::new(1, SeccompCmpArgLen::Dword, SeccompCmpOp::Ne, libc::TIOCSTI as u64)?;
+    rules.insert(libc::SYS_ioctl, vec![SeccompRule::new(vec![no_tiocsti])?]);
+    // clone, as long as it doesn't ask for new namespaces
+    let no_namespaces = SeccompCondition::new(0, SeccompCmpArgLen::Qword, SeccompCmpOp::MaskedEq(CLONE_NEW_FLAGS as u64), 0)?;
+    rules.insert(libc::SYS_clone, vec![SeccompRule::new(vec![no_namespaces])?]);
+    for name in extra {
+        let nr = syscall_number(name)
+            .ok_or(anyhow!("sandbox.allowSyscalls: unknown syscall '{}'", name))?;
+        // Listing a default syscall again doesn't lift its argument checks
+        rules.entry(nr).or_insert_with(Vec::new);
+    }
+
+    let arch = TargetArch::try_from(std::env::consts::ARCH)
+        .map_err(|e| anyhow!("Seccomp is not supported on this architecture: {}", e))?;
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Errno(libc::EPERM as u32), // Not on the list
+        SeccompAction::Allow,
+        arch,
+    )?;
+    Ok(filter.try_into()?)
+}
+
+// clone3 passes its flags in a struct seccomp can't look into. ENOSYS (rather
+// than EPERM) makes libc fall back to plain clone, which the main filter checks.
+fn build_no_clone3() -> Result<BpfProgram> {
+    let arch = TargetArch::try_from(std::env::consts::ARCH)
+        .map_err(|e| anyhow!("Seccomp is not supported on this architecture: {}", e))?;
+    let filter = SeccompFilter::new(
+        BTreeMap::from([(libc::SYS_clone3, vec![])]),
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::ENOSYS as u32),
+        arch,
+    )?;
+    Ok(filter.try_into()?)
+}
+
+// Where the program lives, so it can be exec'd under landlock
+fn resolve_program(program: &str) -> Option<PathBuf> {
+    if program.contains('/') {
+        return Path::new(program).canonicalize().ok();
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+        .and_then(|found| found.canonicalize().ok())
+}
+
+fn c_path(path: &str) -> CString {
+    CString::new(path).expect("static path without NUL")
+}
+
+fn check(rc: libc::c_int) -> io::Result<()> {
+    match rc {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+fn write_file(path: &CString, contents: &[u8]) -> io::Result<()> {
+    // SAFETY: valid NUL-terminated path and buffer; the fd is closed below
+    unsafe {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        check(fd)?;
+        let written = libc::write(fd, contents.as_ptr().cast(), contents.len());
+        libc::close(fd);
+        if written < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+// The middle process: pass shutdown signals on to the server, wait for it and
+// exit the same way. Never returns.
+fn relay_exit(pid: libc::pid_t, mask: &libc::sigset_t) -> ! {
+    // SAFETY: plain syscalls. Closing every fd releases our copies of the
+    // stdio pipes and of std's exec-error pipe, so the host sees the server's
+    // exec succeed (or fail) and its EOF, not ours.
+    unsafe {
+        libc::syscall(libc::SYS_close_range, 0u32, u32::MAX, 0u32);
+
+        // The host's SIGTERM is meant for the server: without this we'd die,
+        // and PR_SET_PDEATHSIG would SIGKILL the server instead
+        SERVER_PID.store(pid, Ordering::Relaxed);
+        for signal in FORWARDED_SIGNALS {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            action.sa_sigaction = forward_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            action.sa_flags = libc::SA_RESTART;
+            libc::sigemptyset(&mut action.sa_mask);
+            libc::sigaction(signal, &action, std::ptr::null_mut());
+        }
+        libc::sigprocmask(libc::SIG_SETMASK, mask, std::ptr::null_mut());
+
+        let mut status = 0;
+        loop {
+            if libc::waitpid(pid, &mut status, 0) == pid {
+                break;
+            }
+            if io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+                libc::_exit(1);
+            }
+        }
+        if libc::WIFEXITED(status) {
+            libc::_exit(libc::WEXITSTATUS(status));
+        }
+        // Die of the same signal so the host can tell e.g. SIGXCPU from a crash
+        let signal = libc::WTERMSIG(status);
+        let mut unblock: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut unblock);
+        libc::sigaddset(&mut unblock, signal);
+        libc::sigprocmask(libc::SIG_UNBLOCK, &unblock, std::ptr::null_mut());
+        libc::signal(signal, libc::SIG_DFL);
+        libc::kill(libc::getpid(), signal);
+        libc::_exit(128 + signal);
+    }
+}
+
+// Signal handler in the middle process (kill is async-signal-safe)
+extern "C" fn forward_signal(signal: libc::c_int) {
+    // SAFETY: plain syscall
+    unsafe {
+        libc::kill(SERVER_PID.load(Ordering::Relaxed), signal);
+    }
+}
+
+// --- THE SYSCALL TABLES ---
+// The default allowlist is enough for typical runtimes (Rust, C, Python,
+// Node) doing file and socket I/O. Anything else has to be granted with
+// `allowSyscalls`, by name.
+const DEFAULT_SYSCALLS: &[(&str, i64)] = &[
+    // Files
+    ("read", libc::SYS_read), ("write", libc::SYS_write), ("readv", libc::SYS_readv),
+    ("writev", libc::SYS_writev), ("pread64", libc::SYS_pread64), ("pwrite64", libc::SYS_pwrite64),
+    ("openat", libc::SYS_openat), ("close", libc::SYS_close),
+    ("close_range", libc::SYS_close_range), ("fstat", libc::SYS_fstat),
+    ("newfstatat", libc::SYS_newfstatat), ("statx", libc::SYS_statx), ("statfs", libc::SYS_statfs),
+    ("fstatfs", libc::SYS_fstatfs), ("lseek", libc::SYS_lseek), ("faccessat", libc::SYS_faccessat),
+    ("faccessat2", libc::SYS_faccessat2), ("getdents64", libc::SYS_getdents64),
+    ("getcwd", libc::SYS_getcwd), ("chdir", libc::SYS_chdir), ("fchdir", libc::SYS_fchdir),
+    ("readlinkat", libc::SYS_readlinkat), ("mkdirat", libc::SYS_mkdirat),
+    ("unlinkat", libc::SYS_unlinkat), ("renameat", libc::SYS_renameat),
+    ("renameat2", libc::SYS_renameat2), ("symlinkat", libc::SYS_symlinkat),
+    ("linkat", libc::SYS_linkat), ("fchmod", libc::SYS_fchmod), ("fchmodat", libc::SYS_fchmodat),
+    ("fchown", libc::SYS_fchown), ("fchownat", libc::SYS_fchownat),
+    ("utimensat", libc::SYS_utimensat), ("umask", libc::SYS_umask),
+    ("ftruncate", libc::SYS_ftruncate), ("fallocate", libc::SYS_fallocate),
+    ("fsync", libc::SYS_fsync), ("fdatasync", libc::SYS_fdatasync), ("flock", libc::SYS_flock),
+    ("fcntl", libc::SYS_fcntl), ("ioctl", libc::SYS_ioctl), ("dup", libc::SYS_dup),
+    ("dup3", libc::SYS_dup3), ("pipe2", libc::SYS_pipe2), ("sendfile", libc::SYS_sendfile),
+    ("copy_file_range", libc::SYS_copy_file_range), ("fadvise64", libc::SYS_fadvise64),
+    ("memfd_create", libc::SYS_memfd_create), ("inotify_init1", libc::SYS_inotify_init1),
+    ("inotify_add_watch", libc::SYS_inotify_add_watch),
+    ("inotify_rm_watch", libc::SYS_inotify_rm_watch),
+    // Memory
+    ("brk", libc::SYS_brk), ("mmap", libc::SYS_mmap), ("munmap", libc::SYS_munmap),
+    ("mprotect", libc::SYS_mprotect), ("mremap", libc::SYS_mremap), ("madvise", libc::SYS_madvise),
+    ("membarrier", libc::SYS_membarrier),
+    // Processes and threads
+    ("execve", libc::SYS_execve), ("execveat", libc::SYS_execveat), ("exit", libc::SYS_exit),
+    ("exit_group", libc::SYS_exit_group), ("wait4", libc::SYS_wait4), ("waitid", libc::SYS_waitid),
+    ("clone", libc::SYS_clone), ("clone3", libc::SYS_clone3), ("kill", libc::SYS_kill),
+    ("tgkill", libc::SYS_tgkill), ("getpid", libc::SYS_getpid), ("getppid", libc::SYS_getppid),
+    ("gettid", libc::SYS_gettid), ("getuid", libc::SYS_getuid), ("geteuid", libc::SYS_geteuid),
+    ("getgid", libc::SYS_getgid), ("getegid", libc::SYS_getegid),
+    ("getgroups", libc::SYS_getgroups), ("getresuid", libc::SYS_getresuid),
+    ("getresgid", libc::SYS_getresgid), ("getpgid", libc::SYS_getpgid),
+    ("setpgid", libc::SYS_setpgid), ("getsid", libc::SYS_getsid), ("setsid", libc::SYS_setsid),
+    ("prctl", libc::SYS_prctl), ("set_tid_address", libc::SYS_set_tid_address),
+    ("set_robust_list", libc::SYS_set_robust_list), ("get_robust_list", libc::SYS_get_robust_list),
+    ("rseq", libc::SYS_rseq), ("futex", libc::SYS_futex), ("sched_yield", libc::SYS_sched_yield),
+    ("sched_getaffinity", libc::SYS_sched_getaffinity), ("getrlimit", libc::SYS_getrlimit),
+    ("prlimit64", libc::SYS_prlimit64), ("getrusage", libc::SYS_getrusage),
+    ("getpriority", libc::SYS_getpriority), ("capget", libc::SYS_capget),
+    ("pidfd_open", libc::SYS_pidfd_open),
+    // Signals
+    ("rt_sigaction", libc::SYS_rt_sigaction), ("rt_sigprocmask", libc::SYS_rt_sigprocmask),
+    ("rt_sigreturn", libc::SYS_rt_sigreturn), ("rt_sigsuspend", libc::SYS_rt_sigsuspend),
+    ("sigaltstack", libc::SYS_sigaltstack),
+    // Time, randomness, system info
+    ("nanosleep", libc::SYS_nanosleep), ("clock_nanosleep", libc::SYS_clock_nanosleep),
+    ("clock_gettime", libc::SYS_clock_gettime), ("clock_getres", libc::SYS_clock_getres),
+    ("gettimeofday", libc::SYS_gettimeofday), ("getrandom", libc::SYS_getrandom),
+    ("uname", libc::SYS_uname), ("sysinfo", libc::SYS_sysinfo), ("times", libc::SYS_times),
+    // Event loops
+    ("epoll_create1", libc::SYS_epoll_create1), ("epoll_ctl", libc::SYS_epoll_ctl),
+    ("epoll_pwait", libc::SYS_epoll_pwait), ("epoll_pwait2", libc::SYS_epoll_pwait2),
+    ("eventfd2", libc::SYS_eventfd2), ("ppoll", libc::SYS_ppoll), ("pselect6", libc::SYS_pselect6),
+    ("timerfd_create", libc::SYS_timerfd_create), ("timerfd_settime", libc::SYS_timerfd_settime),
+    ("timerfd_gettime", libc::SYS_timerfd_gettime),
+    // Sockets (the network namespace decides what they can reach)
+    ("socket", libc::SYS_socket), ("socketpair", libc::SYS_socketpair),
+    ("connect", libc::SYS_connect), ("bind", libc::SYS_bind), ("listen", libc::SYS_listen),
+    ("accept4", libc::SYS_accept4), ("getsockname", libc::SYS_getsockname),
+    ("getpeername", libc::SYS_getpeername), ("sendto", libc::SYS_sendto),
+    ("recvfrom", libc::SYS_recvfrom), ("sendmsg", libc::SYS_sendmsg),
+    ("recvmsg", libc::SYS_recvmsg), ("sendmmsg", libc::SYS_sendmmsg),
+    ("recvmmsg", libc::SYS_recvmmsg), ("shutdown", libc::SYS_shutdown),
+    ("setsockopt", libc::SYS_setsockopt), ("getsockopt", libc::SYS_getsockopt),
+];
+
+// Older call variants libc still uses on x86_64
+#[cfg(target_arch = "x86_64")]
+const ARCH_SYSCALLS: &[(&str, i64)] = &[
+    ("open", libc::SYS_open), ("stat", libc::SYS_stat), ("lstat", libc::SYS_lstat),
+    ("access", libc::SYS_access), ("readlink", libc::SYS_readlink),
+    ("getdents", libc::SYS_getdents), ("mkdir", libc::SYS_mkdir), ("rmdir", libc::SYS_rmdir),
+    ("unlink", libc::SYS_unlink), ("rename", libc::SYS_rename), ("chmod", libc::SYS_chmod),
+    ("pipe", libc::SYS_pipe), ("dup2", libc::SYS_dup2), ("poll", libc::SYS_poll),
+    ("select", libc::SYS_select), ("fork", libc::SYS_fork), ("vfork", libc::SYS_vfork),
+    ("arch_prctl", libc::SYS_arch_prctl), ("epoll_create", libc::SYS_epoll_create),
+    ("epoll_wait", libc::SYS_epoll_wait), ("eventfd", libc::SYS_eventfd), ("time", libc::SYS_time),
+    ("alarm", libc::SYS_alarm),
+];
+#[cfg(not(target_arch = "x86_64"))]
+const ARCH_SYSCALLS: &[(&str, i64)] = &[];
+
+// Never allowed by default, but a profile may ask for them
+const OPT_IN_SYSCALLS: &[(&str, i64)] = &[
+    ("ptrace", libc::SYS_ptrace), ("process_vm_readv", libc::SYS_process_vm_readv),
+    ("process_vm_writev", libc::SYS_process_vm_writev), ("mount", libc::SYS_mount),
+    ("umount2", libc::SYS_umount2), ("pivot_root", libc::SYS_pivot_root),
+    ("chroot", libc::SYS_chroot), ("unshare", libc::SYS_unshare), ("setns", libc::SYS_setns),
+    ("setuid", libc::SYS_setuid), ("setgid", libc::SYS_setgid), ("setgroups", libc::SYS_setgroups),
+    ("setresuid", libc::SYS_setresuid), ("setresgid", libc::SYS_setresgid),
+    ("setrlimit", libc::SYS_setrlimit), ("setpriority", libc::SYS_setpriority),
+    ("sched_setaffinity", libc::SYS_sched_setaffinity), ("mlock", libc::SYS_mlock),
+    ("munlock", libc::SYS_munlock), ("mlockall", libc::SYS_mlockall),
+    ("munlockall", libc::SYS_munlockall), ("bpf", libc::SYS_bpf),
+    ("perf_event_open", libc::SYS_perf_event_open), ("keyctl", libc::SYS_keyctl),
+    ("add_key", libc::SYS_add_key), ("request_key", libc::SYS_request_key),
+    ("io_uring_setup", libc::SYS_io_uring_setup), ("io_uring_enter", libc::SYS_io_uring_enter),
+    ("io_uring_register", libc::SYS_io_uring_register), ("userfaultfd", libc::SYS_userfaultfd),
+    ("personality", libc::SYS_personality),
+];
+
+// Look a syscall up by name
+pub fn syscall_number(name: &str) -> Option<i64> {
+    DEFAULT_SYSCALLS.iter()
+        .chain(ARCH_SYSCALLS)
+        .chain(OPT_IN_SYSCALLS)
+        .find(|(known, _)| *known == name)
+        .map(|(_, nr)| *nr)
+}
@@ -0,0 +1,455 @@
+// src/schema.rs
+// The Proofreader: JSON Schema validation for tool arguments, so the LLM
+// hears about a bad call before the server does.
+// Covers drafts 2020-12 and 07: the keywords tool schemas actually use,
+// local `$ref`s and boolean schemas. `format` and `unevaluated*` are treated
+// as annotations. Refs we can't resolve locally and patterns the regex crate
+// can't compile are skipped with a warning rather than failing the call.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use regex::Regex;
+use serde_json::{Map, Value};
+
+// Deeper than this and we assume a `$ref` loop
+const MAX_DEPTH: usize = 64;
+// Subschema checks per validation. Recursive refs under anyOf/oneOf can
+// branch exponentially long before MAX_DEPTH; past this we stop and let the
+// call through rather than freeze the host.
+const MAX_STEPS: usize = 100_000;
+// Compiled patterns kept across calls (the cache starts over when full)
+const MAX_CACHED_PATTERNS: usize = 1024;
+
+static PATTERNS: OnceLock<Mutex<HashMap<String, Result<Regex, String>>>> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Draft {
+    Draft7,
+    Draft2020,
+}
+
+impl Draft {
+    // From `$schema`. Older drafts validate like 07; no `$schema` means 2020-12 (MCP's default).
+    pub fn detect(schema: &Value) -> Self {
+        let declared = schema.get("$schema").and_then(Value::as_str).unwrap_or_default();
+        match ["draft-07", "draft-06", "draft-04"].iter().any(|d| declared.contains(d)) {
+            true => Draft::Draft7,
+            false => Draft::Draft2020,
+        }
+    }
+}
+
+// One thing wrong with the instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    // JSON Pointer into the instance ("" is the instance itself)
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.path.is_empty() {
+            true => write!(f, "(root): {}", self.message),
+            false => write!(f, "{}: {}", self.path, self.message),
+        }
+    }
+}
+
+// Everything wrong with `instance` (empty means valid)
+pub fn validate(schema: &Value, instance: &Value) -> Vec<Violation> {
+    let mut validator = Validator {
+        root: schema,
+        draft: Draft::detect(schema),
+        violations: Vec::new(),
+        depth: 0,
+        steps: 0,
+    };
+    validator.check(schema, instance, "");
+    // Half a check proves nothing either way
+    match validator.steps > MAX_STEPS {
+        true => {
+            validator.skip("", format!("gave up after {} subschema checks", MAX_STEPS));
+            Vec::new()
+        }
+        false => validator.violations,
+    }
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+    draft: Draft,
+    violations: Vec<Violation>,
+    depth: usize,
+    // Work done so far, against MAX_STEPS
+    steps: usize,
+}
+
+impl<'a> Validator<'a> {
+    fn fail(&mut self, path: &str, message: String) {
+        self.violations.push(Violation { path: path.to_string(), message });
+    }
+
+    // A keyword we can't enforce: say so instead of silently passing
+    fn skip(&self, path: &str, message: String) {
+        let at = match path.is_empty() {
+            true => "(root)",
+            false => path,
+        };
+        tracing::warn!("Schema not enforced at {}: {}", at, message);
+    }
+
+    // Run a subschema on the side (for anyOf, not, if, ...)
+    fn passes(&mut self, schema: &'a Value, instance: &Value) -> bool {
+        let outer = std::mem::take(&mut self.violations);
+        self.check(schema, instance, "");
+        let passed = self.violations.is_empty();
+        self.violations = outer;
+        passed
+    }
+
+    fn check(&mut self, schema: &'a Value, instance: &Value, path: &str) {
+        let keywords = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => return self.fail(path, "no value is allowed here".to_string()),
+            Value::Object(keywords) => keywords,
+            _ => return, // Not a schema; nothing to enforce
+        };
+        self.steps += 1;
+        if self.depth >= MAX_DEPTH || self.steps > MAX_STEPS {
+            return;
+        }
+        self.depth += 1;
+
+        // A. References (in draft-07 a $ref hides its siblings)
+        if let Some(reference) = keywords.get("$ref").and_then(Value::as_str) {
+            match self.resolve(reference) {
+                Some(target) => self.check(target, instance, path),
+                None => self.skip(path, format!("can't resolve $ref '{}' within the schema", reference)),
+            }
+        }
+        if self.draft == Draft::Draft7 && keywords.contains_key("$ref") {
+            self.depth -= 1;
+            return;
+        }
+
+        // B. Keywords for any type
+        self.check_generic(keywords, instance, path);
+        self.check_combinators(keywords, instance, path);
+
+        // C. Type-specific keywords
+        match instance {
+            Value::Number(_) => self.check_number(keywords, instance, path),
+            Value::String(text) => self.check_string(keywords, text, path),
+            Value::Array(items) => self.check_array(keywords, items, path),
+            Value::Object(fields) => self.check_object(keywords, fields, path),
+            _ => {}
+        }
+        self.depth -= 1;
+    }
+
+    // "#/$defs/point" -> the subschema (only refs into this document)
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        let decoded = percent_decode(pointer)?;
+        self.root.pointer(&decoded)
+    }
+
+    fn check_generic(&mut self, keywords: &Map<String, Value>, instance: &Value, path: &str) {
+        if let Some(expected) = keywords.get("type") {
+            let types: Vec<&str> = match expected {
+                Value::String(single) => vec![single.as_str()],
+                Value::Array(many) => many.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|t| has_type(instance, t)) {
+                self.fail(path, format!("expected {}, got {}", types.join(" or "), type_name(instance)));
+            }
+        }
+        if let Some(Value::Array(allowed)) = keywords.get("enum") {
+            if !allowed.iter().any(|a| json_eq(a, instance)) {
+                let list: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                self.fail(path, format!("must be one of {}", list.join(", ")));
+            }
+        }
+        if let Some(constant) = keywords.get("const") {
+            if !json_eq(constant, instance) {
+                self.fail(path, format!("must be {}", constant));
+            }
+        }
+    }
+
+    fn check_combinators(&mut self, keywords: &'a Map<String, Value>, instance: &Value, path: &str) {
+        if let Some(Value::Array(all)) = keywords.get("allOf") {
+            for schema in all {
+                self.check(schema, instance, path);
+            }
+        }
+        if let Some(Value::Array(any)) = keywords.get("anyOf") {
+            if !any.iter().any(|schema| self.passes(schema, instance)) {
+                self.fail(path, "does not match any of the allowed alternatives (anyOf)".to_string());
+            }
+        }
+        if let Some(Value::Array(one)) = keywords.get("oneOf") {
+            let matched = one.iter().filter(|schema| self.passes(schema, instance)).count();
+            if matched != 1 {
+                self.fail(path, format!("matches {} alternatives, expected exactly one (oneOf)", matched));
+            }
+        }
+        if let Some(not) = keywords.get("not") {
+            if self.passes(not, instance) {
+                self.fail(path, "matches a schema it must not match (not)".to_string());
+            }
+        }
+        if let Some(condition) = keywords.get("if") {
+            let branch = match self.passes(condition, instance) {
+                true => keywords.get("then"),
+                false => keywords.get("else"),
+            };
+            if let Some(branch) = branch {
+                self.check(branch, instance, path);
+            }
+        }
+    }
+
+    fn check_number(&mut self, keywords: &Map<String, Value>, instance: &Value, path: &str) {
+        let Some(value) = instance.as_f64() else { return };
+        let limit = |name: &str| keywords.get(name).and_then(Value::as_f64);
+
+        if let Some(min) = limit("minimum").filter(|min| value < *min) {
+            self.fail(path, format!("must be >= {}", min));
+        }
+        if let Some(max) = limit("maximum").filter(|max| value > *max) {
+            self.fail(path, format!("must be <= {}", max));
+        }
+        // Numbers only; draft-04's boolean form is ignored
+        if let Some(min) = limit("exclusiveMinimum").filter(|min| value <= *min) {
+            self.fail(path, format!("must be > {}", min));
+        }
+        if let Some(max) = limit("exclusiveMaximum").filter(|max| value >= *max) {
+            self.fail(path, format!("must be < {}", max));
+        }
+        if let Some(step) = limit("multipleOf").filter(|step| *step > 0.0) {
+            let quotient = value / step;
+            if (quotient - quotient.round()).abs() > 1e-9 {
+                self.fail(path, format!("must be a multiple of {}", step));
+            }
+        }
+    }
+
+    fn check_string(&mut self, keywords: &Map<String, Value>, text: &str, path: &str) {
+        let length = text.chars().count() as u64;
+        if let Some(min) = keywords.get("minLength").and_then(Value::as_u64).filter(|min| length < *min) {
+            self.fail(path, format!("must be at least {} characters long", min));
+        }
+        if let Some(max) = keywords.get("maxLength").and_then(Value::as_u64).filter(|max| length > *max) {
+            self.fail(path, format!("must be at most {} characters long", max));
+        }
+        if let Some(pattern) = keywords.get("pattern").and_then(Value::as_str) {
+            match compiled(pattern) {
+                Ok(re) if !re.is_match(text) => self.fail(path, format!("must match the pattern '{}'", pattern)),
+                Ok(_) => {}
+                Err(e) => self.skip(path, format!("can't compile pattern '{}': {}", pattern, e)),
+            }
+        }
+    }
+
+    fn check_array(&mut self, keywords: &'a Map<String, Value>, items: &[Value], path: &str) {
+        // A. Positional items, then the rest
+        let (prefix, rest) = match self.draft {
+            Draft::Draft2020 => (keywords.get("prefixItems"), keywords.get("items")),
+            Draft::Draft7 => match keywords.get("items") {
+                Some(tuple @ Value::Array(_)) => (Some(tuple), keywords.get("additionalItems")),
+                all => (None, all),
+            },
+        };
+        let prefix: &[Value] = prefix.and_then(Value::as_array).map(Vec::as_slice).unwrap_or_default();
+        for (i, item) in items.iter().enumerate() {
+            let schema = match prefix.get(i) {
+                Some(schema) => schema,
+                None => match rest {
+                    Some(schema) => schema,
+                    None => break,
+                },
+            };
+            if *schema == Value::Bool(false) && i >= prefix.len() {
+                self.fail(path, format!("must have at most {} items", prefix.len()));
+                break;
+            }
+            self.check(schema, item, &format!("{}/{}", path, i));
+        }
+
+        // B. Counts
+        let count = items.len() as u64;
+        if let Some(min) = keywords.get("minItems").and_then(Value::as_u64).filter(|min| count < *min) {
+            self.fail(path, format!("must have at least {} items", min));
+        }
+        if let Some(max) = keywords.get("maxItems").and_then(Value::as_u64).filter(|max| count > *max) {
+            self.fail(path, format!("must have at most {} items", max));
+        }
+        if keywords.get("uniqueItems") == Some(&Value::Bool(true)) {
+            let duplicate = items.iter().enumerate().any(|(i, a)| items[..i].iter().any(|b| json_eq(a, b)));
+            if duplicate {
+                self.fail(path, "items must be unique".to_string());
+            }
+        }
+        if let Some(contains) = keywords.get("contains") {
+            let matching = items.iter().filter(|item| self.passes(contains, item)).count() as u64;
+            let min = keywords.get("minContains").and_then(Value::as_u64).unwrap_or(1);
+            if matching < min {
+                self.fail(path, format!("must contain at least {} matching item(s), found {}", min, matching));
+            }
+            if let Some(max) = keywords.get("maxContains").and_then(Value::as_u64).filter(|max| matching > *max) {
+                self.fail(path, format!("must contain at most {} matching item(s), found {}", max, matching));
+            }
+        }
+    }
+
+    fn check_object(&mut self, keywords: &'a Map<String, Value>, fields: &Map<String, Value>, path: &str) {
+        // A. Required and dependent properties
+        if let Some(Value::Array(required)) = keywords.get("required") {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !fields.contains_key(name) {
+                    self.fail(path, format!("missing required property '{}'", name));
+                }
+            }
+        }
+        let dependencies = match self.draft {
+            Draft::Draft7 => [keywords.get("dependencies"), None],
+            Draft::Draft2020 => [keywords.get("dependentRequired"), keywords.get("dependentSchemas")],
+        };
+        for (trigger, dependency) in dependencies.into_iter().flatten().filter_map(Value::as_object).flatten() {
+            if !fields.contains_key(trigger) {
+                continue;
+            }
+            match dependency {
+                Value::Array(names) => {
+                    for name in names.iter().filter_map(Value::as_str).filter(|n| !fields.contains_key(*n)) {
+                        self.fail(path, format!("property '{}' requires property '{}'", trigger, name));
+                    }
+                }
+                schema => self.check(schema, &Value::Object(fields.clone()), path),
+            }
+        }
+
+        // B. Each property against `properties`, `patternProperties` or `additionalProperties`
+        let properties = keywords.get("properties").and_then(Value::as_object);
+        let patterns: Vec<(Regex, &Value)> = keywords.get("patternProperties").and_then(Value::as_object)
+            .map(|patterns| patterns.iter().filter_map(|(p, s)| match compiled(p) {
+                Ok(re) => Some((re, s)),
+                Err(e) => {
+                    self.skip(path, format!("can't compile patternProperties key '{}': {}", p, e));
+                    None
+                }
+            }).collect())
+            .unwrap_or_default();
+        let additional = keywords.get("additionalProperties");
+
+        for (name, value) in fields {
+            let child = format!("{}/{}", path, escape_pointer(name));
+            let mut matched = false;
+            if let Some(schema) = properties.and_then(|p| p.get(name)) {
+                matched = true;
+                self.check(schema, value, &child);
+            }
+            for (pattern, schema) in &patterns {
+                if pattern.is_match(name) {
+                    matched = true;
+                    self.check(schema, value, &child);
+                }
+            }
+            match additional {
+                _ if matched => {}
+                Some(Value::Bool(false)) => self.fail(path, format!("unexpected property '{}'", name)),
+                Some(schema) => self.check(schema, value, &child),
+                None => {}
+            }
+            if let Some(names) = keywords.get("propertyNames") {
+                if !self.passes(names, &Value::String(name.clone())) {
+                    self.fail(path, format!("property name '{}' is not allowed", name));
+                }
+            }
+        }
+
+        // C. Counts
+        let count = fields.len() as u64;
+        if let Some(min) = keywords.get("minProperties").and_then(Value::as_u64).filter(|min| count < *min) {
+            self.fail(path, format!("must have at least {} properties", min));
+        }
+        if let Some(max) = keywords.get("maxProperties").and_then(Value::as_u64).filter(|max| count > *max) {
+            self.fail(path, format!("must have at most {} properties", max));
+        }
+    }
+}
+
+// A pattern, compiled once and then taken from the cache
+fn compiled(pattern: &str) -> Result<Regex, String> {
+    let mut cache = PATTERNS.get_or_init(Default::default).lock().unwrap();
+    if let Some(found) = cache.get(pattern) {
+        return found.clone();
+    }
+    if cache.len() >= MAX_CACHED_PATTERNS {
+        cache.clear();
+    }
+    let regex = Regex::new(pattern).map_err(|e| e.to_string());
+    cache.insert(pattern.to_string(), regex.clone());
+    regex
+}
+
+fn has_type(instance: &Value, expected: &str) -> bool {
+    match expected {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "string" => instance.is_string(),
+        "array" => instance.is_array(),
+        "object" => instance.is_object(),
+        "number" => instance.is_number(),
+        // 1.0 counts as an integer
+        "integer" => instance.as_f64().is_some_and(|n| n.fract() == 0.0),
+        _ => false,
+    }
+}
+
+fn type_name(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+// Equality where 1 and 1.0 are the same number
+fn json_eq(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        (Value::Array(x), Value::Array(y)) => x.len() == y.len() && x.iter().zip(y).all(|(a, b)| json_eq(a, b)),
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len() && x.iter().all(|(k, v)| y.get(k).is_some_and(|w| json_eq(v, w)))
+        }
+        _ => a == b,
+    }
+}
+
+fn escape_pointer(name: &str) -> String {
+    name.replace('~', "~0").replace('/', "~1")
+}
+
+// `$ref` fragments may be percent-encoded ("#/$defs/a%20b")
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut input = text.bytes();
+    while let Some(byte) = input.next() {
+        match byte {
+            b'%' => {
+                let hex = [input.next()?, input.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
@@ -2,9 +2,16 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
+use serde_json::Value;
+use crate::roots::{path_from_uri, Roots};
 
-#[derive(Debug, Serialize, Deserialize)]
+// Argument names that (probably) hold a filesystem path. Matched against the
+// last word of the name, so "filePath" and "source_dirs" count, "profile" doesn't.
+const PATH_KEYS: &[&str] = &["path", "file", "filename", "dir", "directory", "folder", "root", "cwd"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     pub version: String,
     pub global_policy: String, // "allow" or "deny"
@@ -33,4 +40,75 @@ impl SecurityConfig {
         // Step B: Fallback to global policy
         self.global_policy == "allow"
     }
+
+    // 2b. The same for a tool reached through the registry. A rule for
+    // "server__tool" wins, then one for the name the LLM sees (bare only when
+    // no other server has a tool of that name).
+    pub fn check_tool_permission(&self, exposed: &str, qualified: &str) -> bool {
+        match self.rules.get(qualified).or_else(|| self.rules.get(exposed)) {
+            Some(policy) => policy == "allow",
+            None => self.global_policy == "allow",
+        }
+    }
+
+    // 3. The Fence: the first path argument that points outside the roots.
+    // Checked are file:// URIs anywhere and paths under path-like names
+    // ("path", "filePath", "dirs", ...); relative ones are taken from the
+    // server's `cwd`, and `~` is our home. No roots means no fence.
+    pub fn path_outside_roots(&self, arguments: &Value, roots: &Roots, cwd: &Path) -> Option<String> {
+        if roots.is_empty() {
+            return None;
+        }
+        find_outside(arguments, false, roots, cwd)
+    }
+}
+
+fn find_outside(value: &Value, path_like: bool, roots: &Roots, cwd: &Path) -> Option<String> {
+    match value {
+        Value::String(text) => {
+            let path = match path_from_uri(text) {
+                Some(path) => path,
+                None if path_like => local_path(text, cwd)?,
+                None => return None,
+            };
+            (!roots.contains(&path)).then(|| text.clone())
+        }
+        Value::Array(items) => items.iter().find_map(|item| find_outside(item, path_like, roots, cwd)),
+        Value::Object(fields) => fields.iter().find_map(|(key, field)| {
+            find_outside(field, is_path_key(key), roots, cwd)
+        }),
+        _ => None,
+    }
+}
+
+// "filePath" -> "path", "source_dirs" -> "dir", "profile" -> "profile"
+fn is_path_key(key: &str) -> bool {
+    // The last word of a snake_case, kebab-case or camelCase name
+    let mut word = String::new();
+    let mut after_lower = false;
+    for c in key.chars() {
+        if c == '_' || c == '-' || (c.is_uppercase() && after_lower) {
+            word.clear();
+        }
+        if c != '_' && c != '-' {
+            word.extend(c.to_lowercase());
+        }
+        after_lower = c.is_lowercase() || c.is_ascii_digit();
+    }
+    let singular = word.strip_suffix('s').unwrap_or(&word);
+    PATH_KEYS.contains(&word.as_str()) || PATH_KEYS.contains(&singular)
+}
+
+// Where a path argument points on our side; None for things that aren't local paths (URLs, "")
+fn local_path(text: &str, cwd: &Path) -> Option<PathBuf> {
+    if text.is_empty() || text.contains("://") {
+        return None;
+    }
+    let home = std::env::var_os("HOME").map(PathBuf::from);
+    let path = match (text.strip_prefix('~'), home) {
+        (Some(""), Some(home)) => home,
+        (Some(rest), Some(home)) if rest.starts_with('/') => home.join(rest.trim_start_matches('/')),
+        _ => cwd.join(text),
+    };
+    Some(path)
 }
\ No newline at end of file
@@ -0,0 +1,138 @@
+// src/supervisor.rs
+// The Watchdog: one task per server that notices when the connection dies,
+// logs why, and brings the server back with exponential backoff. A server
+// that keeps falling over is marked unhealthy and left alone.
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::time::Instant;
+use crate::registry::RegistryInner;
+
+// How long we give a dead server's process to report its exit status
+const EXIT_STATUS_GRACE: Duration = Duration::from_secs(2);
+
+// When to retry and when to give up
+#[derive(Debug, Clone)]
+pub struct RestartPolicy {
+    // Delay before the first restart attempt (doubles after every failure)
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    // More than `max_failures` crashes/failed restarts within `window` = unhealthy
+    pub max_failures: usize,
+    pub window: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            max_failures: 5,
+            window: Duration::from_secs(60),
+        }
+    }
+}
+
+// What the TUI shows next to each server
+#[derive(Debug, Clone, PartialEq)]
+pub enum ServerHealth {
+    Healthy,
+    // Waiting to (re)start; `attempt` counts from 1
+    Restarting { attempt: u32, reason: String },
+    // Gave up; needs a host restart (or a config fix)
+    Unhealthy { reason: String },
+}
+
+impl fmt::Display for ServerHealth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServerHealth::Healthy => write!(f, "healthy"),
+            ServerHealth::Restarting { attempt, reason } => {
+                write!(f, "restarting (attempt {}): {}", attempt, reason)
+            }
+            ServerHealth::Unhealthy { reason } => write!(f, "unhealthy: {}", reason),
+        }
+    }
+}
+
+// Broadcast whenever a server's health changes
+#[derive(Debug, Clone)]
+pub struct HealthEvent {
+    pub server: String,
+    pub health: ServerHealth,
+}
+
+// Sliding window of recent failures
+struct FailureWindow {
+    times: VecDeque<Instant>,
+    window: Duration,
+}
+
+impl FailureWindow {
+    fn record(&mut self) -> usize {
+        let now = Instant::now();
+        self.times.push_back(now);
+        while self.times.front().is_some_and(|t| now.duration_since(*t) > self.window) {
+            self.times.pop_front();
+        }
+        self.times.len()
+    }
+}
+
+// The per-server loop. Runs until the registry shuts down or the server is
+// declared unhealthy.
+pub(crate) async fn supervise(registry: Arc<RegistryInner>, index: usize, policy: RestartPolicy) {
+    let name = registry.servers[index].name.clone();
+    let mut failures = FailureWindow { times: VecDeque::new(), window: policy.window };
+
+    loop {
+        // 1. Wait for the connection to die
+        let client = registry.servers[index].client();
+        let reason = client.wait_closed().await;
+        if registry.is_shutting_down() {
+            return;
+        }
+
+        // 2. Find out how (process servers can tell us their exit status)
+        let status = tokio::time::timeout(EXIT_STATUS_GRACE, client.exit_status()).await.ok().flatten();
+        let _ = client.close().await; // Make sure nothing is left running
+        let reason = match status {
+            Some(status) => format!("exited with {} ({})", status, reason),
+            None => reason,
+        };
+        tracing::warn!("MCP server '{}' went away: {}", name, reason);
+
+        // 3. Bring it back, backing off between attempts
+        let mut backoff = policy.initial_backoff;
+        let mut attempt = 0;
+        let mut last_error = reason;
+        loop {
+            if failures.record() > policy.max_failures {
+                tracing::error!("MCP server '{}' is unhealthy: {}", name, last_error);
+                registry.set_health(index, ServerHealth::Unhealthy { reason: last_error });
+                return;
+            }
+
+            attempt += 1;
+            registry.set_health(index, ServerHealth::Restarting { attempt, reason: last_error.clone() });
+            tokio::time::sleep(backoff).await;
+            if registry.is_shutting_down() {
+                return;
+            }
+
+            match registry.reconnect(index).await {
+                Ok(()) => {
+                    tracing::info!("MCP server '{}' restarted (attempt {})", name, attempt);
+                    registry.set_health(index, ServerHealth::Healthy);
+                    break;
+                }
+                Err(e) => {
+                    last_error = format!("{:#}", e);
+                    tracing::warn!("Restart of '{}' failed: {}", name, last_error);
+                    backoff = (backoff * 2).min(policy.max_backoff);
+                }
+            }
+        }
+    }
+}
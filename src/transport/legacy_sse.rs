@@ -0,0 +1,210 @@
+// src/transport/legacy_sse.rs
+// The older HTTP+SSE transport (MCP 2024-11-05).
+// We hold a GET event stream open for everything the server sends us. The
+// first event on it is `endpoint`, which tells us where to POST our messages.
+// If the stream drops we reconnect and resume with `Last-Event-ID`.
+use std::sync::Mutex as StdMutex;
+use std::time::{Duration, Instant};
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use reqwest::Url;
+use tokio::sync::{mpsc, watch, Mutex};
+use tokio::task::JoinHandle;
+use super::sse::SseParser;
+use super::Transport;
+
+// How long connect() waits for the server to announce its POST endpoint
+// (connect_with_timeout picks its own)
+const ENDPOINT_TIMEOUT: Duration = Duration::from_secs(10);
+// Reconnect backoff: start here, double each failure, never exceed the cap
+const INITIAL_RETRY: Duration = Duration::from_millis(500);
+const MAX_RETRY: Duration = Duration::from_secs(30);
+// Give up after this many consecutive failed reconnects
+const MAX_RECONNECTS: u32 = 8;
+// A stream that delivers no message and ends sooner than this counts as a
+// failed reconnect, so a server that hangs up right away gets backed off
+const STABLE_STREAM: Duration = Duration::from_secs(5);
+
+pub struct LegacySseTransport {
+    client: reqwest::Client,
+    // Where to POST (None until the server sends its `endpoint` event)
+    endpoint: watch::Receiver<Option<Url>>,
+    inbox_rx: Mutex<mpsc::UnboundedReceiver<String>>,
+    // The GET stream reader (owns the inbox sender)
+    reader: StdMutex<Option<JoinHandle<()>>>,
+}
+
+impl LegacySseTransport {
+    // 1. Open the event stream and wait for the `endpoint` event
+    pub async fn connect(sse_url: &str) -> Result<Self> {
+        Self::connect_with_timeout(sse_url, ENDPOINT_TIMEOUT).await
+    }
+
+    // Like `connect`, but give up on the `endpoint` event after `endpoint_timeout`
+    pub async fn connect_with_timeout(sse_url: &str, endpoint_timeout: Duration) -> Result<Self> {
+        let sse_url = Url::parse(sse_url).context(format!("Invalid SSE URL: {}", sse_url))?;
+        let client = reqwest::Client::new();
+
+        let (endpoint_tx, mut endpoint_rx) = watch::channel(None);
+        let (inbox_tx, inbox_rx) = mpsc::unbounded_channel();
+
+        let reader = tokio::spawn(read_stream(client.clone(), sse_url.clone(), endpoint_tx, inbox_tx));
+
+        // The reader drops the watch sender if it gives up, which ends the wait early
+        let wait = async { endpoint_rx.wait_for(|e| e.is_some()).await.map(|_| ()) };
+        let announced = tokio::time::timeout(endpoint_timeout, wait).await;
+        match announced {
+            Ok(Ok(_)) => {}
+            Ok(Err(_)) => {
+                return Err(anyhow!("SSE stream at {} closed before announcing an endpoint", sse_url));
+            }
+            Err(_) => {
+                reader.abort();
+                return Err(anyhow!("Timed out waiting for endpoint event from {}", sse_url));
+            }
+        }
+
+        Ok(Self {
+            client,
+            endpoint: endpoint_rx,
+            inbox_rx: Mutex::new(inbox_rx),
+            reader: StdMutex::new(Some(reader)),
+        })
+    }
+
+    // The URL we're currently POSTing to
+    pub fn endpoint(&self) -> Option<Url> {
+        self.endpoint.borrow().clone()
+    }
+}
+
+// The background loop: read events, reconnect when the stream drops
+async fn read_stream(
+    client: reqwest::Client,
+    sse_url: Url,
+    endpoint_tx: watch::Sender<Option<Url>>,
+    inbox: mpsc::UnboundedSender<String>,
+) {
+    let mut last_event_id: Option<String> = None;
+    let mut retry = INITIAL_RETRY;
+    let mut failures = 0;
+
+    loop {
+        // A. (Re)open the stream, resuming where we left off
+        let mut request = client.get(sse_url.clone()).header(ACCEPT, "text/event-stream");
+        if let Some(id) = &last_event_id {
+            request = request.header("Last-Event-ID", id);
+        }
+
+        match request.send().await {
+            Ok(mut res) if res.status().is_success() => {
+                let opened = Instant::now();
+                let mut delivered = false;
+                let mut advised = None;
+
+                // B. Pump events until the stream ends
+                let mut parser = SseParser::new();
+                while let Ok(Some(chunk)) = res.chunk().await {
+                    for event in parser.feed(&chunk) {
+                        if let Some(id) = &event.id {
+                            last_event_id = Some(id.clone());
+                        }
+                        if let Some(ms) = event.retry {
+                            advised = Some(Duration::from_millis(ms));
+                        }
+
+                        match event.event_type() {
+                            "endpoint" => match sse_url.join(event.data.trim()) {
+                                Ok(url) => {
+                                    let _ = endpoint_tx.send(Some(url));
+                                }
+                                Err(e) => tracing::warn!("Bad endpoint from {}: {}", sse_url, e),
+                            },
+                            "message" if !event.data.is_empty() => {
+                                // Nobody is listening anymore
+                                let Ok(()) = inbox.send(event.data) else { return };
+                                delivered = true;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                match delivered || opened.elapsed() >= STABLE_STREAM {
+                    true => {
+                        failures = 0;
+                        retry = INITIAL_RETRY;
+                    }
+                    false => failures += 1,
+                }
+                if let Some(advised) = advised {
+                    retry = advised;
+                }
+                tracing::warn!("SSE stream from {} dropped, reconnecting", sse_url);
+            }
+            Ok(res) => {
+                failures += 1;
+                tracing::warn!("SSE connect to {} failed: HTTP {}", sse_url, res.status());
+            }
+            Err(e) => {
+                failures += 1;
+                tracing::warn!("SSE connect to {} failed: {}", sse_url, e);
+            }
+        }
+
+        // C. Back off before trying again
+        if failures >= MAX_RECONNECTS {
+            tracing::error!("Giving up on SSE stream {} after {} attempts", sse_url, failures);
+            return; // Dropping `inbox` makes receive() fail
+        }
+        tokio::time::sleep(retry).await;
+        if failures > 0 {
+            retry = (retry * 2).min(MAX_RETRY);
+        }
+    }
+}
+
+// A transport dropped without close() (say, on an error path) must not keep reconnecting
+impl Drop for LegacySseTransport {
+    fn drop(&mut self) {
+        if let Some(reader) = self.reader.get_mut().unwrap().take() {
+            reader.abort();
+        }
+    }
+}
+
+#[async_trait]
+impl Transport for LegacySseTransport {
+    // 2. POST to the announced endpoint; the reply arrives on the stream
+    async fn send(&self, message: &str) -> Result<()> {
+        let endpoint = self.endpoint().ok_or(anyhow!("SSE server never announced an endpoint"))?;
+
+        let res = self.client.post(endpoint.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(message.to_string())
+            .send()
+            .await
+            .context(format!("Failed to POST to {}", endpoint))?;
+
+        if !res.status().is_success() {
+            let status = res.status();
+            let error_text = res.text().await.unwrap_or_default();
+            return Err(anyhow!("MCP HTTP Error {}: {}", status, error_text));
+        }
+        Ok(())
+    }
+
+    // 3. Pop the next message from the event stream
+    async fn receive(&self) -> Result<String> {
+        self.inbox_rx.lock().await.recv().await
+            .ok_or(anyhow!("SSE stream is closed"))
+    }
+
+    // 4. Stop the reader (which drops the stream and the inbox)
+    async fn close(&self) -> Result<()> {
+        if let Some(reader) = self.reader.lock().unwrap().take() {
+            reader.abort();
+        }
+        Ok(())
+    }
+}
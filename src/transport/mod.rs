@@ -0,0 +1,52 @@
+// src/transport/mod.rs
+use std::process::ExitStatus;
+use std::time::Duration;
+use anyhow::Result;
+use async_trait::async_trait;
+
+pub mod legacy_sse;
+pub mod sse;
+pub mod streamable_http;
+
+pub use legacy_sse::LegacySseTransport;
+pub use streamable_http::StreamableHttpTransport;
+
+// The Wire Abstraction
+// Anything that can carry JSON-RPC messages to an MCP server and bring the
+// replies back. `McpClient` only ever talks to this trait, so a child process,
+// an HTTP endpoint or a long-lived daemon all look the same to it.
+//
+// Methods take &self so a transport can be shared between a writer and a
+// reader; implementations handle their own locking.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    // 1. Send ONE serialized JSON-RPC message (no trailing newline)
+    async fn send(&self, message: &str) -> Result<()>;
+
+    // 2. Wait for the NEXT serialized JSON-RPC message from the server
+    async fn receive(&self) -> Result<String>;
+
+    // 3. Tear the connection down (idempotent)
+    async fn close(&self) -> Result<()>;
+
+    // 4. How the other side went away, if the transport can tell.
+    // Process transports wait for the child's exit status; others return None.
+    async fn exit_status(&self) -> Option<ExitStatus> {
+        None
+    }
+
+    // 5. The last few lines of diagnostics the other side printed, if any
+    async fn recent_stderr(&self, _lines: usize) -> Vec<String> {
+        Vec::new()
+    }
+
+    // 6. Tear it down politely, giving the other side `grace` to wrap up.
+    // Only process transports have anything to wait for.
+    async fn shutdown(&self, _grace: Duration) -> Result<()> {
+        self.close().await
+    }
+
+    // 7. The protocol revision the handshake settled on.
+    // Only HTTP transports need it (they repeat it in a header).
+    fn set_protocol_version(&self, _version: &str) {}
+}
@@ -0,0 +1,115 @@
+// src/transport/sse.rs
+// A tiny incremental parser for `text/event-stream` bodies.
+// We feed it raw chunks as they arrive off the socket and it hands back
+// every event that has been fully dispatched (terminated by a blank line).
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SseEvent {
+    // The "event:" field. None means the default type ("message").
+    pub event: Option<String>,
+    // All "data:" lines joined with '\n'
+    pub data: String,
+    // The "id:" field, used for Last-Event-ID resumption
+    pub id: Option<String>,
+    // The "retry:" field (milliseconds)
+    pub retry: Option<u64>,
+}
+
+impl SseEvent {
+    // The spec says a missing event type means "message"
+    pub fn event_type(&self) -> &str {
+        self.event.as_deref().unwrap_or("message")
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SseParser {
+    // Bytes we haven't seen a line ending for yet
+    buffer: Vec<u8>,
+    // The event being assembled
+    current: SseEvent,
+    // Whether any field has touched `current` since the last dispatch
+    dirty: bool,
+    // The last byte was '\r', so a following '\n' belongs to the same CRLF
+    skip_lf: bool,
+}
+
+impl SseParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 1. Feed a chunk, get back every event it completed
+    // Chunks can split lines (and CRLF pairs) anywhere; state carries over.
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        let mut events = Vec::new();
+
+        for &b in chunk {
+            let skip_lf = std::mem::take(&mut self.skip_lf);
+            match b {
+                b'\n' if skip_lf => {}
+                b'\n' => self.end_line(&mut events),
+                b'\r' => {
+                    self.end_line(&mut events);
+                    self.skip_lf = true;
+                }
+                _ => self.buffer.push(b),
+            }
+        }
+
+        events
+    }
+
+    // 2. Process one complete line
+    fn end_line(&mut self, events: &mut Vec<SseEvent>) {
+        let line = String::from_utf8_lossy(&self.buffer).into_owned();
+        self.buffer.clear();
+
+        // Blank line = dispatch
+        if line.is_empty() {
+            if self.dirty {
+                let mut event = std::mem::take(&mut self.current);
+                if event.data.ends_with('\n') {
+                    event.data.pop();
+                }
+                events.push(event);
+                self.dirty = false;
+            }
+            return;
+        }
+
+        // Comment line (often used as a keep-alive)
+        if line.starts_with(':') {
+            return;
+        }
+
+        let (field, value) = match line.split_once(':') {
+            Some((f, v)) => (f, v.strip_prefix(' ').unwrap_or(v)),
+            None => (line.as_str(), ""),
+        };
+
+        match field {
+            "event" => {
+                self.current.event = Some(value.to_string());
+                self.dirty = true;
+            }
+            "data" => {
+                self.current.data.push_str(value);
+                self.current.data.push('\n');
+                self.dirty = true;
+            }
+            // Ids containing NUL are ignored per spec
+            "id" if !value.contains('\0') => {
+                self.current.id = Some(value.to_string());
+                self.dirty = true;
+            }
+            "retry" => {
+                if let Ok(ms) = value.parse() {
+                    self.current.retry = Some(ms);
+                    self.dirty = true;
+                }
+            }
+            _ => {} // Unknown fields are ignored
+        }
+    }
+}
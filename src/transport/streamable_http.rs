@@ -0,0 +1,181 @@
+// src/transport/streamable_http.rs
+// The "Streamable HTTP" transport (MCP 2025-03-26 and later).
+// Every message is POSTed to ONE endpoint. The server answers with either a
+// plain JSON body or an SSE stream that carries the response (and possibly
+// notifications/requests that precede it).
+use std::sync::Mutex as StdMutex;
+use anyhow::{anyhow, Context, Result};
+use async_trait::async_trait;
+use reqwest::header::{ACCEPT, CONTENT_TYPE};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+use super::sse::SseParser;
+use super::Transport;
+
+// The header servers use to pin us to a session
+pub const SESSION_HEADER: &str = "Mcp-Session-Id";
+
+// The negotiated revision, on every request after the handshake (2025-06-18)
+pub const PROTOCOL_VERSION_HEADER: &str = "MCP-Protocol-Version";
+
+pub struct StreamableHttpTransport {
+    endpoint: String,
+    client: reqwest::Client,
+    // Assigned by the server on the initialize response (if it uses sessions)
+    session_id: StdMutex<Option<String>>,
+    // Set once the handshake is done
+    protocol_version: StdMutex<Option<String>>,
+    // Everything the server sends us ends up in this queue, in arrival order.
+    // None once the transport is closed.
+    inbox_tx: StdMutex<Option<mpsc::UnboundedSender<String>>>,
+    inbox_rx: Mutex<mpsc::UnboundedReceiver<String>>,
+    // Background readers for SSE response bodies
+    streams: StdMutex<Vec<JoinHandle<()>>>,
+}
+
+impl StreamableHttpTransport {
+    pub fn new(endpoint: &str) -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            endpoint: endpoint.to_string(),
+            client: reqwest::Client::new(),
+            session_id: StdMutex::new(None),
+            protocol_version: StdMutex::new(None),
+            inbox_tx: StdMutex::new(Some(tx)),
+            inbox_rx: Mutex::new(rx),
+            streams: StdMutex::new(Vec::new()),
+        }
+    }
+
+    // The session the server assigned us, if any
+    pub fn session_id(&self) -> Option<String> {
+        self.session_id.lock().unwrap().clone()
+    }
+
+    fn inbox(&self) -> Result<mpsc::UnboundedSender<String>> {
+        self.inbox_tx.lock().unwrap().clone().ok_or(anyhow!("HTTP transport is closed"))
+    }
+}
+
+// A JSON body can hold a single message or a batch (array) of them.
+// We flatten batches so the client always sees one message per receive().
+fn push_json_body(inbox: &mpsc::UnboundedSender<String>, body: &str) -> Result<()> {
+    let value: serde_json::Value = serde_json::from_str(body)
+        .context("Server sent invalid JSON")?;
+
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                let _ = inbox.send(item.to_string());
+            }
+        }
+        single => {
+            let _ = inbox.send(single.to_string());
+        }
+    }
+    Ok(())
+}
+
+#[async_trait]
+impl Transport for StreamableHttpTransport {
+    // 1. POST one message
+    async fn send(&self, message: &str) -> Result<()> {
+        let inbox = self.inbox()?;
+
+        let mut request = self.client.post(&self.endpoint)
+            .header(ACCEPT, "application/json, text/event-stream")
+            .header(CONTENT_TYPE, "application/json")
+            .body(message.to_string());
+
+        if let Some(session) = self.session_id() {
+            request = request.header(SESSION_HEADER, session);
+        }
+        if let Some(version) = self.protocol_version.lock().unwrap().clone() {
+            request = request.header(PROTOCOL_VERSION_HEADER, version);
+        }
+
+        let res = request.send().await
+            .context(format!("Failed to POST to MCP endpoint {}", self.endpoint))?;
+
+        // A. Remember the session the server handed out
+        if let Some(session) = res.headers().get(SESSION_HEADER) {
+            let session = session.to_str().context("Invalid Mcp-Session-Id header")?;
+            *self.session_id.lock().unwrap() = Some(session.to_string());
+        }
+
+        // B. Check the status
+        // 202 Accepted is the normal reply to notifications and responses
+        if res.status() == reqwest::StatusCode::ACCEPTED {
+            return Ok(());
+        }
+        if res.status() == reqwest::StatusCode::NOT_FOUND && self.session_id().is_some() {
+            return Err(anyhow!("MCP session expired (server returned 404)"));
+        }
+        if !res.status().is_success() {
+            let status = res.status();
+            let error_text = res.text().await.unwrap_or_default();
+            return Err(anyhow!("MCP HTTP Error {}: {}", status, error_text));
+        }
+
+        // C. Route the body by content type
+        let content_type = res.headers().get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_ascii_lowercase();
+
+        if content_type.starts_with("text/event-stream") {
+            // The stream may stay open for a long time, so read it in the background
+            let handle = tokio::spawn(async move {
+                let mut res = res;
+                let mut parser = SseParser::new();
+                while let Ok(Some(chunk)) = res.chunk().await {
+                    for event in parser.feed(&chunk) {
+                        if event.event_type() == "message" && !event.data.is_empty() {
+                            let _ = inbox.send(event.data);
+                        }
+                    }
+                }
+            });
+            let mut streams = self.streams.lock().unwrap();
+            streams.retain(|h| !h.is_finished());
+            streams.push(handle);
+            Ok(())
+        } else {
+            let body = res.text().await?;
+            if body.trim().is_empty() {
+                return Ok(());
+            }
+            push_json_body(&inbox, &body)
+        }
+    }
+
+    // 2. Pop the next message the server sent us
+    async fn receive(&self) -> Result<String> {
+        self.inbox_rx.lock().await.recv().await
+            .ok_or(anyhow!("HTTP transport is closed"))
+    }
+
+    // 3. Drop the readers and tell the server we're done with the session
+    async fn close(&self) -> Result<()> {
+        self.inbox_tx.lock().unwrap().take();
+        for handle in self.streams.lock().unwrap().drain(..) {
+            handle.abort();
+        }
+
+        let session = self.session_id.lock().unwrap().take();
+        if let Some(session) = session {
+            // Servers MAY refuse (405); either way we're finished
+            let mut request = self.client.delete(&self.endpoint).header(SESSION_HEADER, session);
+            if let Some(version) = self.protocol_version.lock().unwrap().clone() {
+                request = request.header(PROTOCOL_VERSION_HEADER, version);
+            }
+            let _ = request.send().await;
+        }
+        Ok(())
+    }
+
+    // 7. Repeat the negotiated revision on every later request
+    fn set_protocol_version(&self, version: &str) {
+        *self.protocol_version.lock().unwrap() = Some(version.to_string());
+    }
+}
@@ -1,7 +1,8 @@
 // src/tui.rs
+use std::collections::{BTreeMap, HashMap, VecDeque};
 use std::io;
 use crossterm::{
-    event::{self, Event, KeyCode, KeyEventKind},
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -10,11 +11,15 @@ use ratatui::{
     layout::{Constraint, Direction, Layout},
     style::{Color, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph, Wrap},
+    widgets::{Block, Borders, LineGauge, Paragraph, Wrap},
     Terminal,
 };
 use tokio::sync::mpsc;
 use anyhow::Result;
+use crate::protocol::{ProgressNotification, Prompt};
+use crate::registry::{ServerPrompts, ServerResources};
+use crate::sampling::Approval;
+use crate::supervisor::ServerHealth;
 
 // --- 1. THE MESSAGE TYPES ---
 // These are the signals sent from the Brain to the Face
@@ -24,6 +29,119 @@ pub enum UiMessage {
     Ai(String),        // AI replied
     Log(String),       // System event (tool call, security check)
     Error(String),     // Something broke
+    ServerHealth { server: String, health: ServerHealth }, // A server crashed/restarted
+    Resources(Vec<ServerResources>), // Answer to AgentCommand::ListResources
+    Prompts(Vec<ServerPrompts>),     // Answer to AgentCommand::ListPrompts
+    Approval(Approval),              // A server wants to use the LLM; y/n
+    ToolStarted { id: String, tool: String }, // A tool call went out
+    ToolProgress { id: String, progress: ProgressNotification }, // ...reported progress
+    ToolFinished { id: String },              // ...and returned (or failed)
+    Quit,              // The host was told to stop (SIGTERM etc.)
+}
+
+// ...and these go the other way, from the Face to the Brain
+#[derive(Debug, Clone)]
+pub enum AgentCommand {
+    Prompt(String),                                  // User typed something
+    ListResources,                                   // Fill the resource browser
+    AttachResource { server: String, uri: String },  // Put a resource into the context
+    ListPrompts,                                     // Fill the "/" menu
+    RunPrompt { server: String, name: String, arguments: HashMap<String, String> },
+    ListRoots,                                       // "/roots"
+    AddRoot(String),                                 // "/roots add <dir>"
+    RemoveRoot(String),                              // "/roots remove <dir>"
+    SetLogLevel { server: String, level: String },   // "/loglevel <server> <level>"
+}
+
+// Host commands that live in the "/" menu next to the servers' prompts
+const BUILTINS: &[(&str, &str)] = &[
+    ("roots", "/roots [add|remove <dir>] - directories servers may work in"),
+    ("loglevel", "/loglevel <server> <level> - how much a server logs (debug ... emergency)"),
+];
+
+// Typed input -> host command, if it is one
+pub fn builtin_command(input: &str) -> Option<AgentCommand> {
+    let mut words = input.trim().splitn(3, ' ');
+    let command = match (words.next()?, words.next(), words.next().map(str::trim)) {
+        ("/roots", None, _) => AgentCommand::ListRoots,
+        ("/roots", Some("add"), Some(dir)) if !dir.is_empty() => AgentCommand::AddRoot(dir.to_string()),
+        ("/roots", Some("remove"), Some(dir)) if !dir.is_empty() => AgentCommand::RemoveRoot(dir.to_string()),
+        ("/loglevel", Some(server), Some(level)) if !level.is_empty() => AgentCommand::SetLogLevel {
+            server: server.to_string(),
+            level: level.to_string(),
+        },
+        _ => return None,
+    };
+    Some(command)
+}
+
+// A prompt picked from the "/" menu whose arguments we're still asking for
+pub struct PromptForm {
+    pub server: String,
+    pub prompt: Prompt,
+    pub values: HashMap<String, String>,
+    // Index of the argument the input box is asking for
+    pub next: usize,
+}
+
+// The resource browser: one row per resource, grouped by server
+pub struct ResourceBrowser {
+    pub servers: Vec<ServerResources>,
+    // Index into the flattened (server, resource) rows
+    pub selected: usize,
+}
+
+impl ResourceBrowser {
+    pub fn new(servers: Vec<ServerResources>) -> Self {
+        Self { servers, selected: 0 }
+    }
+
+    // (server, uri) of every row, in display order
+    fn rows(&self) -> Vec<(&str, &str)> {
+        self.servers.iter()
+            .flat_map(|s| s.resources.iter().map(move |r| (s.server.as_str(), r.uri.as_str())))
+            .collect()
+    }
+
+    pub fn up(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn down(&mut self) {
+        let rows = self.rows().len();
+        if self.selected + 1 < rows {
+            self.selected += 1;
+        }
+    }
+
+    pub fn current(&self) -> Option<(String, String)> {
+        self.rows().get(self.selected).map(|(server, uri)| (server.to_string(), uri.to_string()))
+    }
+}
+
+// A tool call that hasn't returned yet
+#[derive(Debug, Clone)]
+pub struct ToolActivity {
+    // The LLM's tool call id
+    pub id: String,
+    pub tool: String,
+    // The latest update, if the server sends any
+    pub progress: Option<ProgressNotification>,
+}
+
+impl ToolActivity {
+    // "read_file: 3/10 Indexing..." (just the name until the server reports something)
+    pub fn label(&self) -> String {
+        let Some(progress) = &self.progress else { return self.tool.clone() };
+        let amount = match progress.total {
+            Some(total) => format!("{}/{}", progress.progress, total),
+            None => progress.progress.to_string(),
+        };
+        match &progress.message {
+            Some(message) => format!("{}: {} {}", self.tool, amount, message),
+            None => format!("{}: {}", self.tool, amount),
+        }
+    }
 }
 
 // --- 2. APP STATE ---
@@ -31,36 +149,210 @@ pub struct App {
     pub input: String,
     pub chat_history: Vec<UiMessage>, // Structured history
     pub logs: Vec<String>,
+    // Latest known health of every MCP server we've heard about
+    pub server_health: BTreeMap<String, ServerHealth>,
     pub should_quit: bool,
+    // Open while the user browses resources (Ctrl+R)
+    pub browser: Option<ResourceBrowser>,
+    // Everything the "/" menu can offer (refreshed whenever it opens)
+    pub prompts: Vec<ServerPrompts>,
+    // Highlighted row of the "/" menu
+    pub menu_selected: usize,
+    // Set while the input box collects a prompt's arguments
+    pub prompt_form: Option<PromptForm>,
+    // Sampling requests waiting for a yes or no, oldest first
+    pub approvals: VecDeque<Approval>,
+    // Tool calls in flight, oldest first
+    pub tools_running: Vec<ToolActivity>,
     // The mailbox to send user input TO the brain
-    pub tx_agent: mpsc::UnboundedSender<String>, 
+    pub tx_agent: mpsc::UnboundedSender<AgentCommand>,
+    // The "stop button" for whatever tool is running right now
+    pub tx_abort: mpsc::UnboundedSender<()>,
 }
 
 impl App {
-    pub fn new(tx_agent: mpsc::UnboundedSender<String>) -> Self {
+    pub fn new(tx_agent: mpsc::UnboundedSender<AgentCommand>, tx_abort: mpsc::UnboundedSender<()>) -> Self {
         Self {
             input: String::new(),
             chat_history: Vec::new(),
             logs: Vec::new(),
+            server_health: BTreeMap::new(),
+            browser: None,
+            prompts: Vec::new(),
+            menu_selected: 0,
+            prompt_form: None,
+            approvals: VecDeque::new(),
+            tools_running: Vec::new(),
             should_quit: false,
             tx_agent,
+            tx_abort,
         }
     }
 
     pub fn on_key(&mut self, c: char) {
         self.input.push(c);
+        self.menu_selected = 0;
+        // A fresh "/" opens the menu; ask for the current prompt list
+        if self.input == "/" && self.prompt_form.is_none() {
+            let _ = self.tx_agent.send(AgentCommand::ListPrompts);
+        }
+    }
+
+    pub fn on_backspace(&mut self) {
+        self.input.pop();
+        self.menu_selected = 0;
+    }
+
+    pub fn on_up(&mut self) {
+        self.menu_selected = self.menu_selected.saturating_sub(1);
+    }
+
+    pub fn on_down(&mut self) {
+        if self.menu_selected + 1 < self.menu_matches().len() {
+            self.menu_selected += 1;
+        }
+    }
+
+    // Esc backs out of the "/" menu or an argument form first, and quits otherwise
+    pub fn on_escape(&mut self) {
+        if self.prompt_form.take().is_some() || self.menu_open() {
+            self.input.clear();
+        } else {
+            self.should_quit = true;
+        }
+    }
+
+    // The "/" menu shows while the input starts with "/"
+    pub fn menu_open(&self) -> bool {
+        self.prompt_form.is_none() && self.input.starts_with('/')
+    }
+
+    // (server, prompt) pairs whose name starts with what follows the "/"
+    pub fn menu_matches(&self) -> Vec<(&str, &Prompt)> {
+        let typed = self.input.trim_start_matches('/').trim();
+        self.prompts.iter()
+            .flat_map(|s| s.prompts.iter().map(move |p| (s.server.as_str(), p)))
+            .filter(|(_, prompt)| prompt.name.starts_with(typed))
+            .collect()
     }
 
     pub fn on_enter(&mut self) {
+        if self.prompt_form.is_some() {
+            return self.on_prompt_argument();
+        }
+        if let Some(command) = builtin_command(&self.input) {
+            self.input.clear();
+            let _ = self.tx_agent.send(command);
+            return;
+        }
+        if self.menu_open() {
+            return self.on_pick_prompt();
+        }
         if !self.input.trim().is_empty() {
             // 1. Show it in UI immediately
             self.chat_history.push(UiMessage::User(self.input.clone()));
             // 2. Send it to the Brain
-            let _ = self.tx_agent.send(self.input.clone());
+            let _ = self.tx_agent.send(AgentCommand::Prompt(self.input.clone()));
             // 3. Clear input
             self.input.clear();
         }
     }
+
+    pub fn on_abort(&mut self) {
+        self.logs.push("Abort requested.".to_string());
+        let _ = self.tx_abort.send(());
+    }
+
+    // Enter in the "/" menu: run the highlighted prompt, or start asking for its arguments
+    fn on_pick_prompt(&mut self) {
+        let picked = self.menu_matches().get(self.menu_selected)
+            .map(|(server, prompt)| (server.to_string(), (*prompt).clone()));
+        let Some((server, prompt)) = picked else {
+            self.logs.push(format!("No prompt matches '{}'", self.input));
+            return;
+        };
+        self.input.clear();
+
+        let form = PromptForm { server, prompt, values: HashMap::new(), next: 0 };
+        match form.prompt.arguments.is_empty() {
+            true => self.run_prompt(form),
+            false => self.prompt_form = Some(form),
+        }
+    }
+
+    // Enter while filling in a prompt: the input is the current argument's value
+    fn on_prompt_argument(&mut self) {
+        let Some(mut form) = self.prompt_form.take() else { return };
+        let argument = &form.prompt.arguments[form.next];
+        let value = self.input.trim().to_string();
+
+        if value.is_empty() && argument.required {
+            self.logs.push(format!("'{}' is required", argument.name));
+            self.prompt_form = Some(form);
+            return;
+        }
+        if !value.is_empty() {
+            form.values.insert(argument.name.clone(), value);
+        }
+        self.input.clear();
+        form.next += 1;
+
+        match form.next == form.prompt.arguments.len() {
+            true => self.run_prompt(form),
+            false => self.prompt_form = Some(form),
+        }
+    }
+
+    fn run_prompt(&mut self, form: PromptForm) {
+        let mut shown = format!("/{}", form.prompt.name);
+        for argument in &form.prompt.arguments {
+            if let Some(value) = form.values.get(&argument.name) {
+                shown.push_str(&format!(" {}={}", argument.name, value));
+            }
+        }
+        self.chat_history.push(UiMessage::User(shown));
+        let _ = self.tx_agent.send(AgentCommand::RunPrompt {
+            server: form.server,
+            name: form.prompt.name,
+            arguments: form.values,
+        });
+    }
+
+    // Answer the oldest pending sampling request
+    pub fn on_approval(&mut self, approved: bool) {
+        if let Some(approval) = self.approvals.pop_front() {
+            let verdict = if approved { "approved" } else { "denied" };
+            self.logs.push(format!("Sampling for '{}' {}", approval.server, verdict));
+            approval.answer(approved);
+        }
+    }
+
+    pub fn on_tool_started(&mut self, id: String, tool: String) {
+        self.tools_running.push(ToolActivity { id, tool, progress: None });
+    }
+
+    // Updates that arrive after the call returned are dropped
+    pub fn on_tool_progress(&mut self, id: &str, progress: ProgressNotification) {
+        if let Some(activity) = self.tools_running.iter_mut().find(|a| a.id == id) {
+            activity.progress = Some(progress);
+        }
+    }
+
+    pub fn on_tool_finished(&mut self, id: &str) {
+        self.tools_running.retain(|a| a.id != id);
+    }
+
+    // Ask the brain for the resource list; the browser opens when it arrives
+    pub fn on_browse(&mut self) {
+        let _ = self.tx_agent.send(AgentCommand::ListResources);
+    }
+
+    // Attach whatever is selected in the browser, and close it
+    pub fn on_attach(&mut self) {
+        if let Some((server, uri)) = self.browser.take().and_then(|b| b.current()) {
+            let _ = self.tx_agent.send(AgentCommand::AttachResource { server, uri });
+        }
+    }
 }
 
 // --- 3. THE MAIN LOOP ---
@@ -85,6 +377,26 @@ pub async fn run_tui(mut app: App, mut rx_ui: mpsc::UnboundedReceiver<UiMessage>
                     app.logs.push(format!("ERROR: {}", text));
                     app.chat_history.push(UiMessage::Error(text));
                 }
+                UiMessage::ServerHealth { server, health } => {
+                    app.logs.push(format!("SERVER {}: {}", server, health));
+                    app.server_health.insert(server, health);
+                }
+                UiMessage::Resources(servers) => {
+                    if servers.is_empty() {
+                        app.logs.push("No connected server offers resources.".to_string());
+                    } else {
+                        app.browser = Some(ResourceBrowser::new(servers));
+                    }
+                }
+                UiMessage::Prompts(prompts) => app.prompts = prompts,
+                UiMessage::Approval(approval) => {
+                    app.logs.push(format!("SAMPLING REQUEST from '{}': {}", approval.server, approval.summary));
+                    app.approvals.push_back(approval);
+                }
+                UiMessage::ToolStarted { id, tool } => app.on_tool_started(id, tool),
+                UiMessage::ToolProgress { id, progress } => app.on_tool_progress(&id, progress),
+                UiMessage::ToolFinished { id } => app.on_tool_finished(&id),
+                UiMessage::Quit => app.should_quit = true,
                 other => app.chat_history.push(other),
             }
         }
@@ -94,14 +406,38 @@ pub async fn run_tui(mut app: App, mut rx_ui: mpsc::UnboundedReceiver<UiMessage>
         if event::poll(std::time::Duration::from_millis(50))? {
             if let Event::Key(key) = event::read()? {
                 if key.kind == KeyEventKind::Press {
+                // A pending approval takes the keys first, then the browser
+                if !app.approvals.is_empty() {
+                    match key.code {
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.should_quit = true,
+                        KeyCode::Char('y') | KeyCode::Char('Y') => app.on_approval(true),
+                        KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => app.on_approval(false),
+                        _ => {}
+                    }
+                } else if let Some(browser) = app.browser.as_mut() {
+                    match key.code {
+                        KeyCode::Esc => app.browser = None,
+                        KeyCode::Up => browser.up(),
+                        KeyCode::Down => browser.down(),
+                        KeyCode::Enter => app.on_attach(),
+                        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.should_quit = true,
+                        _ => {}
+                    }
+                } else {
                 match key.code {
-                    KeyCode::Esc => app.should_quit = true,
+                    KeyCode::Esc => app.on_escape(),
+                    // Raw mode swallows SIGINT, so Ctrl+C arrives as a key
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => app.should_quit = true,
                     KeyCode::Enter => app.on_enter(),
+                    KeyCode::Up => app.on_up(),
+                    KeyCode::Down => app.on_down(),
+                    KeyCode::Char('x') if key.modifiers.contains(KeyModifiers::CONTROL) => app.on_abort(),
+                    KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => app.on_browse(),
                     KeyCode::Char(c) => app.on_key(c),
-                    KeyCode::Backspace => { app.input.pop(); }
+                    KeyCode::Backspace => app.on_backspace(),
                     _ => {}
                     }
-                
+                }
                 }
             }
         }
@@ -119,10 +455,15 @@ pub async fn run_tui(mut app: App, mut rx_ui: mpsc::UnboundedReceiver<UiMessage>
 
 // --- 4. THE RENDERER (Making it pretty) ---
 fn ui_builder(f: &mut ratatui::Frame, app: &App) {
+    // The in-flight panel only takes room while something runs
+    let running = match app.tools_running.len() {
+        0 => 0,
+        n => n as u16 + 2,
+    };
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
-        .constraints([Constraint::Min(1), Constraint::Length(3)].as_ref())
+        .constraints([Constraint::Min(1), Constraint::Length(running), Constraint::Length(3)].as_ref())
         .split(f.size());
 
     let top_chunks = Layout::default()
@@ -143,21 +484,147 @@ fn ui_builder(f: &mut ratatui::Frame, app: &App) {
     let chat_block = Paragraph::new(messages)
         .block(Block::default().borders(Borders::ALL).title(" AETHER TERMINAL "))
         .wrap(Wrap { trim: true });
-    f.render_widget(chat_block, top_chunks[0]);
+    match &app.browser {
+        Some(browser) => f.render_widget(browser_widget(browser), top_chunks[0]),
+        None if app.menu_open() => {
+            // The "/" menu sits under the chat
+            let matches = app.menu_matches();
+            let builtins = matching_builtins(&app.input);
+            let height = ((matches.len() + builtins.len()).max(1) as u16 + 2).min(top_chunks[0].height / 2);
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(1), Constraint::Length(height)].as_ref())
+                .split(top_chunks[0]);
+            f.render_widget(chat_block, parts[0]);
+            f.render_widget(menu_widget(&matches, &builtins, app.menu_selected), parts[1]);
+        }
+        None => f.render_widget(chat_block, top_chunks[0]),
+    }
 
-    // WIDGET 2: LOGS
-    let log_lines: Vec<Line> = app.logs.iter().rev() // Show newest at top
+    // WIDGET 2: LOGS (server health pinned on top, only once something happened)
+    let mut log_lines: Vec<Line> = app.server_health.iter().map(|(server, health)| {
+        let color = match health {
+            ServerHealth::Healthy => Color::Green,
+            ServerHealth::Restarting { .. } => Color::Yellow,
+            ServerHealth::Unhealthy { .. } => Color::Red,
+        };
+        Line::from(Span::styled(format!("[{}] {}", server, health), Style::default().fg(color)))
+    }).collect();
+    log_lines.extend(app.logs.iter().rev() // Show newest at top
         .take(20) // Only last 20 logs
-        .map(|s| Line::from(Span::styled(s, Style::default().fg(Color::DarkGray))))
-        .collect();
+        .map(|s| Line::from(Span::styled(s.as_str(), Style::default().fg(Color::DarkGray)))));
     
     let logs_block = Paragraph::new(log_lines)
         .block(Block::default().borders(Borders::ALL).title(" SYSTEM CORE "));
     f.render_widget(logs_block, top_chunks[1]);
 
-    // WIDGET 3: INPUT
+    // WIDGET 2b: TOOLS IN FLIGHT (one gauge per call)
+    if !app.tools_running.is_empty() {
+        let panel = Block::default().borders(Borders::ALL).title(" RUNNING TOOLS (Ctrl+X to Abort) ");
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![Constraint::Length(1); app.tools_running.len()])
+            .split(panel.inner(chunks[1]));
+        f.render_widget(panel, chunks[1]);
+        for (activity, row) in app.tools_running.iter().zip(rows.iter()) {
+            f.render_widget(tool_gauge(activity), *row);
+        }
+    }
+
+    // WIDGET 3: INPUT (or a pending approval, or the argument a picked prompt is asking for)
+    if let Some(approval) = app.approvals.front() {
+        let waiting = match app.approvals.len() {
+            1 => String::new(),
+            n => format!(", {} more waiting", n - 1),
+        };
+        let approval_block = Paragraph::new(approval.summary.as_str())
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                " SERVER '{}' WANTS TO USE THE LLM (y to Allow, n to Deny{}) ",
+                approval.server, waiting
+            )))
+            .style(Style::default().fg(Color::Magenta));
+        f.render_widget(approval_block, chunks[2]);
+        return;
+    }
+    let title = match &app.prompt_form {
+        Some(form) => {
+            let argument = &form.prompt.arguments[form.next];
+            format!(
+                " /{} > {}{}{} (Esc to Cancel) ",
+                form.prompt.name,
+                argument.name,
+                if argument.required { " (required)" } else { "" },
+                argument.description.as_deref().map(|d| format!(": {}", d)).unwrap_or_default()
+            )
+        }
+        None => " COMMAND INPUT (Esc to Quit, Ctrl+X to Abort Tool, Ctrl+R for Resources, / for Prompts) ".to_string(),
+    };
     let input_block = Paragraph::new(app.input.as_str())
-        .block(Block::default().borders(Borders::ALL).title(" COMMAND INPUT (Esc to Quit) "))
+        .block(Block::default().borders(Borders::ALL).title(title))
         .style(Style::default().fg(Color::Yellow));
-    f.render_widget(input_block, chunks[1]);
-}
\ No newline at end of file
+    f.render_widget(input_block, chunks[2]);
+}
+
+// WIDGET 2b: ONE TOOL CALL (a full bar needs a total; without one it stays empty)
+fn tool_gauge(activity: &ToolActivity) -> LineGauge<'_> {
+    let ratio = activity.progress.as_ref().and_then(ProgressNotification::ratio);
+    LineGauge::default()
+        .gauge_style(Style::default().fg(Color::Cyan))
+        .label(activity.label())
+        .ratio(ratio.unwrap_or(0.0))
+}
+
+// WIDGET 1b: THE RESOURCE BROWSER (takes the chat's place while open)
+fn browser_widget(browser: &ResourceBrowser) -> Paragraph<'_> {
+    let mut lines = Vec::new();
+    let mut row = 0;
+    for server in &browser.servers {
+        lines.push(Line::from(Span::styled(format!("[{}]", server.server), Style::default().fg(Color::Magenta))));
+        for resource in &server.resources {
+            let style = match row == browser.selected {
+                true => Style::default().fg(Color::Black).bg(Color::Cyan),
+                false => Style::default().fg(Color::Cyan),
+            };
+            let mime = resource.mime_type.as_deref().map(|m| format!(" ({})", m)).unwrap_or_default();
+            lines.push(Line::from(Span::styled(format!("  {}  {}{}", resource.name, resource.uri, mime), style)));
+            row += 1;
+        }
+        for template in &server.templates {
+            lines.push(Line::from(Span::styled(
+                format!("  {}  {} (template)", template.name, template.uri_template),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+
+    Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" RESOURCES (Enter to Attach, Esc to Close) "))
+        .wrap(Wrap { trim: false })
+}
+
+// WIDGET 1c: THE "/" MENU
+// Built-in commands whose name fits what's typed so far
+fn matching_builtins(input: &str) -> Vec<&'static str> {
+    let typed = input.trim_start_matches('/').split(' ').next().unwrap_or_default();
+    BUILTINS.iter().filter(|(name, _)| name.starts_with(typed)).map(|(_, usage)| *usage).collect()
+}
+
+fn menu_widget<'a>(matches: &[(&'a str, &'a Prompt)], builtins: &[&'a str], selected: usize) -> Paragraph<'a> {
+    let mut lines: Vec<Line> = matches.iter().enumerate().map(|(row, (server, prompt))| {
+        let style = match row == selected {
+            true => Style::default().fg(Color::Black).bg(Color::Cyan),
+            false => Style::default().fg(Color::Cyan),
+        };
+        let description = prompt.description.as_deref().map(|d| format!(" - {}", d)).unwrap_or_default();
+        Line::from(Span::styled(format!("/{} [{}]{}", prompt.name, server, description), style))
+    }).collect();
+    for usage in builtins {
+        lines.push(Line::from(Span::styled(*usage, Style::default().fg(Color::Yellow))));
+    }
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled("No matching prompts", Style::default().fg(Color::DarkGray))));
+    }
+
+    Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(" PROMPTS (Enter to Run) "))
+}
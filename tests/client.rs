@@ -0,0 +1,533 @@
+// tests/client.rs
+// Exercises McpClient's reader task with an in-memory transport that plays
+// a misbehaving server: out-of-order replies, interleaved notifications and
+// its own requests to us.
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex as StdMutex};
+
+use std::time::Duration;
+
+use aether::client::{McpClient, Timeouts};
+use aether::error::McpError;
+use aether::protocol::CallToolResult;
+use aether::roots::Roots;
+use aether::security::SecurityConfig;
+use aether::transport::Transport;
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::sync::{mpsc, Mutex};
+
+// What the fake server does with each message we send it
+type Script = Box<dyn Fn(&Value, &mpsc::UnboundedSender<String>) + Send + Sync>;
+
+// The test's view into the fake server
+#[derive(Clone, Default)]
+struct Probe {
+    // Everything the client sent, for assertions
+    sent: Arc<StdMutex<Vec<Value>>>,
+    // Server -> client pipe; taking it simulates the server hanging up
+    to_client: Arc<StdMutex<Option<mpsc::UnboundedSender<String>>>>,
+}
+
+impl Probe {
+    fn hang_up(&self) {
+        self.to_client.lock().unwrap().take();
+    }
+}
+
+struct ScriptedTransport {
+    script: Script,
+    probe: Probe,
+    from_server: Mutex<mpsc::UnboundedReceiver<String>>,
+}
+
+impl ScriptedTransport {
+    fn new(script: Script) -> (Self, Probe) {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let probe = Probe::default();
+        *probe.to_client.lock().unwrap() = Some(tx);
+        let transport = Self { script, probe: probe.clone(), from_server: Mutex::new(rx) };
+        (transport, probe)
+    }
+}
+
+#[async_trait]
+impl Transport for ScriptedTransport {
+    async fn send(&self, message: &str) -> Result<()> {
+        let value: Value = serde_json::from_str(message)?;
+        self.probe.sent.lock().unwrap().push(value.clone());
+        let tx = self.probe.to_client.lock().unwrap().clone().ok_or(anyhow!("closed"))?;
+        (self.script)(&value, &tx);
+        Ok(())
+    }
+
+    async fn receive(&self) -> Result<String> {
+        self.from_server.lock().await.recv().await.ok_or(anyhow!("server hung up"))
+    }
+
+    async fn close(&self) -> Result<()> {
+        self.probe.hang_up();
+        Ok(())
+    }
+}
+
+fn allow_all() -> SecurityConfig {
+    SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn concurrent_calls_are_matched_by_id() {
+    // The server holds the first call until the second arrives, then answers
+    // them in reverse order with a notification in between.
+    let held: Arc<StdMutex<Option<Value>>> = Arc::default();
+    let script: Script = Box::new(move |msg, tx| {
+        if msg["method"] != "tools/call" {
+            return;
+        }
+        let mut held = held.lock().unwrap();
+        match held.take() {
+            None => *held = Some(msg.clone()),
+            Some(first) => {
+                for call in [msg, &first] {
+                    let text = call["params"]["arguments"]["tag"].clone();
+                    let reply = json!({ "jsonrpc": "2.0", "id": call["id"], "result": { "tag": text } });
+                    tx.send(reply.to_string()).unwrap();
+                    tx.send(json!({ "jsonrpc": "2.0", "method": "notifications/message", "params": {} }).to_string()).unwrap();
+                }
+            }
+        }
+    });
+
+    let (transport, _probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+    let mut notifications = client.notifications();
+
+    let (a, b) = tokio::join!(
+        client.call_tool("echo", json!({ "tag": "a" })),
+        client.call_tool("echo", json!({ "tag": "b" })),
+    );
+    assert_eq!(a.unwrap()["tag"], "a");
+    assert_eq!(b.unwrap()["tag"], "b");
+
+    let note = notifications.recv().await.unwrap();
+    assert_eq!(note.method, "notifications/message");
+    assert_eq!(note.params, Some(json!({})));
+}
+
+#[tokio::test]
+async fn server_requests_get_answered() {
+    let script: Script = Box::new(|msg, tx| {
+        if msg["method"] == "tools/list" {
+            // Ping the client before answering, with a string id
+            tx.send(json!({ "jsonrpc": "2.0", "id": "srv-1", "method": "ping" }).to_string()).unwrap();
+            tx.send(json!({ "jsonrpc": "2.0", "id": "srv-2", "method": "bogus/method" }).to_string()).unwrap();
+            let reply = json!({ "jsonrpc": "2.0", "id": msg["id"], "result": { "tools": [] } });
+            tx.send(reply.to_string()).unwrap();
+        }
+    });
+
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+    assert!(client.list_tools().await.unwrap().is_empty());
+
+    // Replies are sent from spawned tasks; give them a moment
+    for _ in 0..50 {
+        if probe.sent.lock().unwrap().len() >= 3 {
+            break;
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+
+    let sent = probe.sent.lock().unwrap();
+    let pong = sent.iter().find(|m| m["id"] == "srv-1").unwrap();
+    assert_eq!(pong["result"], json!({}));
+    let refusal = sent.iter().find(|m| m["id"] == "srv-2").unwrap();
+    assert_eq!(refusal["error"]["code"], -32601);
+}
+
+#[tokio::test]
+async fn pending_calls_fail_when_the_server_hangs_up() {
+    let script: Script = Box::new(|_, _| {});
+    let (transport, probe) = ScriptedTransport::new(script);
+
+    let client = McpClient::new(transport, allow_all());
+    let call = tokio::spawn(async move { client.call_tool("slow", json!({})).await });
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    probe.hang_up();
+
+    let err = call.await.unwrap().unwrap_err().to_string();
+    assert!(err.contains("server hung up"), "{}", err);
+}
+
+// Waits for the client to send a message matching `pred`
+async fn wait_for_sent(probe: &Probe, pred: impl Fn(&Value) -> bool) -> Value {
+    for _ in 0..100 {
+        if let Some(m) = probe.sent.lock().unwrap().iter().find(|m| pred(m)) {
+            return m.clone();
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    panic!("expected message was never sent");
+}
+
+#[tokio::test]
+async fn slow_tools_time_out_and_get_cancelled() {
+    let script: Script = Box::new(|_, _| {}); // Never answers
+    let (transport, probe) = ScriptedTransport::new(script);
+
+    let mut timeouts = Timeouts::default();
+    timeouts.per_tool.insert("slow".to_string(), Duration::from_millis(50));
+    let client = McpClient::new(transport, allow_all()).with_timeouts(timeouts);
+
+    let err = client.call_tool("slow", json!({})).await.unwrap_err();
+    match err.downcast_ref::<McpError>() {
+        Some(McpError::Timeout { method, after }) => {
+            assert_eq!(method, "tools/call");
+            assert_eq!(*after, Duration::from_millis(50));
+        }
+        other => panic!("expected a timeout, got {:?}", other),
+    }
+
+    let cancel = wait_for_sent(&probe, |m| m["method"] == "notifications/cancelled").await;
+    assert_eq!(cancel["params"]["requestId"], 1);
+    assert!(cancel.get("id").is_none());
+}
+
+#[tokio::test]
+async fn dropping_a_call_cancels_it() {
+    let script: Script = Box::new(|_, _| {});
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+
+    // The "user" gives up after 20ms
+    let aborted = tokio::time::timeout(Duration::from_millis(20), client.call_tool("slow", json!({}))).await;
+    assert!(aborted.is_err());
+
+    let cancel = wait_for_sent(&probe, |m| m["method"] == "notifications/cancelled").await;
+    assert_eq!(cancel["params"]["requestId"], 1);
+}
+
+#[tokio::test]
+async fn shutdown_fails_pending_and_new_calls() {
+    let script: Script = Box::new(|_, _| {}); // Never answers
+    let (transport, probe) = ScriptedTransport::new(script);
+
+    let client = Arc::new(McpClient::new(transport, allow_all()));
+    let caller = client.clone();
+    let call = tokio::spawn(async move { caller.call_tool("slow", json!({})).await });
+
+    wait_for_sent(&probe, |m| m["method"] == "tools/call").await;
+    client.shutdown(Duration::from_millis(50)).await.unwrap();
+
+    for err in [call.await.unwrap().unwrap_err(), client.list_tools().await.unwrap_err()] {
+        assert!(
+            matches!(err.downcast_ref::<McpError>(), Some(McpError::Shutdown { .. })),
+            "expected a shutdown error, got {}", err
+        );
+    }
+}
+
+#[tokio::test]
+async fn resource_lists_follow_cursors() {
+    let script: Script = Box::new(|msg, tx| {
+        let result = match (msg["method"].as_str(), msg["params"]["cursor"].as_str()) {
+            (Some("initialize"), _) => json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": { "resources": {} },
+                "serverInfo": { "name": "pages", "version": "1" }
+            }),
+            (Some("resources/list"), None) => json!({ "resources": [{ "uri": "a://1", "name": "one" }], "nextCursor": "2" }),
+            (Some("resources/list"), Some("2")) => json!({ "resources": [{ "uri": "a://2", "name": "two" }], "nextCursor": "3" }),
+            (Some("resources/list"), Some(_)) => json!({ "resources": [{ "uri": "a://3", "name": "three" }] }),
+            _ => return,
+        };
+        tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result }).to_string()).unwrap();
+    });
+
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+    client.initialize().await.unwrap();
+    assert!(client.supports("resources"));
+    assert!(!client.supports("prompts"));
+
+    let resources = client.list_resources().await.unwrap();
+    let uris: Vec<&str> = resources.iter().map(|r| r.uri.as_str()).collect();
+    assert_eq!(uris, vec!["a://1", "a://2", "a://3"]);
+    let cursors: Vec<Value> = probe.sent.lock().unwrap().iter()
+        .filter(|m| m["method"] == "resources/list")
+        .map(|m| m["params"]["cursor"].clone())
+        .collect();
+    assert_eq!(cursors, vec![Value::Null, json!("2"), json!("3")]);
+
+    // No "subscribe": true in the capabilities, so we don't even ask
+    let err = client.subscribe_resource("a://1").await.unwrap_err();
+    assert!(err.to_string().contains("does not support resource subscriptions"), "{}", err);
+}
+
+#[tokio::test]
+async fn roots_are_listed_and_changes_announced() {
+    let script: Script = Box::new(|msg, tx| match msg["method"].as_str() {
+        Some("initialize") => {
+            let result = json!({
+                "protocolVersion": "2024-11-05",
+                "capabilities": {},
+                "serverInfo": { "name": "fs", "version": "1" }
+            });
+            tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result }).to_string()).unwrap();
+        }
+        Some("notifications/roots/list_changed") => {
+            tx.send(json!({ "jsonrpc": "2.0", "id": "roots-2", "method": "roots/list" }).to_string()).unwrap();
+        }
+        _ => {}
+    });
+    let dir = std::env::temp_dir().join(format!("aether-roots-{}", std::process::id()));
+    let extra = dir.join("My Project");
+    std::fs::create_dir_all(&extra).unwrap();
+    let roots = Roots::new([dir.clone()]);
+
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all()).with_roots(roots.clone());
+    client.initialize().await.unwrap();
+
+    let init = wait_for_sent(&probe, |m| m["method"] == "initialize").await;
+    assert_eq!(init["params"]["capabilities"]["roots"], json!({ "listChanged": true }));
+
+    roots.add(&extra);
+    wait_for_sent(&probe, |m| m["method"] == "notifications/roots/list_changed").await;
+    let listed = wait_for_sent(&probe, |m| m["id"] == "roots-2").await;
+    let uris: Vec<&str> = listed["result"]["roots"].as_array().unwrap().iter()
+        .map(|r| r["uri"].as_str().unwrap())
+        .collect();
+    assert_eq!(uris.len(), 2);
+    assert!(uris[1].ends_with("/My%20Project"), "{:?}", uris);
+    assert_eq!(listed["result"]["roots"][1]["name"], "My Project");
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[tokio::test]
+async fn paths_outside_the_roots_are_blocked() {
+    let script: Script = Box::new(|msg, tx| {
+        if msg["method"] == "tools/call" {
+            tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": { "content": [] } }).to_string()).unwrap();
+        }
+    });
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all()).with_roots(Roots::new([PathBuf::from("/srv/project")]));
+
+    client.call_tool("read_file", json!({ "path": "/srv/project/src/main.rs" })).await.unwrap();
+    let err = client.call_tool("read_file", json!({ "path": "/srv/project/../../etc/passwd" })).await.unwrap_err();
+    assert!(err.to_string().contains("outside the granted roots"), "{}", err);
+
+    // Only the allowed call reached the server
+    let calls = probe.sent.lock().unwrap().iter().filter(|m| m["method"] == "tools/call").count();
+    assert_eq!(calls, 1);
+}
+
+// A fake server that answers initialize with `version` and `capabilities`
+fn handshake_script(version: &'static str, capabilities: Value) -> Script {
+    Box::new(move |msg, tx| {
+        if msg["method"] == "initialize" {
+            let result = json!({
+                "protocolVersion": version,
+                "capabilities": capabilities,
+                "serverInfo": { "name": "negotiator", "version": "1" }
+            });
+            tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result }).to_string()).unwrap();
+        }
+    })
+}
+
+#[tokio::test]
+async fn handshake_negotiates_and_announces_initialized() {
+    let (transport, probe) = ScriptedTransport::new(handshake_script("2025-03-26", json!({ "tools": { "listChanged": true } })));
+    let client = McpClient::new(transport, allow_all());
+    client.initialize().await.unwrap();
+
+    assert_eq!(client.protocol_version(), Some("2025-03-26"));
+    assert!(client.server_capabilities().unwrap().tools.as_ref().unwrap().list_changed);
+
+    let sent = probe.sent.lock().unwrap().clone();
+    assert_eq!(sent[0]["params"]["protocolVersion"], "2025-06-18");
+    assert_eq!(sent[1], json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+}
+
+#[tokio::test]
+async fn unknown_protocol_versions_are_refused() {
+    let (transport, probe) = ScriptedTransport::new(handshake_script("1999-01-01", json!({})));
+    let client = McpClient::new(transport, allow_all());
+
+    let err = client.initialize().await.unwrap_err().to_string();
+    assert!(err.contains("MCP 1999-01-01") && err.contains("2024-11-05"), "{}", err);
+    assert!(client.protocol_version().is_none());
+    assert!(!probe.sent.lock().unwrap().iter().any(|m| m["method"] == "notifications/initialized"));
+}
+
+#[tokio::test]
+async fn unadvertised_methods_fail_fast() {
+    let (transport, probe) = ScriptedTransport::new(handshake_script("2024-11-05", json!({ "tools": {} })));
+    let client = McpClient::new(transport, allow_all());
+    client.initialize().await.unwrap();
+
+    let err = client.list_resources().await.unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<McpError>(),
+        Some(&McpError::Unsupported { method: "resources/list".to_string(), capability: "resources".to_string() })
+    );
+    assert!(err.to_string().contains("did not advertise the 'resources' capability"), "{}", err);
+    assert!(client.list_prompts().await.is_err());
+
+    // Nothing but the handshake went over the wire
+    let methods: Vec<Value> = probe.sent.lock().unwrap().iter().map(|m| m["method"].clone()).collect();
+    assert_eq!(methods, vec![json!("initialize"), json!("notifications/initialized")]);
+}
+
+#[tokio::test]
+async fn arguments_are_checked_against_the_input_schema() {
+    let script: Script = Box::new(|msg, tx| {
+        let result = match msg["method"].as_str() {
+            Some("tools/list") => json!({ "tools": [{
+                "name": "add",
+                "inputSchema": {
+                    "type": "object",
+                    "properties": { "a": { "type": "number" }, "b": { "type": "number" } },
+                    "required": ["a", "b"],
+                    "additionalProperties": false
+                }
+            }] }),
+            Some("tools/call") => json!({ "content": [{ "type": "text", "text": "3" }] }),
+            _ => return,
+        };
+        tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result }).to_string()).unwrap();
+    });
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+    client.list_tools().await.unwrap();
+
+    client.call_tool("add", json!({ "a": 1, "b": 2 })).await.unwrap();
+    let err = client.call_tool("add", json!({ "a": "one", "c": 2 })).await.unwrap_err();
+    match err.downcast_ref::<McpError>() {
+        Some(McpError::InvalidArguments { tool, violations }) => {
+            assert_eq!(tool, "add");
+            assert_eq!(violations.len(), 3, "{:?}", violations);
+        }
+        other => panic!("expected invalid arguments, got {:?}", other),
+    }
+    let message = err.to_string();
+    assert!(message.contains("/a: expected number, got string"), "{}", message);
+    assert!(message.contains("missing required property 'b'"), "{}", message);
+    assert!(message.contains("unexpected property 'c'"), "{}", message);
+
+    // Tools we never listed aren't checked
+    client.call_tool("other", json!("anything")).await.unwrap();
+
+    // The rejected call never reached the server
+    let calls = probe.sent.lock().unwrap().iter().filter(|m| m["method"] == "tools/call").count();
+    assert_eq!(calls, 2);
+}
+
+#[tokio::test]
+async fn structured_output_is_checked_against_the_output_schema() {
+    let script: Script = Box::new(|msg, tx| {
+        let result = match (msg["method"].as_str(), msg["params"]["arguments"]["mode"].as_str()) {
+            (Some("tools/list"), _) => json!({ "tools": [{
+                "name": "weather",
+                "inputSchema": { "type": "object" },
+                "outputSchema": {
+                    "type": "object",
+                    "properties": { "celsius": { "type": "number" } },
+                    "required": ["celsius"]
+                }
+            }] }),
+            (Some("tools/call"), Some("good")) => json!({ "content": [], "structuredContent": { "celsius": 21.5 } }),
+            (Some("tools/call"), Some("bad")) => json!({ "content": [], "structuredContent": { "celsius": "warm" } }),
+            (Some("tools/call"), Some("bare")) => json!({ "content": [{ "type": "text", "text": "21.5" }] }),
+            (Some("tools/call"), _) => json!({ "content": [{ "type": "text", "text": "offline" }], "isError": true }),
+            _ => return,
+        };
+        tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result }).to_string()).unwrap();
+    });
+    let (transport, _probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+    let tools = client.list_tools().await.unwrap();
+    assert!(tools[0].output_schema.is_some());
+
+    #[derive(serde::Deserialize)]
+    struct Weather {
+        celsius: f64,
+    }
+    let good = client.call_tool("weather", json!({ "mode": "good" })).await.unwrap();
+    let result: CallToolResult = serde_json::from_value(good).unwrap();
+    let weather: Weather = result.structured().unwrap().unwrap();
+    assert_eq!(weather.celsius, 21.5);
+
+    for (mode, expected) in [("bad", "/celsius: expected number, got string"), ("bare", "missing structuredContent")] {
+        let err = client.call_tool("weather", json!({ "mode": mode })).await.unwrap_err();
+        assert!(matches!(err.downcast_ref::<McpError>(), Some(McpError::InvalidOutput { .. })), "{}", err);
+        assert!(err.to_string().contains(expected), "{}", err);
+    }
+
+    // A failed call has nothing to validate
+    client.call_tool("weather", json!({ "mode": "down" })).await.unwrap();
+}
+
+#[tokio::test]
+async fn tool_listing_follows_every_page() {
+    let script: Script = Box::new(|msg, tx| {
+        if msg["method"] != "tools/list" {
+            return;
+        }
+        let tool = |name: &str| json!({ "name": name, "inputSchema": { "type": "object" } });
+        let result = match msg["params"]["cursor"].as_str() {
+            None => json!({ "tools": [tool("first")], "nextCursor": "2" }),
+            Some("2") => json!({ "tools": [tool("second"), tool("third")], "nextCursor": "3" }),
+            Some(_) => json!({ "tools": [tool("last")] }),
+        };
+        tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": result }).to_string()).unwrap();
+    });
+    let (transport, _probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+
+    let names: Vec<String> = client.list_tools().await.unwrap().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["first", "second", "third", "last"]);
+}
+
+#[tokio::test]
+async fn progress_reaches_the_caller() {
+    let script: Script = Box::new(|msg, tx| {
+        if msg["method"] != "tools/call" {
+            return;
+        }
+        let token = msg["params"]["_meta"]["progressToken"].clone();
+        for (step, message) in [(1, "Reading"), (2, "Writing")] {
+            let params = json!({ "progressToken": token, "progress": step, "total": 2, "message": message });
+            tx.send(json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": params }).to_string()).unwrap();
+        }
+        // Someone else's token goes nowhere
+        let stray = json!({ "progressToken": "other", "progress": 9 });
+        tx.send(json!({ "jsonrpc": "2.0", "method": "notifications/progress", "params": stray }).to_string()).unwrap();
+        tx.send(json!({ "jsonrpc": "2.0", "id": msg["id"], "result": { "content": [] } }).to_string()).unwrap();
+    });
+    let (transport, probe) = ScriptedTransport::new(script);
+    let client = McpClient::new(transport, allow_all());
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    client.call_tool_with_progress("copy", json!({}), Some(tx)).await.unwrap();
+    let updates: Vec<_> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+    let steps: Vec<(f64, Option<f64>)> = updates.iter().map(|u| (u.progress, u.ratio())).collect();
+    assert_eq!(steps, vec![(1.0, Some(0.5)), (2.0, Some(1.0))]);
+    assert_eq!(updates[1].message.as_deref(), Some("Writing"));
+
+    // Plain calls don't ask for progress
+    client.call_tool("copy", json!({})).await.unwrap();
+    let sent = probe.sent.lock().unwrap();
+    assert!(sent[0]["params"]["_meta"]["progressToken"].is_number());
+    assert!(sent[1]["params"].get("_meta").is_none());
+}
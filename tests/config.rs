@@ -0,0 +1,201 @@
+// tests/config.rs
+use aether::config::{AetherConfig, TransportKind, DEFAULT_MODEL};
+
+#[test]
+fn desktop_config_parses_verbatim() {
+    let config = AetherConfig::parse(r#"{
+        "mcpServers": {
+            "filesystem": {
+                "command": "npx",
+                "args": ["-y", "@modelcontextprotocol/server-filesystem", "/tmp"],
+                "env": { "DEBUG": "1" }
+            },
+            "remote": { "url": "https://example.com/mcp" },
+            "old": { "type": "sse", "url": "http://localhost:8080/sse", "disabled": true }
+        },
+        "globalShortcut": "Ctrl+Space"
+    }"#).unwrap();
+
+    assert_eq!(config.llm.model, DEFAULT_MODEL);
+    assert_eq!(config.permissions.to_str(), Some("permissions.json"));
+
+    let fs = &config.mcp_servers["filesystem"];
+    assert_eq!(fs.name, "filesystem");
+    assert_eq!(fs.transport_kind(), TransportKind::Stdio);
+    assert_eq!(fs.args.len(), 3);
+    assert_eq!(config.mcp_servers["remote"].transport_kind(), TransportKind::Http);
+
+    // Disabled entries are parsed but not launched
+    let names: Vec<String> = config.servers().into_iter().map(|s| s.name).collect();
+    assert_eq!(names, vec!["filesystem", "remote"]);
+}
+
+#[test]
+fn aether_sections_are_read() {
+    let config = AetherConfig::parse(r#"{
+        "mcpServers": {},
+        "llm": { "model": "llama-3.1-8b-instant", "samplingModels": ["llama-3.3-70b-versatile"] },
+        "permissions": "config/permissions.json"
+    }"#).unwrap();
+
+    assert_eq!(config.llm.model, "llama-3.1-8b-instant");
+    assert_eq!(config.llm.sampling_models, vec!["llama-3.3-70b-versatile"]);
+    assert_eq!(config.permissions.to_str(), Some("config/permissions.json"));
+}
+
+fn error_of(json: &str) -> String {
+    format!("{:#}", AetherConfig::parse(json).unwrap_err())
+}
+
+#[test]
+fn type_errors_name_the_key() {
+    let err = error_of(r#"{ "mcpServers": { "math": { "command": "x", "args": ["ok", 3] } } }"#);
+    assert!(err.starts_with("mcpServers.math.args[1]:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "math": { "type": "carrier-pigeon", "url": "http://x" } } }"#);
+    assert!(err.starts_with("mcpServers.math.type:"), "{}", err);
+
+    // A typo must not quietly drop a protection
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "sandbox": { "writeable": ["/tmp"] } } } }"#);
+    assert!(err.starts_with("mcpServers.m.sandbox.writeable:"), "{}", err);
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "limits": { "memoryMB": 64 } } } }"#);
+    assert!(err.starts_with("mcpServers.m.limits.memoryMB:"), "{}", err);
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "sandbx": {} } } }"#);
+    assert!(err.starts_with("mcpServers.m.sandbx:"), "{}", err);
+}
+
+#[test]
+fn semantic_errors_name_the_key() {
+    let err = error_of(r#"{ "mcpServers": { "math": { "args": [] } } }"#);
+    assert!(err.starts_with("mcpServers.math.command:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "web": { "type": "http" } } }"#);
+    assert!(err.starts_with("mcpServers.web.url:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "web": { "url": "ftp://example.com" } } }"#);
+    assert!(err.starts_with("mcpServers.web.url:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "a__b": { "command": "x" } } }"#);
+    assert!(err.starts_with("mcpServers.a__b:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "web": { "url": "https://x", "sandbox": {} } } }"#);
+    assert!(err.starts_with("mcpServers.web.sandbox:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "limits": { "memoryMb": 0 } } } }"#);
+    assert!(err.starts_with("mcpServers.m.limits.memoryMb:"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "limits": { "cpuPercent": 50 } } } }"#);
+    assert!(err.starts_with("mcpServers.m.limits.cpuPercent:"), "{}", err);
+
+    // RLIMIT_NPROC counts all of the user's processes, so tiny values only work with a cgroup
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "limits": { "processes": 16 } } } }"#);
+    assert!(err.starts_with("mcpServers.m.limits.processes:"), "{}", err);
+    assert!(AetherConfig::parse(r#"{ "mcpServers": { "m": { "command": "x", "limits": { "processes": 16, "cgroup": true } } } }"#).is_ok());
+
+    let err = error_of(r#"{ "llm": { "model": " " } }"#);
+    assert!(err.starts_with("llm.model:"), "{}", err);
+
+    let err = error_of(r#"{ "llm": { "samplingModels": ["llama-3.3-70b-versatile", ""] } }"#);
+    assert!(err.starts_with("llm.samplingModels[1]:"), "{}", err);
+
+    let err = error_of(r#"{ "roots": ["/srv", ""] }"#);
+    assert!(err.starts_with("roots[1]:"), "{}", err);
+}
+
+#[test]
+fn sandbox_profiles_default_to_locked_down() {
+    let config = AetherConfig::parse(r#"{
+        "mcpServers": {
+            "caged": { "command": "x", "sandbox": { "writable": ["/tmp/scratch"] } },
+            "open": { "command": "x", "sandbox": { "network": true, "seccomp": false, "allowSyscalls": ["ptrace"], "allowWithoutLandlock": true, "writableRoots": true } }
+        }
+    }"#).unwrap();
+
+    let caged = config.mcp_servers["caged"].sandbox.as_ref().unwrap();
+    assert!(!caged.network);
+    assert!(caged.seccomp);
+    assert!(!caged.allow_without_landlock && !caged.writable_roots);
+    assert_eq!(caged.writable[0].to_str(), Some("/tmp/scratch"));
+
+    let open = config.mcp_servers["open"].sandbox.as_ref().unwrap();
+    assert!(open.network && !open.seccomp && open.allow_without_landlock && open.writable_roots);
+    assert_eq!(open.allow_syscalls, vec!["ptrace"]);
+}
+
+#[test]
+fn secret_references_are_checked_at_parse_time() {
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "env": { "KEY": "${vault:key}" } } } }"#);
+    assert!(err.starts_with("mcpServers.m.env.KEY: unknown reference"), "{}", err);
+
+    let err = error_of(r#"{ "mcpServers": { "m": { "command": "x", "env": { "KEY": "${env:OOPS" } } } }"#);
+    assert!(err.starts_with("mcpServers.m.env.KEY: unterminated"), "{}", err);
+}
+
+#[test]
+fn secret_references_resolve_per_server() {
+    let dir = std::env::temp_dir().join(format!("aether-config-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("token"), "file-secret\n").unwrap();
+    std::fs::write(dir.join("aether.json"), r#"{
+        "mcpServers": {
+            "uses": { "command": "x", "env": {
+                "AUTH": "Bearer ${env:AETHER_CONFIG_TEST_KEY}",
+                "TOKEN": "${file:token}",
+                "PLAIN": "plain"
+            } },
+            "missing": { "command": "x", "env": { "KEY": "${env:AETHER_CONFIG_TEST_UNSET}" } }
+        }
+    }"#).unwrap();
+    std::env::set_var("AETHER_CONFIG_TEST_KEY", "env-secret");
+
+    let config = AetherConfig::load(dir.join("aether.json")).unwrap();
+    let env = config.mcp_servers["uses"].resolved_env().unwrap();
+    assert_eq!(env["AUTH"], "Bearer env-secret");
+    assert_eq!(env["TOKEN"], "file-secret");
+    assert_eq!(env["PLAIN"], "plain");
+
+    let err = config.mcp_servers["missing"].resolved_env().unwrap_err().to_string();
+    assert!(err.starts_with("mcpServers.missing.env.KEY:"), "{}", err);
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+#[test]
+fn roots_resolve_against_the_config_file() {
+    let dir = std::env::temp_dir().join(format!("aether-config-roots-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("workspace")).unwrap();
+    let path = dir.join("aether.json");
+    std::fs::write(&path, r#"{ "roots": ["workspace", "missing"] }"#).unwrap();
+
+    let config = AetherConfig::load(&path).unwrap();
+    assert_eq!(config.roots, vec![dir.join("workspace"), dir.join("missing")]);
+    let err = format!("{:#}", config.granted_roots().err().unwrap());
+    assert!(err.starts_with("roots[1]:"), "{}", err);
+
+    std::fs::write(&path, r#"{ "roots": ["workspace"] }"#).unwrap();
+    let roots = AetherConfig::load(&path).unwrap().granted_roots().unwrap();
+    assert!(roots.contains(&dir.join("workspace/notes.md")));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn server_paths_expand_home() {
+    let dir = std::env::temp_dir().join(format!("aether-config-home-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("aether.json");
+    std::fs::write(&path, r#"{ "mcpServers": { "m": {
+        "command": "x", "cwd": "~/work",
+        "sandbox": { "readOnly": ["~/data", "data"], "writable": ["~"] }
+    } } }"#).unwrap();
+
+    let home = std::path::PathBuf::from(std::env::var_os("HOME").unwrap());
+    let config = AetherConfig::load(&path).unwrap();
+    let server = &config.mcp_servers["m"];
+    assert_eq!(server.cwd, Some(home.join("work")));
+    let sandbox = server.sandbox.as_ref().unwrap();
+    assert_eq!(sandbox.read_only, vec![home.join("data"), dir.join("data")]);
+    assert_eq!(sandbox.writable, vec![home.clone()]);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,90 @@
+// tests/content.rs
+// Tool results as the LLM and the log pane see them.
+use aether::content::{decode_base64, image_size, render_result, summarize_block, summarize_result};
+use aether::protocol::{CallToolResult, ContentBlock};
+use serde_json::json;
+
+// A 1x1 transparent PNG
+const PIXEL_PNG: &str = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+fn parse(value: serde_json::Value) -> CallToolResult {
+    serde_json::from_value(value).unwrap()
+}
+
+#[test]
+fn every_block_type_parses() {
+    let result = parse(json!({
+        "content": [
+            { "type": "text", "text": "Rendered." },
+            { "type": "image", "data": PIXEL_PNG, "mimeType": "image/png" },
+            { "type": "audio", "data": "UklGRg==", "mimeType": "audio/wav" },
+            { "type": "resource_link", "uri": "file:///srv/out.csv", "name": "out.csv", "mimeType": "text/csv", "size": 2048 },
+            { "type": "resource", "resource": { "uri": "mock://notes/a", "mimeType": "text/plain", "text": "Note a" } },
+            { "type": "hologram" }
+        ]
+    }));
+    assert!(!result.is_error);
+    assert_eq!(result.content.len(), 6);
+    assert!(matches!(&result.content[3], ContentBlock::ResourceLink(link) if link.size == Some(2048)));
+    assert_eq!(result.content[5], ContentBlock::Unknown);
+
+    let summary = summarize_result(&result);
+    assert_eq!(
+        summary,
+        "Rendered. | [image 1x1 png] | [audio wav, 6 B] | [link out.csv <file:///srv/out.csv> (text/csv, 2.0 KB)] \
+         | [resource mock://notes/a (text/plain)] | [unsupported content]"
+    );
+
+    // The LLM gets embedded resources in full
+    let rendered = render_result(&result);
+    assert!(rendered.starts_with("Rendered.\n\n[image 1x1 png]"), "{}", rendered);
+    assert!(rendered.contains("\n\nNote a\n\n"), "{}", rendered);
+}
+
+#[test]
+fn errors_are_flagged_for_the_llm() {
+    let result = parse(json!({ "content": [{ "type": "text", "text": "Disk full" }], "isError": true }));
+    assert_eq!(render_result(&result), "Error: Disk full");
+    assert_eq!(summarize_result(&result), "ERROR: Disk full");
+}
+
+#[test]
+fn structured_content_is_shown_once() {
+    let mirrored = parse(json!({
+        "content": [{ "type": "text", "text": "{\"sum\": 3}" }],
+        "structuredContent": { "sum": 3 }
+    }));
+    assert_eq!(render_result(&mirrored), "{\"sum\": 3}");
+
+    let bare = parse(json!({ "structuredContent": { "sum": 3 } }));
+    assert_eq!(render_result(&bare), "Structured result: {\"sum\":3}");
+    assert_eq!(render_result(&parse(json!({}))), "(no content)");
+}
+
+#[test]
+fn image_sizes_come_from_the_header() {
+    let png = decode_base64(PIXEL_PNG).unwrap();
+    assert_eq!(image_size(&png), Some((1, 1)));
+
+    let gif = b"GIF89a\x40\x01\xc8\x00rest";
+    assert_eq!(image_size(gif), Some((320, 200)));
+
+    // SOI, an APP0 segment, then SOF0 with height 200 and width 320
+    let jpeg = [
+        0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00,
+        0xFF, 0xC0, 0x00, 0x11, 0x08, 0x00, 0xC8, 0x01, 0x40, 0x03,
+    ];
+    assert_eq!(image_size(&jpeg), Some((320, 200)));
+    assert_eq!(image_size(b"not an image"), None);
+
+    let unknown = ContentBlock::Image { data: "bm90IGFuIGltYWdl".to_string(), mime_type: "image/webp".to_string() };
+    assert_eq!(summarize_block(&unknown), "[image webp]");
+}
+
+#[test]
+fn base64_accepts_both_alphabets() {
+    assert_eq!(decode_base64("aGk/Pz8+").unwrap(), b"hi???>");
+    assert_eq!(decode_base64("aGk_Pz8-").unwrap(), b"hi???>");
+    assert_eq!(decode_base64("aG k=\n").unwrap(), b"hi");
+    assert!(decode_base64("a*b").is_none());
+}
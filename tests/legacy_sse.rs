@@ -0,0 +1,151 @@
+// tests/legacy_sse.rs
+// Drives the legacy HTTP+SSE transport against a local axum stand-in server.
+// The stand-in ends its event stream after every batch, so each reply is
+// only delivered after the client reconnects with Last-Event-ID.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use aether::client::McpClient;
+use aether::security::SecurityConfig;
+use aether::transport::{LegacySseTransport, Transport};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Response, StatusCode},
+    routing::{get, post},
+    Router,
+};
+use serde_json::{json, Value};
+
+#[derive(Default)]
+struct ServerState {
+    // Replies waiting for the next GET
+    queued: Vec<String>,
+    next_event_id: u64,
+    // The Last-Event-ID header of every GET, in order
+    resumed_from: Vec<Option<String>>,
+}
+
+type Shared = Arc<Mutex<ServerState>>;
+
+async fn handle_stream(State(state): State<Shared>, headers: HeaderMap) -> Response<Body> {
+    let mut state = state.lock().unwrap();
+    let last_id = headers.get("last-event-id").map(|v| v.to_str().unwrap().to_string());
+    state.resumed_from.push(last_id);
+
+    let mut body = String::from("event: endpoint\ndata: /messages?session=abc\n\n");
+    for reply in std::mem::take(&mut state.queued) {
+        state.next_event_id += 1;
+        body.push_str(&format!("id: {}\nevent: message\ndata: {}\n\n", state.next_event_id, reply));
+    }
+
+    Response::builder()
+        .header("content-type", "text/event-stream")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+async fn handle_message(State(state): State<Shared>, body: String) -> StatusCode {
+    let msg: Value = serde_json::from_str(&body).unwrap();
+    let Some(id) = msg.get("id").cloned() else {
+        return StatusCode::ACCEPTED;
+    };
+
+    let result = match msg["method"].as_str().unwrap_or("") {
+        "initialize" => json!({
+            "protocolVersion": "2024-11-05",
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": "LegacyStandIn", "version": "0.0.1" }
+        }),
+        "tools/list" => json!({ "tools": [{ "name": "ping", "inputSchema": { "type": "object" } }] }),
+        _ => json!({}),
+    };
+
+    let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+    state.lock().unwrap().queued.push(reply.to_string());
+    StatusCode::ACCEPTED
+}
+
+async fn spawn_server() -> (String, Shared) {
+    let state: Shared = Arc::default();
+    let app = Router::new()
+        .route("/sse", get(handle_stream))
+        .route("/messages", post(handle_message))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (format!("http://{}/sse", addr), state)
+}
+
+#[tokio::test]
+async fn client_reconnects_and_resumes() {
+    let (url, state) = spawn_server().await;
+    let transport = LegacySseTransport::connect(&url).await.unwrap();
+    assert_eq!(transport.endpoint().unwrap().path(), "/messages");
+
+    let security = SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    };
+    let client = McpClient::new(transport, security);
+
+    client.initialize().await.unwrap();
+    let tools = client.list_tools().await.unwrap();
+    assert_eq!(tools[0].name, "ping");
+
+    // The first GET had nothing to resume; later ones picked up after event 1
+    let resumed = state.lock().unwrap().resumed_from.clone();
+    assert_eq!(resumed[0], None);
+    assert!(resumed.contains(&Some("1".to_string())));
+}
+
+#[tokio::test]
+async fn connect_fails_without_a_server() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/sse", listener.local_addr().unwrap());
+    drop(listener);
+
+    let transport = tokio::time::timeout(
+        std::time::Duration::from_secs(5),
+        LegacySseTransport::connect_with_timeout(&url, std::time::Duration::from_millis(300)),
+    ).await.unwrap();
+    let err = transport.err().unwrap();
+    assert!(err.to_string().contains("endpoint"), "{}", err);
+}
+
+#[tokio::test]
+async fn close_ends_the_stream() {
+    let (url, _state) = spawn_server().await;
+    let transport = LegacySseTransport::connect(&url).await.unwrap();
+
+    transport.close().await.unwrap();
+    assert!(transport.receive().await.is_err());
+}
+
+#[tokio::test]
+async fn dropping_the_transport_stops_the_reader() {
+    let (url, state) = spawn_server().await;
+    let transport = LegacySseTransport::connect(&url).await.unwrap();
+    drop(transport);
+
+    let gets = state.lock().unwrap().resumed_from.len();
+    tokio::time::sleep(std::time::Duration::from_millis(1200)).await;
+    assert_eq!(state.lock().unwrap().resumed_from.len(), gets);
+}
+
+#[tokio::test]
+async fn streams_that_end_right_away_are_backed_off() {
+    // Nothing queued: every stream is just the endpoint event, then EOF
+    let (url, state) = spawn_server().await;
+    let transport = LegacySseTransport::connect(&url).await.unwrap();
+
+    // Reconnects after 0.5 s and 1.5 s, then not before 3.5 s (a flat 0.5 s would be ~5 GETs)
+    tokio::time::sleep(std::time::Duration::from_millis(2500)).await;
+    let gets = state.lock().unwrap().resumed_from.len();
+    assert!(gets <= 3, "{} GETs", gets);
+    transport.close().await.unwrap();
+}
@@ -0,0 +1,114 @@
+// tests/limits.rs
+// Real processes running into their resource limits.
+#![cfg(target_os = "linux")]
+use std::collections::HashMap;
+use std::time::Duration;
+
+use aether::client::McpClient;
+use aether::config::ResourceLimits;
+use aether::error::McpError;
+use aether::runtime::limits::group_name;
+use aether::runtime::{LaunchOptions, McpProcess};
+use aether::security::SecurityConfig;
+use aether::transport::Transport;
+use serde_json::json;
+
+fn limited(script: &str, limits: ResourceLimits) -> anyhow::Result<McpProcess> {
+    let options = LaunchOptions {
+        name: Some("hog".to_string()),
+        limits: Some(limits),
+        ..LaunchOptions::default()
+    };
+    McpProcess::start_with("sh", &["-c", script], &options)
+}
+
+fn client(process: McpProcess) -> McpClient {
+    let security = SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    };
+    McpClient::new(process, security)
+}
+
+fn limit_of(err: &anyhow::Error) -> String {
+    match err.downcast_ref::<McpError>() {
+        Some(McpError::ResourceLimit { server, limit }) => {
+            assert_eq!(server, "hog");
+            limit.clone()
+        }
+        other => panic!("expected a resource limit error, got {:?} ({})", other, err),
+    }
+}
+
+#[tokio::test]
+async fn rlimits_are_applied() {
+    let limits = ResourceLimits { open_files: Some(64), processes: Some(4096), ..ResourceLimits::default() };
+    // Soft limits, in /proc/self/limits order: processes, then open files
+    let script = "grep -E '^Max (processes|open files)' /proc/self/limits | awk '{ print $(NF-2) }'";
+    let process = limited(script, limits).unwrap();
+
+    assert_eq!(process.receive().await.unwrap().trim(), "4096");
+    assert_eq!(process.receive().await.unwrap().trim(), "64");
+}
+
+#[tokio::test]
+async fn cpu_hogs_are_killed_with_a_typed_error() {
+    let limits = ResourceLimits { cpu_seconds: Some(1), ..ResourceLimits::default() };
+    let client = client(limited("while :; do :; done", limits).unwrap());
+
+    let err = tokio::time::timeout(Duration::from_secs(20), client.call_tool("spin", json!({})))
+        .await.unwrap().unwrap_err();
+    assert_eq!(limit_of(&err), "CPU time limit (1 s)");
+
+    // Later calls keep reporting why the server is gone
+    let err = client.call_tool("spin", json!({})).await.unwrap_err();
+    assert_eq!(limit_of(&err), "CPU time limit (1 s)");
+}
+
+#[tokio::test]
+async fn memory_hogs_are_reported() {
+    if std::process::Command::new("python3").arg("--version").output().is_err() {
+        return;
+    }
+    let limits = ResourceLimits { memory_mb: Some(64), ..ResourceLimits::default() };
+    let client = client(limited("exec python3 -c 'x = bytearray(512 * 1024 * 1024)'", limits).unwrap());
+
+    let err = client.call_tool("hog", json!({})).await.unwrap_err();
+    assert_eq!(limit_of(&err), "memory limit (64 MB)");
+}
+
+#[tokio::test]
+async fn cgroup_limits_are_written() {
+    let limits = ResourceLimits { processes: Some(16), cgroup: true, ..ResourceLimits::default() };
+    // Prints its group and the pids.max the kernel enforces there
+    let script = "group=$(grep '^0::' /proc/self/cgroup | cut -c4-); \
+                  mount=$(awk '$3 == \"cgroup2\" { print $2; exit }' /proc/self/mounts); \
+                  echo \"$group\"; cat \"$mount$group/pids.max\"";
+    // Needs a delegated cgroup v2 with the pids controller. Without one the
+    // server must refuse to start (not run unlimited), and the test is skipped.
+    let process = match limited(script, limits) {
+        Ok(process) => process,
+        Err(e) => {
+            let reason = format!("{:#}", e);
+            assert!(reason.contains("cgroup"), "{}", reason);
+            eprintln!("skipping cgroup_limits_are_written: {}", reason);
+            return;
+        }
+    };
+
+    let group = process.receive().await.unwrap();
+    assert!(group.contains("aether-hog-"), "{}", group);
+    assert_eq!(process.receive().await.unwrap().trim(), "16");
+}
+
+#[test]
+fn cgroup_names_stay_in_their_directory() {
+    for server in ["../../escape", "a/b", "..", "my server", "ünï"] {
+        let name = group_name(server);
+        assert!(name.starts_with("aether-"), "{}", name);
+        assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{}", name);
+    }
+    assert!(group_name("hog").starts_with("aether-hog-"));
+    assert!(group_name(&"x".repeat(1000)).len() < 100);
+}
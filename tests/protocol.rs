@@ -0,0 +1,212 @@
+// tests/protocol.rs
+// JSON-RPC 2.0 messages, checked against the examples in the spec
+// (https://www.jsonrpc.org/specification#examples), plus the MCP shapes built on them.
+use aether::protocol::{
+    ContentBlock, ErrorCode, InitializeResult, JsonRpcError, JsonRpcFrame, JsonRpcMessage,
+    JsonRpcNotification, JsonRpcRequest, LoggingLevel, LoggingMessageNotification, Prompt, RequestId,
+    ResourceContents, ServerCapabilities, LATEST_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS,
+};
+use serde_json::{json, Value};
+
+// Decode, check the kind, and make sure encoding gives back the same JSON
+fn round_trip(wire: Value) -> JsonRpcMessage {
+    let message: JsonRpcMessage = serde_json::from_value(wire.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&message).unwrap(), wire);
+    message
+}
+
+#[test]
+fn requests_and_responses_round_trip() {
+    let request = round_trip(json!({"jsonrpc": "2.0", "method": "subtract", "params": [42, 23], "id": 1}));
+    assert_eq!(request, JsonRpcMessage::Request(JsonRpcRequest::new(1, "subtract", Some(json!([42, 23])))));
+
+    let JsonRpcMessage::Response(response) = round_trip(json!({"jsonrpc": "2.0", "result": 19, "id": 1})) else {
+        panic!("expected a response");
+    };
+    assert_eq!(response.id, RequestId::Number(1));
+    assert_eq!(response.result, json!(19));
+
+    // Named params
+    let JsonRpcMessage::Request(request) = round_trip(
+        json!({"jsonrpc": "2.0", "method": "subtract", "params": {"subtrahend": 23, "minuend": 42}, "id": 3}),
+    ) else {
+        panic!("expected a request");
+    };
+    assert_eq!(request.params.unwrap()["minuend"], 42);
+}
+
+#[test]
+fn string_ids_are_kept_as_strings() {
+    let JsonRpcMessage::Request(request) = round_trip(json!({"jsonrpc": "2.0", "method": "ping", "id": "abc-1"})) else {
+        panic!("expected a request");
+    };
+    assert_eq!(request.id, RequestId::from("abc-1"));
+    // "1" and 1 are different ids
+    assert_ne!(RequestId::from("1"), RequestId::from(1));
+
+    // Fractional or null ids are not valid request ids
+    assert!(serde_json::from_value::<JsonRpcMessage>(json!({"jsonrpc": "2.0", "method": "x", "id": 1.5})).is_err());
+    assert!(serde_json::from_value::<JsonRpcMessage>(json!({"jsonrpc": "2.0", "method": "x", "id": null})).is_err());
+}
+
+#[test]
+fn notifications_have_no_id() {
+    let update = round_trip(json!({"jsonrpc": "2.0", "method": "update", "params": [1, 2, 3, 4, 5]}));
+    assert_eq!(update, JsonRpcMessage::Notification(JsonRpcNotification::new("update", Some(json!([1, 2, 3, 4, 5])))));
+
+    let foobar = round_trip(json!({"jsonrpc": "2.0", "method": "foobar"}));
+    assert!(matches!(foobar, JsonRpcMessage::Notification(n) if n.params.is_none()));
+}
+
+#[test]
+fn error_responses_round_trip() {
+    let JsonRpcMessage::Error(missing) = round_trip(
+        json!({"jsonrpc": "2.0", "error": {"code": -32601, "message": "Method not found"}, "id": "1"}),
+    ) else {
+        panic!("expected an error response");
+    };
+    assert_eq!(missing.id, Some(RequestId::from("1")));
+    assert_eq!(missing.error, JsonRpcError::new(ErrorCode::MethodNotFound, "Method not found"));
+
+    // Parse errors can't name a request, so the id is null
+    let JsonRpcMessage::Error(parse) = round_trip(
+        json!({"jsonrpc": "2.0", "error": {"code": -32700, "message": "Parse error"}, "id": null}),
+    ) else {
+        panic!("expected an error response");
+    };
+    assert_eq!(parse.id, None);
+    assert_eq!(parse.error.code, ErrorCode::ParseError);
+
+    // Extra data survives
+    round_trip(json!({"jsonrpc": "2.0", "error": {"code": -32001, "message": "busy", "data": {"retry": 5}}, "id": 7}));
+}
+
+#[test]
+fn error_codes_map_both_ways() {
+    for (code, number) in [
+        (ErrorCode::ParseError, -32700),
+        (ErrorCode::InvalidRequest, -32600),
+        (ErrorCode::MethodNotFound, -32601),
+        (ErrorCode::InvalidParams, -32602),
+        (ErrorCode::InternalError, -32603),
+        (ErrorCode::ServerError(-32000), -32000),
+        (ErrorCode::ServerError(-32099), -32099),
+        (ErrorCode::Other(-32100), -32100),
+        (ErrorCode::Other(42), 42),
+    ] {
+        assert_eq!(ErrorCode::from(number), code);
+        assert_eq!(i32::from(code), number);
+        assert_eq!(serde_json::to_value(code).unwrap(), json!(number));
+    }
+}
+
+#[test]
+fn invalid_messages_are_rejected() {
+    for wire in [
+        json!({"jsonrpc": "2.0", "method": 1, "params": "bar"}),
+        json!({"jsonrpc": "1.0", "method": "x", "id": 1}),
+        json!({"method": "x", "id": 1}),
+        json!({"jsonrpc": "2.0", "result": 1, "error": {"code": 1, "message": "both"}, "id": 1}),
+        json!({"jsonrpc": "2.0", "result": 1}),
+        json!({"jsonrpc": "2.0", "method": "x", "result": 1, "id": 1}),
+        json!(1),
+    ] {
+        assert!(serde_json::from_value::<JsonRpcMessage>(wire.clone()).is_err(), "accepted {}", wire);
+    }
+}
+
+#[test]
+fn batches_hold_several_messages() {
+    let wire = json!([
+        {"jsonrpc": "2.0", "method": "sum", "params": [1, 2, 4], "id": "1"},
+        {"jsonrpc": "2.0", "method": "notify_hello", "params": [7]},
+        {"jsonrpc": "2.0", "result": 19, "id": "2"},
+        {"jsonrpc": "2.0", "error": {"code": -32600, "message": "Invalid Request"}, "id": null}
+    ]);
+    let frame: JsonRpcFrame = serde_json::from_value(wire.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&frame).unwrap(), wire);
+
+    let messages = frame.into_messages();
+    assert!(matches!(messages[0], JsonRpcMessage::Request(_)));
+    assert!(matches!(messages[1], JsonRpcMessage::Notification(_)));
+    assert!(matches!(messages[2], JsonRpcMessage::Response(_)));
+    assert!(matches!(messages[3], JsonRpcMessage::Error(_)));
+
+    // A single message is a frame too
+    let single: JsonRpcFrame = serde_json::from_value(json!({"jsonrpc": "2.0", "method": "foobar"})).unwrap();
+    assert_eq!(single.into_messages().len(), 1);
+
+    // The spec's invalid batches
+    assert!(serde_json::from_value::<JsonRpcFrame>(json!([])).is_err());
+    assert!(serde_json::from_value::<JsonRpcFrame>(json!([1, 2, 3])).is_err());
+}
+
+#[test]
+fn content_blocks_are_tagged_by_type() {
+    let blocks: Vec<ContentBlock> = serde_json::from_value(json!([
+        { "type": "text", "text": "hi" },
+        { "type": "image", "data": "AAAA", "mimeType": "image/png" },
+        { "type": "resource", "resource": { "uri": "file:///a.txt", "mimeType": "text/plain", "text": "A" } },
+        { "type": "hologram", "frames": 3 }
+    ])).unwrap();
+
+    assert_eq!(blocks[0], ContentBlock::Text { text: "hi".to_string() });
+    assert!(matches!(&blocks[1], ContentBlock::Image { mime_type, .. } if mime_type == "image/png"));
+    assert!(matches!(&blocks[2], ContentBlock::Resource { resource: ResourceContents::Text { text, .. } } if text == "A"));
+    assert_eq!(blocks[3], ContentBlock::Unknown);
+}
+
+#[test]
+fn prompt_arguments_default_to_optional() {
+    let prompt: Prompt = serde_json::from_value(json!({
+        "name": "review",
+        "arguments": [{ "name": "code", "required": true }, { "name": "focus" }]
+    })).unwrap();
+    assert!(prompt.arguments[0].required);
+    assert!(!prompt.arguments[1].required);
+
+    let bare: Prompt = serde_json::from_value(json!({ "name": "hello" })).unwrap();
+    assert!(bare.arguments.is_empty());
+}
+
+#[test]
+fn server_capabilities_are_typed() {
+    let result: InitializeResult = serde_json::from_value(json!({
+        "protocolVersion": "2025-06-18",
+        "capabilities": {
+            "logging": {},
+            "resources": { "subscribe": true },
+            "tools": { "listChanged": true },
+            "somethingNew": {}
+        },
+        "serverInfo": { "name": "typed", "version": "1" },
+        "instructions": "Call `search` first."
+    })).unwrap();
+
+    let caps = &result.capabilities;
+    assert!(caps.has("logging") && caps.has("resources") && caps.has("tools"));
+    assert!(!caps.has("prompts") && !caps.has("completions"));
+    assert!(caps.resources.as_ref().unwrap().subscribe);
+    assert!(!caps.resources.as_ref().unwrap().list_changed);
+    assert!(caps.tools.as_ref().unwrap().list_changed);
+    assert_eq!(result.instructions.as_deref(), Some("Call `search` first."));
+
+    assert_eq!(ServerCapabilities::required_for("resources/templates/list"), Some("resources"));
+    assert_eq!(ServerCapabilities::required_for("completion/complete"), Some("completions"));
+    assert_eq!(ServerCapabilities::required_for("ping"), None);
+    assert_eq!(LATEST_PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS[0]);
+}
+
+#[test]
+fn log_levels_parse_and_order() {
+    assert_eq!("Warning".parse::<LoggingLevel>(), Ok(LoggingLevel::Warning));
+    assert!("loud".parse::<LoggingLevel>().unwrap_err().contains("debug, info, notice"));
+    assert!(LoggingLevel::Debug < LoggingLevel::Error && LoggingLevel::Error < LoggingLevel::Emergency);
+
+    let message: LoggingMessageNotification = serde_json::from_value(json!({
+        "level": "error", "data": { "error": "Connection failed", "attempt": 3 }
+    })).unwrap();
+    assert_eq!(message.level, LoggingLevel::Error);
+    assert_eq!(message.logger, None);
+    assert_eq!(message.text(), r#"{"attempt":3,"error":"Connection failed"}"#);
+}
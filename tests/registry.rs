@@ -0,0 +1,295 @@
+// tests/registry.rs
+// Runs the registry against real mock_tool child processes.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use aether::config::ServerConfig;
+use aether::protocol::{ContentBlock, LoggingLevel, ResourceContents};
+use aether::registry::ServerRegistry;
+use aether::security::SecurityConfig;
+use aether::supervisor::{RestartPolicy, ServerHealth};
+use serde_json::json;
+
+fn mock(name: &str) -> ServerConfig {
+    ServerConfig::stdio(name, env!("CARGO_BIN_EXE_mock_tool"), &[])
+}
+
+fn allow_all() -> SecurityConfig {
+    SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn single_server_keeps_bare_tool_names() {
+    let registry = ServerRegistry::start(&[mock("math")], &allow_all()).await.unwrap();
+
+    let tools = registry.tools();
+    let names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["calculate_sum"]);
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn colliding_tools_are_namespaced_and_routed() {
+    let registry = ServerRegistry::start(&[mock("left"), mock("right")], &allow_all()).await.unwrap();
+
+    let tools = registry.tools();
+    let mut names: Vec<&str> = tools.iter().map(|t| t.name.as_str()).collect();
+    names.sort();
+    assert_eq!(names, vec!["left__calculate_sum", "right__calculate_sum"]);
+
+    let result = registry.call_tool("right__calculate_sum", json!({ "a": 2, "b": 3 })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "The sum is 5");
+
+    assert!(registry.call_tool("calculate_sum", json!({})).await.is_err());
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn permissions_follow_the_exposed_names() {
+    // One server's copy is blocked; a bare rule doesn't reach namespaced tools
+    let security = SecurityConfig {
+        rules: HashMap::from([
+            ("left__calculate_sum".to_string(), "deny".to_string()),
+            ("calculate_sum".to_string(), "deny".to_string()),
+        ]),
+        ..allow_all()
+    };
+    let registry = ServerRegistry::start(&[mock("left"), mock("right")], &security).await.unwrap();
+
+    let err = registry.call_tool("left__calculate_sum", json!({ "a": 1, "b": 1 })).await.unwrap_err();
+    assert!(err.to_string().contains("blocked by permissions.json"), "{}", err);
+    let result = registry.call_tool("right__calculate_sum", json!({ "a": 1, "b": 1 })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "The sum is 2");
+    registry.close().await;
+
+    // A lone server's tool answers to its bare name and to server__tool
+    let registry = ServerRegistry::start(&[mock("left")], &security).await.unwrap();
+    let err = registry.call_tool("calculate_sum", json!({ "a": 1, "b": 1 })).await.unwrap_err();
+    assert!(err.to_string().contains("blocked by permissions.json"), "{}", err);
+    registry.close().await;
+
+    let security = SecurityConfig {
+        rules: HashMap::from([("left__calculate_sum".to_string(), "allow".to_string())]),
+        global_policy: "deny".to_string(),
+        ..allow_all()
+    };
+    let registry = ServerRegistry::start(&[mock("left")], &security).await.unwrap();
+    assert!(registry.call_tool("calculate_sum", json!({ "a": 1, "b": 1 })).await.is_ok());
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn broken_servers_are_reported_not_fatal() {
+    let mut broken = mock("broken");
+    broken.command = Some("/definitely/not/a/real/binary".to_string());
+
+    let registry = ServerRegistry::start(&[broken, mock("ok")], &allow_all()).await.unwrap();
+    assert_eq!(registry.server_names(), vec!["ok"]);
+    assert_eq!(registry.failures()[0].0, "broken");
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn resources_are_listed_read_and_watched() {
+    let registry = ServerRegistry::start(&[mock("notes")], &allow_all()).await.unwrap();
+
+    // Both pages, plus the template
+    let offers = registry.resources();
+    assert_eq!(offers.len(), 1);
+    let uris: Vec<&str> = offers[0].resources.iter().map(|r| r.uri.as_str()).collect();
+    assert_eq!(uris, vec!["mock://notes/readme", "mock://images/pixel"]);
+    assert_eq!(offers[0].templates[0].uri_template, "mock://notes/{name}");
+
+    let text = registry.read_resource("notes", "mock://notes/readme").await.unwrap();
+    assert!(matches!(&text[0], ResourceContents::Text { text, .. } if text == "Hello from MockTool"));
+    let blob = registry.read_resource("notes", "mock://images/pixel").await.unwrap();
+    assert!(matches!(&blob[0], ResourceContents::Blob { .. }));
+    assert_eq!(blob[0].mime_type(), Some("image/png"));
+
+    assert!(registry.read_resource("notes", "mock://nowhere").await.is_err());
+    assert!(registry.read_resource("nobody", "mock://notes/readme").await.is_err());
+
+    // The mock confirms a subscription with an immediate update
+    let mut updates = registry.resource_updates();
+    registry.subscribe_resource("notes", "mock://notes/readme").await.unwrap();
+    let update = tokio::time::timeout(Duration::from_secs(5), updates.recv()).await.unwrap().unwrap();
+    assert_eq!((update.server.as_str(), update.uri.as_str()), ("notes", "mock://notes/readme"));
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn prompts_are_listed_and_filled_in() {
+    let registry = ServerRegistry::start(&[mock("canned")], &allow_all()).await.unwrap();
+
+    let offers = registry.prompts();
+    let names: Vec<&str> = offers[0].prompts.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, vec!["review", "hello"]);
+    let review = &offers[0].prompts[0];
+    assert!(review.arguments[0].required);
+    assert!(!review.arguments[1].required);
+
+    let arguments = HashMap::from([("code".to_string(), "fn main() {}".to_string())]);
+    let filled = registry.get_prompt("canned", "review", arguments).await.unwrap();
+    let roles: Vec<&str> = filled.messages.iter().map(|m| m.role.as_str()).collect();
+    assert_eq!(roles, vec!["assistant", "user"]);
+    assert!(matches!(&filled.messages[1].content, ContentBlock::Text { text } if text.ends_with("fn main() {}")));
+
+    // The server insists on its required arguments
+    assert!(registry.get_prompt("canned", "review", HashMap::new()).await.is_err());
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn log_levels_are_set_and_messages_forwarded() {
+    let registry = ServerRegistry::start(&[mock("chatty")], &allow_all()).await.unwrap();
+    let mut logs = registry.server_logs();
+
+    // The mock announces its new level in a log message
+    registry.set_log_level("chatty", LoggingLevel::Debug).await.unwrap();
+    let log = tokio::time::timeout(Duration::from_secs(5), logs.recv()).await.unwrap().unwrap();
+    assert_eq!(log.server, "chatty");
+    assert_eq!(log.message.level, LoggingLevel::Notice);
+    assert_eq!(log.message.logger.as_deref(), Some("mock"));
+    assert_eq!(log.message.text(), "Log level is now debug");
+
+    assert!(registry.set_log_level("nobody", LoggingLevel::Info).await.is_err());
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn tool_list_changes_are_refetched() {
+    // Announces a change before it even reads the handshake
+    let script = format!(
+        "printf '%s\\n' '{{\"jsonrpc\":\"2.0\",\"method\":\"notifications/tools/list_changed\"}}'; exec '{}'",
+        env!("CARGO_BIN_EXE_mock_tool"),
+    );
+    let changing = ServerConfig::stdio("changing", "sh", &["-c", &script]);
+
+    let registry = ServerRegistry::start(&[changing], &allow_all()).await.unwrap();
+    let mut changes = registry.tool_changes();
+    let change = tokio::time::timeout(Duration::from_secs(5), changes.recv()).await.unwrap().unwrap();
+    assert_eq!((change.server.as_str(), change.count), ("changing", 1));
+    assert_eq!(registry.tools()[0].name, "calculate_sum");
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn server_without_tools_is_not_asked_for_them() {
+    // Answers initialize with no capabilities at all, then never replies again
+    let script = concat!(
+        "IFS= read -r line; id=$(printf '%s' \"$line\" | sed 's/.*\"id\":\\([0-9]*\\).*/\\1/'); ",
+        "printf '{\"jsonrpc\":\"2.0\",\"id\":%s,\"result\":{\"protocolVersion\":\"2025-06-18\",",
+        "\"capabilities\":{},\"serverInfo\":{\"name\":\"bare\",\"version\":\"1\"}}}\\n' \"$id\"; cat >/dev/null",
+    );
+    let bare = ServerConfig::stdio("bare", "sh", &["-c", script]);
+
+    let registry = tokio::time::timeout(
+        Duration::from_secs(5),
+        ServerRegistry::start(&[bare, mock("math")], &allow_all()),
+    ).await.unwrap().unwrap();
+    assert!(registry.failures().is_empty(), "{:?}", registry.failures());
+    let names: Vec<String> = registry.tools().into_iter().map(|t| t.name).collect();
+    assert_eq!(names, vec!["calculate_sum"]);
+    registry.close().await;
+}
+
+// A server that answers the handshake and then exits (stdin is cut after
+// initialize, notifications/initialized and tools/list). With a marker file it
+// only crashes the first time.
+fn flaky(name: &str, marker: Option<&std::path::Path>) -> ServerConfig {
+    let mock = env!("CARGO_BIN_EXE_mock_tool");
+    let three_lines = "i=0; while [ $i -lt 3 ] && IFS= read -r line; do printf '%s\\n' \"$line\"; i=$((i+1)); done";
+    let script = match marker {
+        Some(marker) => format!(
+            "if [ -e '{m}' ]; then exec '{mock}'; else touch '{m}'; {three_lines} | '{mock}'; fi",
+            m = marker.display(),
+        ),
+        None => format!("{} | '{}'", three_lines, mock),
+    };
+    ServerConfig::stdio(name, "sh", &["-c", &script])
+}
+
+fn fast_policy(max_failures: usize) -> RestartPolicy {
+    RestartPolicy {
+        initial_backoff: Duration::from_millis(50),
+        max_backoff: Duration::from_millis(200),
+        max_failures,
+        window: Duration::from_secs(60),
+    }
+}
+
+#[tokio::test]
+async fn crashed_server_is_restarted() {
+    let marker = std::env::temp_dir().join(format!("aether-flaky-{}", std::process::id()));
+    let _ = std::fs::remove_file(&marker);
+
+    let registry = ServerRegistry::start_with_policy(
+        &[flaky("flaky", Some(&marker))], &allow_all(), fast_policy(5),
+    ).await.unwrap();
+
+    // The first process dies right after the handshake; the restarted one stays up
+    let result = tokio::time::timeout(Duration::from_secs(10), async {
+        loop {
+            if let Ok(result) = registry.call_tool("calculate_sum", json!({ "a": 1, "b": 1 })).await {
+                return result;
+            }
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }).await.unwrap();
+
+    assert_eq!(result["content"][0]["text"], "The sum is 2");
+    assert_eq!(registry.health()[0].1, ServerHealth::Healthy);
+    registry.close().await;
+    let _ = std::fs::remove_file(&marker);
+}
+
+#[tokio::test]
+async fn crash_looping_server_is_marked_unhealthy() {
+    let registry = ServerRegistry::start_with_policy(
+        &[flaky("looping", None)], &allow_all(), fast_policy(2),
+    ).await.unwrap();
+
+    tokio::time::timeout(Duration::from_secs(10), async {
+        while !matches!(registry.health()[0].1, ServerHealth::Unhealthy { .. }) {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }).await.unwrap();
+
+    let err = registry.call_tool("calculate_sum", json!({ "a": 1, "b": 1 })).await.unwrap_err();
+    assert!(err.to_string().contains("unhealthy"));
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn startup_errors_include_stderr() {
+    let noisy = ServerConfig::stdio("noisy", "sh", &["-c", "echo 'missing API key' >&2; exit 3"]);
+
+    let registry = ServerRegistry::start(&[noisy], &allow_all()).await.unwrap();
+    let (name, reason) = &registry.failures()[0];
+    assert_eq!(name, "noisy");
+    assert!(reason.contains("missing API key"), "{}", reason);
+}
+
+#[tokio::test]
+async fn chatty_stderr_does_not_block_the_server() {
+    // ~200KB of stderr before the server even starts reading: far more than a pipe holds
+    let script = format!(
+        "i=0; while [ $i -lt 2000 ]; do echo \"log line $i: {}\" >&2; i=$((i+1)); done; exec '{}'",
+        "x".repeat(80),
+        env!("CARGO_BIN_EXE_mock_tool"),
+    );
+    let chatty = ServerConfig::stdio("chatty", "sh", &["-c", &script]);
+
+    let registry = tokio::time::timeout(
+        Duration::from_secs(10),
+        ServerRegistry::start(&[chatty], &allow_all()),
+    ).await.unwrap().unwrap();
+
+    let result = registry.call_tool("calculate_sum", json!({ "a": 2, "b": 2 })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "The sum is 4");
+    registry.close().await;
+}
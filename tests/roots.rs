@@ -0,0 +1,122 @@
+// tests/roots.rs
+// The shared root set, file URIs, and the path fence in the security layer.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use aether::roots::{file_uri, normalize, path_from_uri, Roots};
+use aether::security::SecurityConfig;
+use serde_json::json;
+
+fn allow_all() -> SecurityConfig {
+    SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    }
+}
+
+#[test]
+fn paths_are_made_absolute_and_clean() {
+    let cwd = normalize(&std::env::current_dir().unwrap());
+    assert_eq!(normalize(Path::new("a/./b/../c")), cwd.join("a/c"));
+    assert_eq!(normalize(Path::new("/no/such/dir/../file")), PathBuf::from("/no/such/file"));
+}
+
+#[test]
+fn file_uris_round_trip() {
+    let path = Path::new("/home/me/My Project/100%.txt");
+    let uri = file_uri(path);
+    assert_eq!(uri, "file:///home/me/My%20Project/100%25.txt");
+    assert_eq!(path_from_uri(&uri).as_deref(), Some(path));
+    assert_eq!(path_from_uri("file://localhost/tmp").as_deref(), Some(Path::new("/tmp")));
+    assert_eq!(path_from_uri("https://example.com/tmp"), None);
+    assert_eq!(path_from_uri("file:///bad%zz"), None);
+}
+
+#[test]
+fn roots_change_and_notify() {
+    let roots = Roots::new([PathBuf::from("/srv/a"), PathBuf::from("/srv/a/"), PathBuf::from("/srv/b")]);
+    assert_eq!(roots.dirs(), vec![PathBuf::from("/srv/a"), PathBuf::from("/srv/b")]);
+
+    let changes = roots.subscribe();
+    assert!(!roots.add(Path::new("/srv/b")));
+    assert!(!changes.has_changed().unwrap());
+
+    assert!(roots.add(Path::new("/srv/c")));
+    assert!(changes.has_changed().unwrap());
+    assert!(roots.remove(Path::new("/srv/a")));
+    assert!(!roots.remove(Path::new("/srv/a")));
+
+    let list = roots.list();
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[0].uri, "file:///srv/b");
+    assert_eq!(list[0].name.as_deref(), Some("b"));
+}
+
+#[test]
+fn containment_follows_components() {
+    let roots = Roots::new([PathBuf::from("/srv/project")]);
+    assert!(roots.contains(Path::new("/srv/project")));
+    assert!(roots.contains(Path::new("/srv/project/src/lib.rs")));
+    assert!(!roots.contains(Path::new("/srv/project-old/secrets")));
+    assert!(!roots.contains(Path::new("/srv/project/../other")));
+}
+
+#[test]
+fn the_fence_checks_path_arguments_only() {
+    let security = allow_all();
+    let project = Path::new("/srv/project");
+    let roots = Roots::new([project.to_path_buf()]);
+
+    let inside = json!({ "path": "/srv/project/a.txt", "uri": "file:///srv/project/b.txt" });
+    assert_eq!(security.path_outside_roots(&inside, &roots, project), None);
+
+    let nested = json!({ "edits": [{ "filePath": "/etc/passwd" }] });
+    assert_eq!(security.path_outside_roots(&nested, &roots, project).as_deref(), Some("/etc/passwd"));
+
+    let uri = json!({ "target": "file:///etc/shadow" });
+    assert_eq!(security.path_outside_roots(&uri, &roots, project).as_deref(), Some("file:///etc/shadow"));
+
+    // Absolute-looking strings under other names are left alone
+    let route = json!({ "endpoint": "/api/v1", "paths": ["relative/ok.txt"] });
+    assert_eq!(security.path_outside_roots(&route, &roots, project), None);
+
+    // No roots, no fence
+    assert_eq!(security.path_outside_roots(&nested, &Roots::default(), project), None);
+}
+
+#[test]
+fn the_fence_resolves_relative_and_home_paths() {
+    let security = allow_all();
+    let project = Path::new("/srv/project");
+    let roots = Roots::new([project.to_path_buf()]);
+
+    // Relative paths are where the server will look for them
+    let inside = json!({ "path": "src/main.rs", "dirs": ["."] });
+    assert_eq!(security.path_outside_roots(&inside, &roots, project), None);
+    let escape = json!({ "path": "../../etc/passwd" });
+    assert_eq!(security.path_outside_roots(&escape, &roots, project).as_deref(), Some("../../etc/passwd"));
+    let relative = json!({ "path": "src/main.rs" });
+    assert_eq!(security.path_outside_roots(&relative, &roots, Path::new("/srv/other")).as_deref(), Some("src/main.rs"));
+
+    // ~ is the home directory, not a folder called "~"
+    let home = PathBuf::from(std::env::var_os("HOME").unwrap());
+    let dotfile = json!({ "file": "~/.ssh/id_ed25519" });
+    assert_eq!(security.path_outside_roots(&dotfile, &roots, project).as_deref(), Some("~/.ssh/id_ed25519"));
+    assert_eq!(security.path_outside_roots(&dotfile, &Roots::new([home.join(".ssh")]), project), None);
+}
+
+#[test]
+fn the_fence_matches_whole_words_in_argument_names() {
+    let security = allow_all();
+    let project = Path::new("/srv/project");
+    let roots = Roots::new([project.to_path_buf()]);
+
+    for key in ["path", "filePath", "source_dir", "output-file", "CWD", "folders"] {
+        let arguments = json!({ key: "/etc" });
+        assert_eq!(security.path_outside_roots(&arguments, &roots, project).as_deref(), Some("/etc"), "{}", key);
+    }
+    // Merely containing "file" or "dir" isn't enough, and URLs aren't local paths
+    let unrelated = json!({ "profile": "/etc", "direction": "../up", "filePath": "https://example.com/a" });
+    assert_eq!(security.path_outside_roots(&unrelated, &roots, project), None);
+}
@@ -0,0 +1,71 @@
+// tests/runtime.rs
+// Shutting down real child processes that don't want to go.
+#![cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+use std::time::{Duration, Instant};
+
+use aether::runtime::{LaunchOptions, McpProcess};
+use aether::transport::Transport;
+
+const GRACE: Duration = Duration::from_millis(300);
+
+#[tokio::test]
+async fn well_behaved_servers_exit_on_eof() {
+    let process = McpProcess::start(env!("CARGO_BIN_EXE_mock_tool"), &[]).unwrap();
+
+    let started = Instant::now();
+    process.shutdown(GRACE).await.unwrap();
+    assert!(started.elapsed() < GRACE);
+    assert!(process.exit_status().await.unwrap().success());
+}
+
+#[tokio::test]
+async fn servers_ignoring_eof_get_sigterm() {
+    let process = McpProcess::start("sh", &["-c", "sleep 5 & wait"]).unwrap();
+
+    process.shutdown(GRACE).await.unwrap();
+    assert_eq!(process.exit_status().await.unwrap().signal(), Some(libc::SIGTERM));
+}
+
+#[tokio::test]
+async fn servers_ignoring_sigterm_get_killed() {
+    let process = McpProcess::start("sh", &["-c", "trap '' TERM; sleep 5 & wait"]).unwrap();
+
+    let started = Instant::now();
+    process.shutdown(GRACE).await.unwrap();
+    assert!(started.elapsed() >= GRACE * 2);
+    assert_eq!(process.exit_status().await.unwrap().signal(), Some(libc::SIGKILL));
+}
+
+#[tokio::test]
+async fn children_get_a_scrubbed_environment() {
+    std::env::set_var("AETHER_TEST_SECRET", "hunter2");
+    std::env::set_var("AETHER_TEST_SHARED", "shared");
+
+    let options = LaunchOptions {
+        env: [("EXPLICIT".to_string(), "yes".to_string())].into(),
+        inherit_env: vec!["AETHER_TEST_SHARED".to_string()],
+        ..LaunchOptions::default()
+    };
+    let script = "echo ${AETHER_TEST_SECRET:-scrubbed} $AETHER_TEST_SHARED $EXPLICIT ${PATH:+has-path}";
+    let process = McpProcess::start_with("sh", &["-c", script], &options).unwrap();
+
+    assert_eq!(process.receive().await.unwrap().trim(), "scrubbed shared yes has-path");
+}
+
+#[tokio::test]
+async fn mock_tool_echoes_ids_and_skips_notifications() {
+    let process = McpProcess::start(env!("CARGO_BIN_EXE_mock_tool"), &[]).unwrap();
+
+    // A notification gets no reply, so the first line back answers the ping
+    process.send(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await.unwrap();
+    process.send(r#"{"jsonrpc":"2.0","id":"ping-7","method":"ping"}"#).await.unwrap();
+    let reply: serde_json::Value = serde_json::from_str(&process.receive().await.unwrap()).unwrap();
+    assert_eq!(reply["id"], "ping-7");
+
+    // Broken JSON: a parse error with a null id, not a made-up one
+    process.send("{oops").await.unwrap();
+    let reply: serde_json::Value = serde_json::from_str(&process.receive().await.unwrap()).unwrap();
+    assert_eq!(reply["error"]["code"], -32700);
+    assert!(reply["id"].is_null());
+}
@@ -0,0 +1,171 @@
+// tests/sampling.rs
+// Sampling requests against a local axum stand-in for the LLM provider.
+use std::sync::{Arc, Mutex};
+
+use aether::client::RequestHandler;
+use aether::llm::LlmClient;
+use aether::protocol::{CreateMessageResult, ContentBlock, ErrorCode, ModelHint, ModelPreferences};
+use aether::sampling::{choose_model, Approval, SamplingHandler};
+use aether::tui::UiMessage;
+use axum::{extract::State, routing::post, Json, Router};
+use serde_json::{json, Value};
+use tokio::sync::mpsc;
+
+type Seen = Arc<Mutex<Vec<Value>>>;
+
+async fn handle_completion(State(seen): State<Seen>, Json(body): Json<Value>) -> Json<Value> {
+    let model = body["model"].clone();
+    seen.lock().unwrap().push(body);
+    Json(json!({
+        "model": model,
+        "choices": [{
+            "message": { "role": "assistant", "content": "Paris" },
+            "finish_reason": "stop"
+        }]
+    }))
+}
+
+async fn spawn_llm() -> (LlmClient, Seen) {
+    let seen: Seen = Arc::default();
+    let app = Router::new()
+        .route("/v1/chat/completions", post(handle_completion))
+        .with_state(seen.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    std::env::set_var("GROQ_API_KEY", "test-key");
+    let llm = LlmClient::new("big-model").unwrap()
+        .with_endpoint(&format!("http://{}/v1/chat/completions", addr));
+    (llm, seen)
+}
+
+fn candidates() -> Vec<String> {
+    vec!["llama-3.3-70b".to_string(), "llama-3.1-8b-instant".to_string()]
+}
+
+fn request() -> Value {
+    json!({
+        "messages": [{ "role": "user", "content": { "type": "text", "text": "Capital of France?" } }],
+        "systemPrompt": "Answer in one word.",
+        "modelPreferences": { "hints": [{ "name": "8b" }] },
+        "maxTokens": 20,
+        "stopSequences": ["\n"]
+    })
+}
+
+// Answers the next approval prompt the way the user would
+async fn answer_next(rx_ui: &mut mpsc::UnboundedReceiver<UiMessage>, approved: bool) -> Approval {
+    loop {
+        if let UiMessage::Approval(approval) = rx_ui.recv().await.unwrap() {
+            approval.answer(approved);
+            return approval;
+        }
+    }
+}
+
+#[test]
+fn hints_pick_a_matching_model() {
+    let preferences = ModelPreferences {
+        hints: vec![ModelHint { name: Some("claude".to_string()) }, ModelHint { name: Some("8B".to_string()) }],
+        ..ModelPreferences::default()
+    };
+    assert_eq!(choose_model(Some(&preferences), "default", &candidates()), "llama-3.1-8b-instant");
+}
+
+#[test]
+fn priorities_pick_cheap_or_capable_models() {
+    let cheap = ModelPreferences { cost_priority: Some(0.9), intelligence_priority: Some(0.2), ..ModelPreferences::default() };
+    let fast = ModelPreferences { speed_priority: Some(0.8), ..ModelPreferences::default() };
+    let smart = ModelPreferences { intelligence_priority: Some(1.0), cost_priority: Some(0.3), ..ModelPreferences::default() };
+    let even = ModelPreferences { intelligence_priority: Some(0.5), cost_priority: Some(0.5), ..ModelPreferences::default() };
+
+    assert_eq!(choose_model(Some(&cheap), "default", &candidates()), "llama-3.1-8b-instant");
+    assert_eq!(choose_model(Some(&fast), "default", &candidates()), "llama-3.1-8b-instant");
+    assert_eq!(choose_model(Some(&smart), "default", &candidates()), "llama-3.3-70b");
+    assert_eq!(choose_model(Some(&even), "default", &candidates()), "default");
+}
+
+#[test]
+fn without_preferences_or_candidates_the_default_is_used() {
+    let smart = ModelPreferences { intelligence_priority: Some(1.0), ..ModelPreferences::default() };
+    assert_eq!(choose_model(None, "default", &candidates()), "default");
+    assert_eq!(choose_model(Some(&smart), "default", &[]), "default");
+}
+
+#[tokio::test]
+async fn only_the_first_answer_counts() {
+    let (approval, answer) = Approval::new("math", "Capital of France?");
+    let copy = approval.clone();
+    approval.answer(true);
+    copy.answer(false);
+    assert_eq!(answer.await, Ok(true));
+
+    // Dropped unanswered means no answer at all
+    let (approval, answer) = Approval::new("math", "Capital of France?");
+    drop(approval);
+    assert!(answer.await.is_err());
+}
+
+#[tokio::test]
+async fn approved_requests_go_to_the_llm() {
+    let (llm, seen) = spawn_llm().await;
+    let (tx_ui, mut rx_ui) = mpsc::unbounded_channel();
+    let handler = SamplingHandler::new("math", llm, candidates(), tx_ui);
+    assert!(handler.capabilities().sampling.is_some());
+
+    let (result, approval) = tokio::join!(
+        handler.handle("sampling/createMessage", Some(request())),
+        answer_next(&mut rx_ui, true),
+    );
+    assert_eq!(approval.server, "math");
+    assert!(approval.summary.contains("Capital of France?"));
+
+    let result: CreateMessageResult = serde_json::from_value(result.unwrap()).unwrap();
+    assert_eq!(result.role, "assistant");
+    assert_eq!(result.model, "llama-3.1-8b-instant");
+    assert_eq!(result.stop_reason.as_deref(), Some("endTurn"));
+    assert!(matches!(result.content, ContentBlock::Text { ref text } if text == "Paris"));
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0]["model"], "llama-3.1-8b-instant");
+    assert_eq!(seen[0]["max_tokens"], 20);
+    assert_eq!(seen[0]["stop"], json!(["\n"]));
+    assert_eq!(seen[0]["messages"][0], json!({ "role": "system", "content": "Answer in one word." }));
+    assert_eq!(seen[0]["messages"][1]["content"], "Capital of France?");
+}
+
+#[tokio::test]
+async fn denied_requests_never_reach_the_llm() {
+    let (llm, seen) = spawn_llm().await;
+    let (tx_ui, mut rx_ui) = mpsc::unbounded_channel();
+    let handler = SamplingHandler::new("math", llm, candidates(), tx_ui);
+
+    let (result, _) = tokio::join!(
+        handler.handle("sampling/createMessage", Some(request())),
+        answer_next(&mut rx_ui, false),
+    );
+    let error = result.unwrap_err();
+    assert_eq!(error.code, ErrorCode::from(-1));
+    assert!(seen.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn malformed_requests_are_invalid_params() {
+    let (llm, _) = spawn_llm().await;
+    let (tx_ui, _rx_ui) = mpsc::unbounded_channel();
+    let handler = SamplingHandler::new("math", llm, candidates(), tx_ui);
+
+    let missing = handler.handle("sampling/createMessage", Some(json!({ "messages": [] }))).await;
+    assert_eq!(missing.unwrap_err().code, ErrorCode::InvalidParams);
+
+    let mut bad_role = request();
+    bad_role["messages"][0]["role"] = json!("system");
+    let bad_role = handler.handle("sampling/createMessage", Some(bad_role)).await;
+    assert_eq!(bad_role.unwrap_err().code, ErrorCode::InvalidParams);
+
+    // Everything else still gets the default answers
+    assert_eq!(handler.handle("ping", None).await.unwrap(), json!({}));
+}
@@ -0,0 +1,211 @@
+// tests/sandbox.rs
+// Runs real processes inside the sandbox and checks what they can see.
+// Needs unprivileged user namespaces (the tests skip themselves without).
+#![cfg(target_os = "linux")]
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use aether::client::{DefaultRequestHandler, RequestHandler};
+use aether::config::{SandboxConfig, ServerConfig};
+use aether::registry::{HandlerFactory, ServerRegistry};
+use aether::roots::Roots;
+use aether::runtime::{LaunchOptions, McpProcess};
+use aether::sandbox;
+use aether::security::SecurityConfig;
+use aether::supervisor::RestartPolicy;
+use aether::transport::Transport;
+use serde_json::json;
+
+fn namespaces_available() -> bool {
+    std::process::Command::new("unshare")
+        .args(["--user", "--map-root-user", "true"])
+        .status()
+        .is_ok_and(|s| s.success())
+}
+
+// Run a shell script in the sandbox and collect its stdout
+async fn sandboxed_sh(script: &str, profile: SandboxConfig) -> Vec<String> {
+    let options = LaunchOptions { sandbox: Some(profile), ..LaunchOptions::default() };
+    let process = McpProcess::start_with("sh", &["-c", script], &options).unwrap();
+
+    let mut lines = Vec::new();
+    while let Ok(line) = process.receive().await {
+        lines.push(line.trim_end().to_string());
+    }
+    lines
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("aether-sandbox-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[tokio::test]
+async fn server_gets_its_own_pid_and_network_namespace() {
+    if !namespaces_available() {
+        return;
+    }
+    let lines = sandboxed_sh(
+        "echo $$; tail -n +3 /proc/net/dev | cut -d: -f1 | tr -d ' '",
+        SandboxConfig::default(),
+    ).await;
+    assert_eq!(lines, vec!["1", "lo"]);
+}
+
+#[tokio::test]
+async fn filesystem_access_is_limited_to_the_profile() {
+    if !namespaces_available() || sandbox::landlock_abi().is_none() {
+        return;
+    }
+    let allowed = scratch_dir("allowed");
+    let denied = scratch_dir("denied");
+    let profile = SandboxConfig { writable: vec![allowed.clone()], ..SandboxConfig::default() };
+
+    let script = format!(
+        "echo ok > '{}/f' && echo wrote-allowed; echo no > '{}/f' 2>/dev/null || echo blocked-denied",
+        allowed.display(),
+        denied.display(),
+    );
+    let lines = sandboxed_sh(&script, profile).await;
+    assert_eq!(lines, vec!["wrote-allowed", "blocked-denied"]);
+    assert!(!denied.join("f").exists());
+
+    let _ = std::fs::remove_dir_all(allowed);
+    let _ = std::fs::remove_dir_all(denied);
+}
+
+#[tokio::test]
+async fn devices_and_the_terminal_are_out_of_reach() {
+    if !namespaces_available() || sandbox::landlock_abi().is_none() {
+        return;
+    }
+    let script = "cat /dev/tty >/dev/null 2>&1 && echo tty || echo no-tty; \
+                  ls /dev >/dev/null 2>&1 && echo dev || echo no-dev; \
+                  head -c 1 /dev/urandom >/dev/null && echo urandom; \
+                  echo session $(cut -d' ' -f6 /proc/self/stat)";
+    let lines = sandboxed_sh(script, SandboxConfig::default()).await;
+    assert_eq!(lines, vec!["no-tty", "no-dev", "urandom", "session 1"]);
+}
+
+#[tokio::test]
+async fn syscalls_off_the_allowlist_fail() {
+    if !namespaces_available() {
+        return;
+    }
+    // unshare(1) needs the unshare syscall, which is opt-in
+    let script = "unshare --user true 2>/dev/null && echo allowed || echo blocked";
+    assert_eq!(sandboxed_sh(script, SandboxConfig::default()).await, vec!["blocked"]);
+
+    let profile = SandboxConfig { allow_syscalls: vec!["unshare".to_string()], ..SandboxConfig::default() };
+    assert_eq!(sandboxed_sh(script, profile).await, vec!["allowed"]);
+}
+
+// Raw syscall numbers, so this one is x86_64 only
+#[cfg(target_arch = "x86_64")]
+#[tokio::test]
+async fn clone_cannot_create_namespaces() {
+    if !namespaces_available() || !std::path::Path::new("/usr/bin/perl").exists() {
+        return;
+    }
+    // clone(CLONE_NEWUSER | SIGCHLD) is refused, clone3 claims not to exist, a plain fork still works
+    let script = r#"perl -e '
+        use Errno;
+        print syscall(56, 0x10000000 | 17, 0, 0, 0, 0) == -1 && $!{EPERM} ? "no-newuser\n" : "newuser\n";
+        print syscall(435, 0, 0) == -1 && $!{ENOSYS} ? "no-clone3\n" : "clone3\n";
+        my $pid = fork; if ($pid == 0) { exit 0 } waitpid($pid, 0); print "forked\n";
+    '"#;
+    let lines = sandboxed_sh(script, SandboxConfig::default()).await;
+    assert_eq!(lines, vec!["no-newuser", "no-clone3", "forked"]);
+}
+
+#[tokio::test]
+async fn shutdown_sigterm_reaches_the_server() {
+    if !namespaces_available() || sandbox::landlock_abi().is_none() {
+        return;
+    }
+    let scratch = scratch_dir("sigterm");
+    let profile = SandboxConfig { writable: vec![scratch.clone()], ..SandboxConfig::default() };
+    let options = LaunchOptions { sandbox: Some(profile), ..LaunchOptions::default() };
+    // Ignores EOF on stdin and only leaves on SIGTERM, noting that it did
+    let script = format!(
+        "trap 'echo term > \"{}/got\"; exit 0' TERM; echo ready; while :; do sleep 0.05; done",
+        scratch.display(),
+    );
+    let process = McpProcess::start_with("sh", &["-c", &script], &options).unwrap();
+    assert_eq!(process.receive().await.unwrap().trim(), "ready");
+
+    process.shutdown(Duration::from_millis(300)).await.unwrap();
+    assert!(scratch.join("got").exists());
+    assert!(process.exit_status().await.unwrap().success());
+    let _ = std::fs::remove_dir_all(scratch);
+}
+
+#[tokio::test]
+async fn unknown_syscalls_are_rejected() {
+    let profile = SandboxConfig { allow_syscalls: vec!["not_a_syscall".to_string()], ..SandboxConfig::default() };
+    let options = LaunchOptions { sandbox: Some(profile), ..LaunchOptions::default() };
+
+    let err = McpProcess::start_with("true", &[], &options).err().unwrap();
+    assert!(err.to_string().contains("not_a_syscall"), "{}", err);
+}
+
+#[tokio::test]
+async fn sandboxed_mcp_server_still_works() {
+    if !namespaces_available() {
+        return;
+    }
+    let mut server = ServerConfig::stdio("caged", env!("CARGO_BIN_EXE_mock_tool"), &[]);
+    server.sandbox = Some(SandboxConfig::default());
+    let security = SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    };
+
+    let registry = ServerRegistry::start(&[server], &security).await.unwrap();
+    assert!(registry.failures().is_empty(), "{:?}", registry.failures());
+    let result = registry.call_tool("calculate_sum", json!({ "a": 4, "b": 5 })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "The sum is 9");
+    registry.close().await;
+}
+
+#[tokio::test]
+async fn roots_are_writable_only_when_the_profile_says_so() {
+    if !namespaces_available() || sandbox::landlock_abi().is_none() {
+        return;
+    }
+    let root = scratch_dir("root");
+    let mock = PathBuf::from(env!("CARGO_BIN_EXE_mock_tool"));
+    let handlers: HandlerFactory = Arc::new(|_: &str| -> Arc<dyn RequestHandler> { Arc::new(DefaultRequestHandler) });
+    let security = SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    };
+
+    // Tries to drop a file into the root before it starts serving
+    for (writable_roots, file) in [(false, "readonly"), (true, "writable")] {
+        let script = format!(
+            "touch '{root}/{file}' 2>/dev/null; exec '{}'",
+            mock.display(),
+            root = root.display(),
+        );
+        let mut server = ServerConfig::stdio("caged", "sh", &["-c", &script]);
+        server.sandbox = Some(SandboxConfig {
+            read_only: vec![mock.clone()],
+            writable_roots,
+            ..SandboxConfig::default()
+        });
+
+        let registry = ServerRegistry::start_with_handlers(
+            &[server], &security, RestartPolicy::default(), handlers.clone(), Roots::new([root.clone()]),
+        ).await.unwrap();
+        assert!(registry.failures().is_empty(), "{:?}", registry.failures());
+        assert_eq!(root.join(file).exists(), writable_roots, "{}", file);
+        registry.close().await;
+    }
+    let _ = std::fs::remove_dir_all(root);
+}
@@ -0,0 +1,249 @@
+// tests/schema.rs
+// The argument validator against both supported drafts.
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use aether::agent::parse_arguments;
+use aether::schema::{validate, Draft};
+use serde_json::{json, Value};
+
+// Just the messages, with their paths
+fn errors(schema: Value, instance: Value) -> Vec<String> {
+    validate(&schema, &instance).iter().map(ToString::to_string).collect()
+}
+
+// Collects what the validator logs
+#[derive(Clone, Default)]
+struct LogBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl io::Write for LogBuffer {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(bytes);
+        Ok(bytes.len())
+    }
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// The errors, plus every warning logged while validating
+fn errors_and_warnings(schema: Value, instance: Value) -> (Vec<String>, String) {
+    let buffer = LogBuffer::default();
+    let sink = buffer.clone();
+    let subscriber = tracing_subscriber::fmt().with_writer(move || sink.clone()).with_ansi(false).finish();
+    let errors = tracing::subscriber::with_default(subscriber, || errors(schema, instance));
+    let logged = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+    (errors, logged)
+}
+
+#[test]
+fn the_draft_comes_from_the_schema_keyword() {
+    assert_eq!(Draft::detect(&json!({ "$schema": "http://json-schema.org/draft-07/schema#" })), Draft::Draft7);
+    assert_eq!(Draft::detect(&json!({ "$schema": "http://json-schema.org/draft-04/schema#" })), Draft::Draft7);
+    assert_eq!(Draft::detect(&json!({ "$schema": "https://json-schema.org/draft/2020-12/schema" })), Draft::Draft2020);
+    assert_eq!(Draft::detect(&json!({})), Draft::Draft2020);
+}
+
+#[test]
+fn types_and_required_properties() {
+    let schema = json!({
+        "type": "object",
+        "properties": {
+            "count": { "type": "integer" },
+            "name": { "type": ["string", "null"] }
+        },
+        "required": ["count"]
+    });
+    assert!(errors(schema.clone(), json!({ "count": 3, "name": null })).is_empty());
+    assert!(errors(schema.clone(), json!({ "count": 3.0 })).is_empty());
+    assert_eq!(errors(schema.clone(), json!({ "count": 1.5 })), vec!["/count: expected integer, got number"]);
+    assert_eq!(errors(schema.clone(), json!({ "name": 7 })), vec![
+        "(root): missing required property 'count'",
+        "/name: expected string or null, got integer",
+    ]);
+    assert_eq!(errors(schema, json!([1])), vec!["(root): expected object, got array"]);
+}
+
+#[test]
+fn string_and_number_limits() {
+    let schema = json!({
+        "properties": {
+            "code": { "type": "string", "minLength": 2, "maxLength": 3, "pattern": "^[A-Z]+$" },
+            "ratio": { "minimum": 0, "exclusiveMaximum": 1 },
+            "step": { "multipleOf": 0.1 },
+            "mode": { "enum": ["fast", "slow"] },
+            "fixed": { "const": 1 }
+        }
+    });
+    assert!(errors(schema.clone(), json!({ "code": "AB", "ratio": 0, "step": 0.3, "mode": "fast", "fixed": 1.0 })).is_empty());
+    assert_eq!(errors(schema, json!({ "code": "abcd", "ratio": 1, "step": 0.25, "mode": "warp", "fixed": 2 })), vec![
+        "/code: must be at most 3 characters long",
+        "/code: must match the pattern '^[A-Z]+$'",
+        "/fixed: must be 1",
+        "/mode: must be one of \"fast\", \"slow\"",
+        "/ratio: must be < 1",
+        "/step: must be a multiple of 0.1",
+    ]);
+}
+
+#[test]
+fn extra_properties_and_pattern_properties() {
+    let schema = json!({
+        "properties": { "id": { "type": "string" } },
+        "patternProperties": { "^x-": { "type": "boolean" } },
+        "additionalProperties": false,
+        "propertyNames": { "maxLength": 5 }
+    });
+    assert!(errors(schema.clone(), json!({ "id": "a", "x-on": true })).is_empty());
+    assert_eq!(errors(schema, json!({ "x-on": 1, "others": 2, "a/b~c": 0 })), vec![
+        "(root): unexpected property 'a/b~c'",
+        "(root): unexpected property 'others'",
+        "(root): property name 'others' is not allowed",
+        "/x-on: expected boolean, got integer",
+    ]);
+}
+
+#[test]
+fn arrays_in_draft_2020() {
+    let schema = json!({
+        "type": "array",
+        "prefixItems": [{ "type": "string" }],
+        "items": { "type": "number" },
+        "minItems": 2,
+        "uniqueItems": true,
+        "contains": { "const": 5 }
+    });
+    assert!(errors(schema.clone(), json!(["x", 1, 5])).is_empty());
+    assert_eq!(errors(schema.clone(), json!([1, "x", 2.5])), vec![
+        "/0: expected string, got integer",
+        "/1: expected number, got string",
+        "(root): must contain at least 1 matching item(s), found 0",
+    ]);
+    assert_eq!(errors(schema, json!(["x", 5, 5])), vec!["(root): items must be unique"]);
+}
+
+#[test]
+fn arrays_in_draft_07() {
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "items": [{ "type": "string" }, { "type": "integer" }],
+        "additionalItems": false
+    });
+    assert!(errors(schema.clone(), json!(["x", 1])).is_empty());
+    assert_eq!(errors(schema.clone(), json!(["x", 1, 2])), vec!["(root): must have at most 2 items"]);
+
+    // A single `items` schema applies to every item
+    let schema = json!({ "$schema": "http://json-schema.org/draft-07/schema#", "items": { "type": "integer" } });
+    assert_eq!(errors(schema, json!([1, "two"])), vec!["/1: expected integer, got string"]);
+}
+
+#[test]
+fn combinators_and_conditionals() {
+    let schema = json!({
+        "properties": {
+            "id": { "anyOf": [{ "type": "string" }, { "type": "integer" }] },
+            "shape": { "oneOf": [{ "minimum": 0 }, { "maximum": 10 }] },
+            "tag": { "not": { "const": "banned" } }
+        },
+        "if": { "properties": { "kind": { "const": "file" } }, "required": ["kind"] },
+        "then": { "required": ["path"] },
+        "else": { "required": ["url"] }
+    });
+    assert!(errors(schema.clone(), json!({ "id": "a", "shape": -1, "kind": "file", "path": "/x" })).is_empty());
+    assert_eq!(errors(schema, json!({ "id": true, "shape": 5, "tag": "banned", "kind": "link" })), vec![
+        "(root): missing required property 'url'",
+        "/id: does not match any of the allowed alternatives (anyOf)",
+        "/shape: matches 2 alternatives, expected exactly one (oneOf)",
+        "/tag: matches a schema it must not match (not)",
+    ]);
+}
+
+#[test]
+fn dependencies_in_both_drafts() {
+    let modern = json!({
+        "dependentRequired": { "user": ["password"] },
+        "dependentSchemas": { "port": { "properties": { "port": { "maximum": 65535 } } } }
+    });
+    assert_eq!(errors(modern, json!({ "user": "me", "port": 70000 })), vec![
+        "(root): property 'user' requires property 'password'",
+        "/port: must be <= 65535",
+    ]);
+
+    let legacy = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "dependencies": { "user": ["password"] }
+    });
+    assert_eq!(errors(legacy, json!({ "user": "me" })), vec!["(root): property 'user' requires property 'password'"]);
+}
+
+#[test]
+fn local_references_are_followed() {
+    let schema = json!({
+        "$defs": { "point": { "type": "object", "required": ["x", "y"] } },
+        "properties": { "from": { "$ref": "#/$defs/point" }, "to": { "$ref": "#/$defs/point", "required": ["z"] } }
+    });
+    assert_eq!(errors(schema, json!({ "from": { "x": 1 }, "to": { "x": 1, "y": 2 } })), vec![
+        "/from: missing required property 'y'",
+        "/to: missing required property 'z'",
+    ]);
+
+    // In draft-07 the siblings of a $ref don't count
+    let schema = json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": { "name": { "type": "string" } },
+        "properties": { "name": { "$ref": "#/definitions/name", "minLength": 5 } }
+    });
+    assert!(errors(schema.clone(), json!({ "name": "ab" })).is_empty());
+    assert_eq!(errors(schema, json!({ "name": 1 })), vec!["/name: expected string, got integer"]);
+
+    // A ref loop or a remote ref doesn't hang or fail the call
+    assert!(errors(json!({ "$ref": "#" }), json!(1)).is_empty());
+    assert!(errors(json!({ "$ref": "https://example.com/schema.json" }), json!(1)).is_empty());
+}
+
+#[test]
+fn branching_ref_loops_give_up_quickly() {
+    // Every level doubles the work: 2^64 checks without a budget
+    let schema = json!({
+        "$defs": { "knot": { "anyOf": [{ "$ref": "#/$defs/knot" }, { "$ref": "#/$defs/knot" }], "not": { "type": "integer" } } },
+        "$ref": "#/$defs/knot"
+    });
+    let started = std::time::Instant::now();
+    let (errors, logged) = errors_and_warnings(schema, json!(1));
+    assert!(started.elapsed() < std::time::Duration::from_secs(5), "{:?}", started.elapsed());
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert!(logged.contains("gave up after"), "{}", logged);
+}
+
+#[test]
+fn keywords_that_cannot_be_enforced_are_logged() {
+    // Lookahead is fine in JavaScript but not in the regex crate
+    let schema = json!({
+        "properties": { "id": { "pattern": "^(?!tmp)" }, "to": { "$ref": "#/$defs/missing" } },
+        "patternProperties": { "(?<=x)y": { "type": "string" } }
+    });
+    let (errors, logged) = errors_and_warnings(schema, json!({ "id": "tmp1", "to": 1, "xy": 2 }));
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert!(logged.contains("/id: can't compile pattern '^(?!tmp)'"), "{}", logged);
+    assert!(logged.contains("/to: can't resolve $ref '#/$defs/missing'"), "{}", logged);
+    assert!(logged.contains("(root): can't compile patternProperties key '(?<=x)y'"), "{}", logged);
+}
+
+#[test]
+fn boolean_schemas() {
+    assert!(errors(json!(true), json!({ "anything": 1 })).is_empty());
+    assert_eq!(errors(json!(false), json!(1)), vec!["(root): no value is allowed here"]);
+    assert_eq!(
+        errors(json!({ "properties": { "legacy": false } }), json!({ "legacy": 1 })),
+        vec!["/legacy: no value is allowed here"]
+    );
+}
+
+#[test]
+fn argument_strings_must_be_json() {
+    assert_eq!(parse_arguments(" ").unwrap(), json!({}));
+    assert_eq!(parse_arguments("null").unwrap(), json!({}));
+    assert_eq!(parse_arguments(r#"{"a": 1}"#).unwrap(), json!({ "a": 1 }));
+    let err = parse_arguments(r#"{"a": 1"#).unwrap_err().to_string();
+    assert!(err.contains("not valid JSON"), "{}", err);
+}
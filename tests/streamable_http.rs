@@ -0,0 +1,197 @@
+// tests/streamable_http.rs
+// Drives the Streamable HTTP transport against a local axum stand-in server.
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use aether::client::McpClient;
+use aether::security::SecurityConfig;
+use aether::transport::sse::SseParser;
+use aether::transport::{StreamableHttpTransport, Transport};
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderMap, Response, StatusCode},
+    routing::post,
+    Router,
+};
+use serde_json::{json, Value};
+
+const SESSION: &str = "session-1234";
+
+#[derive(Default)]
+struct ServerState {
+    // Session id seen on every request after initialize
+    seen_sessions: Vec<Option<String>>,
+    // MCP-Protocol-Version seen on every request after initialize
+    seen_versions: Vec<Option<String>>,
+    deleted: bool,
+}
+
+type Shared = Arc<Mutex<ServerState>>;
+
+async fn handle_post(State(state): State<Shared>, headers: HeaderMap, body: String) -> Response<Body> {
+    let msg: Value = serde_json::from_str(&body).unwrap();
+    let method = msg["method"].as_str().unwrap_or("");
+    let session = headers.get("mcp-session-id").map(|v| v.to_str().unwrap().to_string());
+
+    if method != "initialize" {
+        let version = headers.get("mcp-protocol-version").map(|v| v.to_str().unwrap().to_string());
+        state.lock().unwrap().seen_versions.push(version);
+        state.lock().unwrap().seen_sessions.push(session.clone());
+        if session.as_deref() != Some(SESSION) {
+            return Response::builder().status(StatusCode::BAD_REQUEST).body(Body::empty()).unwrap();
+        }
+    }
+
+    // Notifications get 202 with no body
+    if msg.get("id").is_none() {
+        return Response::builder().status(StatusCode::ACCEPTED).body(Body::empty()).unwrap();
+    }
+    let id = msg["id"].clone();
+
+    match method {
+        "initialize" => {
+            let reply = json!({
+                "jsonrpc": "2.0", "id": id,
+                "result": {
+                    "protocolVersion": "2025-03-26",
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "StandIn", "version": "0.0.1" }
+                }
+            });
+            Response::builder()
+                .header("content-type", "application/json")
+                .header("Mcp-Session-Id", SESSION)
+                .body(Body::from(reply.to_string()))
+                .unwrap()
+        }
+        // tools/list answers over SSE, with a keep-alive comment and CRLF endings
+        "tools/list" => {
+            let reply = json!({
+                "jsonrpc": "2.0", "id": id,
+                "result": { "tools": [{
+                    "name": "echo",
+                    "description": "Echo the input",
+                    "inputSchema": { "type": "object" }
+                }]}
+            });
+            let stream = format!(": keep-alive\r\n\r\nid: 1\r\nevent: message\r\ndata: {}\r\n\r\n", reply);
+            Response::builder()
+                .header("content-type", "text/event-stream")
+                .body(Body::from(stream))
+                .unwrap()
+        }
+        "tools/call" => {
+            let text = msg["params"]["arguments"]["text"].as_str().unwrap_or("").to_string();
+            let reply = json!({
+                "jsonrpc": "2.0", "id": id,
+                "result": { "content": [{ "type": "text", "text": text }] }
+            });
+            Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from(reply.to_string()))
+                .unwrap()
+        }
+        _ => {
+            let reply = json!({
+                "jsonrpc": "2.0", "id": id,
+                "error": { "code": -32601, "message": "Method not found" }
+            });
+            Response::builder()
+                .header("content-type", "application/json")
+                .body(Body::from(reply.to_string()))
+                .unwrap()
+        }
+    }
+}
+
+async fn handle_delete(State(state): State<Shared>, headers: HeaderMap) -> StatusCode {
+    if headers.get("mcp-session-id").map(|v| v.to_str().unwrap()) == Some(SESSION) {
+        state.lock().unwrap().deleted = true;
+    }
+    StatusCode::OK
+}
+
+async fn spawn_server() -> (String, Shared) {
+    let state: Shared = Arc::default();
+    let app = Router::new()
+        .route("/mcp", post(handle_post).delete(handle_delete))
+        .with_state(state.clone());
+
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    (format!("http://{}/mcp", addr), state)
+}
+
+fn allow_all() -> SecurityConfig {
+    SecurityConfig {
+        version: "1".to_string(),
+        global_policy: "allow".to_string(),
+        rules: HashMap::new(),
+    }
+}
+
+#[tokio::test]
+async fn client_talks_to_http_server() {
+    let (url, state) = spawn_server().await;
+    let client = McpClient::new(StreamableHttpTransport::new(&url), allow_all());
+
+    client.initialize().await.unwrap();
+    assert_eq!(client.protocol_version(), Some("2025-03-26"));
+
+    let tools = client.list_tools().await.unwrap();
+    assert_eq!(tools.len(), 1);
+    assert_eq!(tools[0].name, "echo");
+
+    let result = client.call_tool("echo", json!({ "text": "hello" })).await.unwrap();
+    assert_eq!(result["content"][0]["text"], "hello");
+
+    // Every request after initialize (notifications/initialized, tools/list,
+    // tools/call) carried the session id and the negotiated version
+    let seen = state.lock().unwrap().seen_sessions.clone();
+    assert_eq!(seen, vec![Some(SESSION.to_string()); 3]);
+    let versions = state.lock().unwrap().seen_versions.clone();
+    assert_eq!(versions, vec![Some("2025-03-26".to_string()); 3]);
+}
+
+#[tokio::test]
+async fn close_deletes_the_session() {
+    let (url, state) = spawn_server().await;
+    let transport = StreamableHttpTransport::new(&url);
+
+    transport.send(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#).await.unwrap();
+    transport.receive().await.unwrap();
+    assert_eq!(transport.session_id().as_deref(), Some(SESSION));
+
+    transport.close().await.unwrap();
+    assert!(state.lock().unwrap().deleted);
+    assert!(transport.receive().await.is_err());
+}
+
+#[tokio::test]
+async fn notifications_are_accepted_without_a_body() {
+    let (url, _state) = spawn_server().await;
+    let transport = StreamableHttpTransport::new(&url);
+
+    transport.send(r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{}}"#).await.unwrap();
+    transport.receive().await.unwrap();
+
+    transport.send(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#).await.unwrap();
+}
+
+#[test]
+fn sse_parser_handles_split_chunks() {
+    let mut parser = SseParser::new();
+    assert!(parser.feed(b"data: {\"a\":").is_empty());
+    assert!(parser.feed(b"1}\r").is_empty());
+    let events = parser.feed(b"\n\r\n");
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].data, "{\"a\":1}");
+    assert_eq!(events[0].event_type(), "message");
+
+    let events = parser.feed(b"event: endpoint\ndata: /messages\nid: 7\n\n");
+    assert_eq!(events[0].event_type(), "endpoint");
+    assert_eq!(events[0].id.as_deref(), Some("7"));
+}
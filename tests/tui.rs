@@ -0,0 +1,145 @@
+// tests/tui.rs
+// The "/" prompt menu and the running-tools panel, driven through App's handlers (no terminal needed).
+use aether::protocol::{ProgressNotification, Prompt, PromptArgument};
+use aether::registry::ServerPrompts;
+use aether::tui::{builtin_command, AgentCommand, App, UiMessage};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+fn prompt(name: &str, arguments: &[(&str, bool)]) -> Prompt {
+    Prompt {
+        name: name.to_string(),
+        description: None,
+        arguments: arguments.iter()
+            .map(|(name, required)| PromptArgument { name: name.to_string(), description: None, required: *required })
+            .collect(),
+    }
+}
+
+fn app() -> (App, mpsc::UnboundedReceiver<AgentCommand>) {
+    let (tx_agent, rx_agent) = mpsc::unbounded_channel();
+    let (tx_abort, _) = mpsc::unbounded_channel();
+    let mut app = App::new(tx_agent, tx_abort);
+    app.prompts = vec![ServerPrompts {
+        server: "canned".to_string(),
+        prompts: vec![prompt("hello", &[]), prompt("review", &[("code", true), ("focus", false)])],
+    }];
+    (app, rx_agent)
+}
+
+fn type_text(app: &mut App, text: &str) {
+    for c in text.chars() {
+        app.on_key(c);
+    }
+}
+
+#[test]
+fn slash_opens_the_menu_and_filters_it() {
+    let (mut app, mut rx) = app();
+    app.on_key('/');
+    assert!(matches!(rx.try_recv(), Ok(AgentCommand::ListPrompts)));
+    assert!(app.menu_open());
+    assert_eq!(app.menu_matches().len(), 2);
+
+    type_text(&mut app, "re");
+    let names: Vec<&str> = app.menu_matches().iter().map(|(_, p)| p.name.as_str()).collect();
+    assert_eq!(names, vec!["review"]);
+
+    // Esc leaves the menu without quitting
+    app.on_escape();
+    assert!(!app.menu_open());
+    assert!(!app.should_quit);
+}
+
+#[test]
+fn prompts_without_arguments_run_right_away() {
+    let (mut app, mut rx) = app();
+    app.on_key('/');
+    app.on_enter();
+
+    let _ = rx.try_recv(); // ListPrompts
+    match rx.try_recv() {
+        Ok(AgentCommand::RunPrompt { server, name, arguments }) => {
+            assert_eq!((server.as_str(), name.as_str()), ("canned", "hello"));
+            assert!(arguments.is_empty());
+        }
+        other => panic!("expected RunPrompt, got {:?}", other),
+    }
+    assert!(matches!(app.chat_history.last(), Some(UiMessage::User(text)) if text == "/hello"));
+}
+
+#[test]
+fn arguments_are_asked_for_one_by_one() {
+    let (mut app, mut rx) = app();
+    app.on_key('/');
+    app.on_down();
+    app.on_enter();
+    assert_eq!(app.prompt_form.as_ref().unwrap().prompt.name, "review");
+
+    // Required arguments can't be skipped...
+    app.on_enter();
+    assert_eq!(app.prompt_form.as_ref().unwrap().next, 0);
+    type_text(&mut app, "fn main() {}");
+    app.on_enter();
+    // ...optional ones can
+    app.on_enter();
+    assert!(app.prompt_form.is_none());
+
+    let _ = rx.try_recv(); // ListPrompts
+    match rx.try_recv() {
+        Ok(AgentCommand::RunPrompt { name, arguments, .. }) => {
+            assert_eq!(name, "review");
+            assert_eq!(arguments.get("code").map(String::as_str), Some("fn main() {}"));
+            assert!(!arguments.contains_key("focus"));
+        }
+        other => panic!("expected RunPrompt, got {:?}", other),
+    }
+}
+
+#[test]
+fn builtin_commands_go_to_the_agent() {
+    let (mut app, mut rx) = app();
+    type_text(&mut app, "/roots add ~/My Project");
+    app.on_enter();
+    assert!(app.input.is_empty());
+    let sent: Vec<AgentCommand> = std::iter::from_fn(|| rx.try_recv().ok()).collect();
+    assert!(matches!(sent.last(), Some(AgentCommand::AddRoot(dir)) if dir == "~/My Project"), "{:?}", sent);
+
+    assert!(matches!(builtin_command("/roots"), Some(AgentCommand::ListRoots)));
+    assert!(matches!(builtin_command("/roots remove /tmp"), Some(AgentCommand::RemoveRoot(dir)) if dir == "/tmp"));
+    assert!(builtin_command("/roots add").is_none());
+    assert!(matches!(
+        builtin_command("/loglevel notes debug"),
+        Some(AgentCommand::SetLogLevel { server, level }) if server == "notes" && level == "debug"
+    ));
+    assert!(builtin_command("/loglevel notes").is_none());
+    assert!(builtin_command("/review").is_none());
+}
+
+#[test]
+fn running_tools_show_their_progress() {
+    let (mut app, _rx) = app();
+    app.on_tool_started("call_1".to_string(), "index".to_string());
+    app.on_tool_started("call_2".to_string(), "fetch".to_string());
+    assert_eq!(app.tools_running[0].label(), "index");
+
+    let progress: ProgressNotification = serde_json::from_value(json!({
+        "progressToken": 7, "progress": 3, "total": 10, "message": "Scanning"
+    })).unwrap();
+    app.on_tool_progress("call_1", progress);
+    assert_eq!(app.tools_running[0].label(), "index: 3/10 Scanning");
+    assert_eq!(app.tools_running[0].progress.as_ref().unwrap().ratio(), Some(0.3));
+
+    // No total: a count, but no ratio
+    let progress: ProgressNotification = serde_json::from_value(json!({ "progressToken": 8, "progress": 4 })).unwrap();
+    app.on_tool_progress("call_2", progress);
+    assert_eq!(app.tools_running[1].label(), "fetch: 4");
+    assert_eq!(app.tools_running[1].progress.as_ref().unwrap().ratio(), None);
+
+    // Finished calls leave the panel, and late updates for them are dropped
+    app.on_tool_finished("call_1");
+    let late: ProgressNotification = serde_json::from_value(json!({ "progressToken": 7, "progress": 10 })).unwrap();
+    app.on_tool_progress("call_1", late);
+    let ids: Vec<&str> = app.tools_running.iter().map(|a| a.id.as_str()).collect();
+    assert_eq!(ids, vec!["call_2"]);
+}